@@ -0,0 +1,112 @@
+//! Micro-benchmarks for the hot paths of the emulation core, to give optimization work a baseline.
+//!
+//! The benchmarks run a small synthetic NROM cartridge, generated on the fly, so that no ROM files are needed.
+//! Run them with `cargo bench -p nesemu-core`.
+
+use std::{hint::black_box, path::PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use nesemu_core::{bus::Bus, cpu::CPU, ppu::PPU, screen::Frame};
+
+/// A loop of common instructions, covering immediate, zero page indexed and absolute addressing as well as
+/// a taken branch and a jump
+#[rustfmt::skip]
+const PROGRAM: [u8; 17] = [
+    0xA9, 0x00,         // $8000: LDA #$00
+    0xA2, 0x10,         // $8002: LDX #$10
+    0x95, 0x00,         // $8004: STA $00,X
+    0x69, 0x03,         // $8006: ADC #$03
+    0x0D, 0x00, 0x02,   // $8008: ORA $0200
+    0xCA,               // $800B: DEX
+    0xD0, 0xF6,         // $800C: BNE $8004
+    0x4C, 0x00, 0x80,   // $800E: JMP $8000
+];
+
+/// Writes an NROM cartridge containing the benchmark program to a temporary file, returning its path
+fn write_rom() -> String {
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 1, 1];
+    rom.resize(16, 0);
+
+    let mut prg_rom = vec![0xEAu8; 16384];
+    prg_rom[..PROGRAM.len()].copy_from_slice(&PROGRAM);
+    // Reset vector, at $FFFC once the 16K of PRG ROM is mirrored into $C000
+    prg_rom[0x3FFC..0x3FFE].copy_from_slice(&0x8000u16.to_le_bytes());
+    rom.extend_from_slice(&prg_rom);
+
+    // CHR ROM filled with a pattern, so that every tile has visible pixels
+    rom.extend((0..8192).map(|i| (i * 37) as u8));
+
+    let path: PathBuf = std::env::temp_dir().join("nesemu-bench.nes");
+    std::fs::write(&path, rom).expect("Failed to write benchmark ROM");
+    path.to_string_lossy().into_owned()
+}
+
+fn cpu_benches(c: &mut Criterion, rom_path: &str) {
+    let mut bus = Bus::new(rom_path, None).unwrap();
+    let mut cpu = CPU::new(&mut bus).unwrap();
+    let mut pending_interrupt = false;
+
+    let mut group = c.benchmark_group("cpu");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("step", |b| {
+        b.iter(|| cpu.step(&mut bus, &mut pending_interrupt).unwrap())
+    });
+    group.finish();
+}
+
+/// The PPU draws a whole scanline once it has been stepped through all of the scanline's dots, so the frame
+/// benchmark mostly measures scanline drawing, which 240 of the 262 scanlines perform
+fn ppu_benches(c: &mut Criterion, rom_path: &str) {
+    let mut bus = Bus::new(rom_path, None).unwrap();
+    let mut ppu = PPU::new();
+    let mut frame = Frame::new();
+    // Fill the nametables and OAM with a spread of tiles and sprites
+    for address in 0x2000..0x2800 {
+        bus.cpu_write_byte(0x2006, (address >> 8) as u8).unwrap();
+        bus.cpu_write_byte(0x2006, address as u8).unwrap();
+        bus.cpu_write_byte(0x2007, (address * 7) as u8).unwrap();
+    }
+    for (i, byte) in bus.oam_ram.iter_mut().enumerate() {
+        *byte = (i * 13) as u8;
+    }
+
+    let mut group = c.benchmark_group("ppu");
+    group.throughput(Throughput::Elements(240));
+    group.bench_function("frame", |b| {
+        b.iter(|| while !ppu.step(&mut frame, &mut bus).unwrap() {})
+    });
+    group.finish();
+}
+
+fn bus_benches(c: &mut Criterion, rom_path: &str) {
+    let mut bus = Bus::new(rom_path, None).unwrap();
+
+    let mut group = c.benchmark_group("bus");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("read_ram", |b| {
+        b.iter(|| bus.cpu_read_byte(black_box(0x0123)).unwrap())
+    });
+    group.bench_function("read_prg_rom", |b| {
+        b.iter(|| bus.cpu_read_byte(black_box(0x8123)).unwrap())
+    });
+    group.bench_function("write_ram", |b| {
+        b.iter(|| {
+            bus.cpu_write_byte(black_box(0x0123), black_box(0x42))
+                .unwrap()
+        })
+    });
+    group.bench_function("read_nametable", |b| {
+        b.iter(|| bus.ppu_read_nametable(black_box(0x2123)).unwrap())
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let rom_path = write_rom();
+    cpu_benches(c, &rom_path);
+    ppu_benches(c, &rom_path);
+    bus_benches(c, &rom_path);
+}
+
+criterion_group!(core, benches);
+criterion_main!(core);
@@ -0,0 +1,9 @@
+//! Feeds arbitrary bytes into the ROM loader. Run with `cargo +nightly fuzz run rom_parsing` from nesemu-core.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = nesemu_core::fuzzing::load_rom(data);
+});
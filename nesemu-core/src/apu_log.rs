@@ -0,0 +1,77 @@
+//! Records every write the CPU makes to the APU's registers, along with when it was made, so that a game's music
+//! can be ripped or its sound engine studied offline. The APU itself isn't emulated yet, but the writes a game
+//! makes to it are exactly what a player or converter needs to reproduce its sound.
+//!
+//! The registers logged are $4000-$4013, $4015 and $4017. $4014 and $4016 fall in the same range, but belong to
+//! OAM DMA and the controllers rather than the APU.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Error, Write},
+    path::Path,
+};
+
+/// A single write to an APU register
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ApuWrite {
+    /// The number of frames completed since power on when the write was made
+    pub frame: u64,
+    /// The number of CPU cycles run since power on, as of the start of the instruction that made the write
+    pub cycle: u64,
+    pub address: u16,
+    pub value: u8,
+}
+
+/// Checks whether a CPU address is one of the APU's registers
+pub(crate) fn is_apu_register(address: usize) -> bool {
+    matches!(address, 0x4000..=0x4013 | 0x4015 | 0x4017)
+}
+
+#[derive(Default)]
+pub struct ApuLog {
+    enabled: bool,
+    writes: Vec<ApuWrite>,
+}
+
+impl ApuLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts or stops recording. What was recorded so far is kept either way
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Forgets everything that was recorded
+    pub fn clear(&mut self) {
+        self.writes.clear();
+    }
+
+    pub(crate) fn record(&mut self, write: ApuWrite) {
+        self.writes.push(write);
+    }
+
+    /// Gets every write recorded so far, in the order they were made
+    pub fn writes(&self) -> &[ApuWrite] {
+        &self.writes
+    }
+
+    /// Writes the log to a text file, with the frame, cycle, register and value of one write on each line
+    pub fn export(&self, path: &Path) -> Result<(), Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "; frame cycle register value")?;
+        for write in &self.writes {
+            writeln!(
+                file,
+                "{} {} ${:04X} ${:02X}",
+                write.frame, write.cycle, write.address, write.value
+            )?;
+        }
+        file.flush()
+    }
+}
@@ -0,0 +1,131 @@
+//! ROM collections are usually stored compressed, with one or more ROMs to a zip or 7z archive. Rather than
+//! extracting them by hand, a ROM can be loaded straight from an archive.
+//!
+//! A ROM inside an archive is named by the path of the archive, followed by `#` and the name of the entry, such
+//! as `collection.zip#Game.nes`. This way it can be passed around, saved to the recent ROMs list and loaded
+//! again like any other path. Giving only the path of the archive loads the first `.nes` entry in it.
+
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Read},
+    path::Path,
+};
+
+use sevenz_rust2::{ArchiveReader, Password};
+use zip::ZipArchive;
+
+/// Separates the path of an archive from the name of the entry inside it
+pub const ENTRY_SEPARATOR: char = '#';
+
+/// Whether the given path names a zip or 7z archive, or an entry inside one
+pub fn is_archive(rom_path: &str) -> bool {
+    archive_kind(split_path(rom_path).0).is_some()
+}
+
+/// Joins the path of an archive and the name of an entry inside it into a path that can be loaded
+pub fn entry_path(archive_path: &str, entry: &str) -> String {
+    format!("{}{}{}", archive_path, ENTRY_SEPARATOR, entry)
+}
+
+/// Lists the names of the `.nes` entries in an archive, in the order they are stored
+pub fn list_roms(archive_path: &str) -> Result<Vec<String>, Error> {
+    let mut names: Vec<String> = match archive_kind(archive_path) {
+        Some(ArchiveKind::Zip) => {
+            let archive = ZipArchive::new(File::open(archive_path)?).map_err(invalid)?;
+            archive
+                .file_names()
+                .map(|name| name.map(String::from))
+                .collect::<Result<_, _>>()
+                .map_err(invalid)?
+        }
+        Some(ArchiveKind::SevenZip) => {
+            let reader = ArchiveReader::open(archive_path, Password::empty()).map_err(invalid)?;
+            reader
+                .archive()
+                .files
+                .iter()
+                .filter(|entry| !entry.is_directory())
+                .map(|entry| entry.name().to_string())
+                .collect()
+        }
+        None => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Not a zip or 7z archive",
+            ))
+        }
+    };
+    names.retain(|name| is_rom_name(name));
+    Ok(names)
+}
+
+/// Reads the ROM at the given path, extracting it first if the path names an archive or an entry inside one
+pub fn read_rom(rom_path: &str) -> Result<Vec<u8>, Error> {
+    let (archive_path, entry) = split_path(rom_path);
+    let Some(kind) = archive_kind(archive_path) else {
+        return std::fs::read(rom_path);
+    };
+    let entry = match entry {
+        Some(entry) => entry.to_string(),
+        None => list_roms(archive_path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "Archive holds no .nes ROMs"))?,
+    };
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(File::open(archive_path)?).map_err(invalid)?;
+            let mut file = archive.by_name(&entry).map_err(not_found)?;
+            let mut rom = Vec::new();
+            file.read_to_end(&mut rom)?;
+            Ok(rom)
+        }
+        ArchiveKind::SevenZip => {
+            let mut reader =
+                ArchiveReader::open(archive_path, Password::empty()).map_err(invalid)?;
+            reader.read_file(&entry).map_err(not_found)
+        }
+    }
+}
+
+enum ArchiveKind {
+    Zip,
+    SevenZip,
+}
+
+fn archive_kind(path: &str) -> Option<ArchiveKind> {
+    let extension = Path::new(path)
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    match extension.as_str() {
+        "zip" => Some(ArchiveKind::Zip),
+        "7z" => Some(ArchiveKind::SevenZip),
+        _ => None,
+    }
+}
+
+/// Splits a path into the path of the archive and the name of the entry inside it. Paths that exist on disk
+/// as they are are never split, so that ROMs with a `#` in their name still load
+fn split_path(rom_path: &str) -> (&str, Option<&str>) {
+    if Path::new(rom_path).exists() {
+        return (rom_path, None);
+    }
+    match rom_path.rsplit_once(ENTRY_SEPARATOR) {
+        Some((archive_path, entry)) => (archive_path, Some(entry)),
+        None => (rom_path, None),
+    }
+}
+
+fn is_rom_name(name: &str) -> bool {
+    name.to_lowercase().ends_with(".nes")
+}
+
+fn invalid(error: impl std::fmt::Display) -> Error {
+    Error::new(ErrorKind::InvalidData, error.to_string())
+}
+
+fn not_found(error: impl std::fmt::Display) -> Error {
+    Error::new(ErrorKind::NotFound, error.to_string())
+}
@@ -0,0 +1,49 @@
+//! Mixes the console's sound down to samples at a fixed rate, for a frontend to play or record. The APU isn't
+//! emulated yet, so for now the only sound comes from cartridges with expansion audio, such as VRC7's FM channels.
+//!
+//! Mixing is off until a frontend enables it, so that samples don't pile up when nothing takes them.
+
+#[derive(Default)]
+pub struct Audio {
+    enabled: bool,
+    /// How far the mixer is towards its next sample, in samples
+    phase: f64,
+    samples: Vec<f32>,
+}
+
+impl Audio {
+    pub const SAMPLE_RATE: u32 = 44_100;
+    /// The NTSC CPU runs at the 21.477272MHz master clock divided by 12
+    const CPU_CLOCK_RATE: f64 = 21_477_272.0 / 12.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts or stops mixing. Samples that haven't been taken yet are kept either way
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Takes the samples mixed since the last call, mono, at [`Audio::SAMPLE_RATE`] and between -1 and 1
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// Advances the mixer by the given number of CPU cycles, during which the cartridge's expansion audio was at
+    /// the given level
+    pub(crate) fn clock(&mut self, cycles: u16, expansion: f32) {
+        if !self.enabled {
+            return;
+        }
+        self.phase += cycles as f64 * Audio::SAMPLE_RATE as f64 / Audio::CPU_CLOCK_RATE;
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+            self.samples.push(expansion);
+        }
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use bitfield::{Bit, BitRangeMut};
+use tock_registers::interfaces::{ReadWriteable, Readable, Writeable};
+
+use super::{
+    apu_log::is_apu_register,
+    cheats::CheatEngine,
+    controller::{Controller, ControllerType, Zapper},
+    debugger::{register_address, Access},
+    mappers::{new_mapper, new_mapper_from_bytes, A12Filter, Banks, Mapper, MirrorMode},
+    ppu::{
+        palette_memory::PaletteMemory,
+        ppu_registers::{PPURegisters, PPUCTRL, PPUSTATUS},
+        tile_cache::{TileCache, TileRow},
+    },
+    ram_map::RamMap,
+    rom_db::RomInfo,
+};
+
+pub struct Bus {
+    mapper: Box<dyn Mapper>,
+    rom_info: RomInfo,
+    /// Where the cartridge's battery backed RAM or EEPROM is saved, unless the ROM didn't come from a file
+    save_path: Option<PathBuf>,
+    /// The contents of the save file as of when it was last read or written, to tell whether it is out of date
+    saved_data: Vec<u8>,
+    cpu_ram: [u8; 2048],
+    ppu_ram: [u8; 2048], // TODO: Certain mappers can reroute this memory
+    pub oam_ram: [u8; 256],
+    oam_addr: u8,
+    pending_dma: bool,
+    dma_page_addr: usize,
+    ppu_registers: PPURegisters,
+    pub palette_memory: PaletteMemory,
+    tile_cache: TileCache,
+    pub controller: Controller,
+    pub controller_two: Controller,
+    pub zapper: Zapper,
+    pub cheats: CheatEngine,
+    pub ram_map: RamMap,
+    /// Whether to log every write to the cartridge, along with the state of the mapper after it
+    pub log_mapper_writes: bool,
+    /// The address of the last write to the cartridge, until it is taken by [`Bus::take_mapper_write`]
+    last_mapper_write: Option<usize>,
+    /// Writes to the APU's registers since they were last taken by [`Bus::take_apu_writes`]
+    apu_writes: Vec<(u16, u8)>,
+    /// Whether to record accesses to the PPU, APU and I/O registers, for the debugger's register breakpoints
+    pub(crate) watch_registers: bool,
+    /// Accesses to registers since they were last taken by [`Bus::take_register_accesses`], while watching them
+    register_accesses: Vec<(usize, Access)>,
+    /// Whether to record every write the CPU makes, for the memory write hooks
+    pub(crate) record_writes: bool,
+    /// Writes the CPU made since they were last taken by [`Bus::take_writes`], while recording them
+    writes: Vec<(usize, u8)>,
+    a12_filter: A12Filter,
+    /// How many dots the PPU had run for since power on when it last caught up with the CPU
+    ppu_dot: u64,
+}
+
+impl Bus {
+    /// The controller ports only drive the low 5 bits of the data bus, so the upper bits read back whatever was
+    /// last on it. That is the high byte of the port's address, from the operand of the instruction reading it
+    const CONTROLLER_OPEN_BUS: u8 = 0x40;
+    const NO_PATTERN: &'static str = "Pattern read from outside the cartridge's CHR memory";
+
+    /// Connects the cartridge in the given ROM file, with the given IPS or BPS patch applied to it
+    pub fn new(rom_path: &str, patch_path: Option<&str>) -> Result<Self, Error> {
+        let (mut mapper, rom_info) = new_mapper(rom_path, patch_path)?;
+        let save_path = Path::new(rom_path).with_extension("sav");
+        let saved_data = match std::fs::read(&save_path) {
+            Ok(data) => {
+                mapper.load_save_data(&data);
+                data
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                log::warn!("Failed to read save file {}: {err}", save_path.display());
+                Vec::new()
+            }
+        };
+        let mut bus = Bus::with_mapper(mapper, rom_info);
+        bus.save_path = Some(save_path);
+        bus.saved_data = saved_data;
+        bus.cheats = CheatEngine::load(rom_path);
+        Ok(bus)
+    }
+
+    /// Connects the cartridge in a ROM image that is already in memory. Its battery backed RAM and cheats are
+    /// never saved, since there is no file to save them next to
+    pub fn from_bytes(rom: &[u8]) -> Result<Self, Error> {
+        let (mapper, rom_info) = new_mapper_from_bytes(rom)?;
+        Ok(Bus::with_mapper(mapper, rom_info))
+    }
+
+    fn with_mapper(mapper: Box<dyn Mapper>, rom_info: RomInfo) -> Self {
+        let mut zapper = Zapper::new();
+        zapper.connected = rom_info.controller == ControllerType::Zapper;
+        Self {
+            mapper,
+            rom_info,
+            save_path: None,
+            saved_data: Vec::new(),
+            cpu_ram: [0u8; 2048], // Real RAM starts in an uninit state, but rust
+            // makes us init it
+            ppu_ram: [0u8; 2048],
+            oam_ram: [0u8; 256],
+            oam_addr: 0,
+            pending_dma: false,
+            dma_page_addr: 0,
+            ppu_registers: PPURegisters::default(),
+            palette_memory: PaletteMemory::new(),
+            tile_cache: TileCache::new(),
+            controller: Controller::new(),
+            controller_two: Controller::new(),
+            zapper,
+            cheats: CheatEngine::new(),
+            ram_map: RamMap::new(),
+            log_mapper_writes: false,
+            last_mapper_write: None,
+            apu_writes: Vec::new(),
+            watch_registers: false,
+            register_accesses: Vec::new(),
+            record_writes: false,
+            writes: Vec::new(),
+            a12_filter: A12Filter::new(),
+            ppu_dot: 0,
+        }
+    }
+}
+
+impl Bus {
+    /// Returns the bus to the state it would be in after the console's Reset button is pressed
+    ///
+    /// Unlike a power cycle, the contents of CPU RAM, PPU RAM and OAM are preserved
+    pub fn reset(&mut self) {
+        // TODO: Silence the APU by clearing $4015, once it is emulated
+        self.ppu_registers.reset();
+        self.pending_dma = false;
+    }
+
+    /// Writes the cartridge's battery backed RAM or EEPROM to the save file next to the ROM, so that it is
+    /// restored the next time the ROM is loaded. Does nothing for cartridges that don't keep any data or weren't
+    /// loaded from a file, or if the data hasn't changed since the save file was last read or written, so this is
+    /// cheap to call regularly
+    ///
+    /// The data is written to a temporary file which then replaces the save file, so that a crash or power loss
+    /// partway through leaves the old save intact rather than a truncated one
+    pub fn save(&mut self) -> Result<(), Error> {
+        let (Some(data), Some(save_path)) = (self.mapper.save_data(), &self.save_path) else {
+            return Ok(());
+        };
+        if data == self.saved_data {
+            return Ok(());
+        }
+        let temp_path = save_path.with_extension("sav.tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, save_path)?;
+        self.saved_data = data.to_vec();
+        Ok(())
+    }
+
+    /// What is known about the ROM in the cartridge, such as its title and whether it is a good dump
+    pub fn rom_info(&self) -> &RomInfo {
+        &self.rom_info
+    }
+
+    /// Whether the cartridge is requesting an interrupt
+    pub fn irq_pending(&self) -> bool {
+        self.mapper.irq_pending()
+    }
+
+    /// Lets the cartridge know that the CPU ran for the given number of cycles
+    pub fn clock_mapper(&mut self, cycles: u16) {
+        self.mapper.clock_cpu(cycles);
+    }
+
+    /// Gets the current level of the cartridge's expansion audio, between -1 and 1
+    pub fn expansion_audio(&self) -> f32 {
+        self.mapper.audio_output()
+    }
+
+    pub fn cpu_ram(&self) -> &[u8] {
+        &self.cpu_ram
+    }
+
+    /// Gets the console's 2KB of nametable RAM, which holds two nametables and their attributes
+    pub fn ppu_ram(&self) -> &[u8] {
+        &self.ppu_ram
+    }
+
+    pub fn pending_dma(&self) -> bool {
+        self.pending_dma
+    }
+
+    /// Copies the page written to $4014 into OAM through $2004
+    ///
+    /// The page is read over the CPU bus like any other read, so it can come from anywhere the CPU can see, such
+    /// as a sprite buffer kept in PRG RAM or sprite data in ROM
+    pub fn process_dma(&mut self) -> Result<(), &'static str> {
+        self.pending_dma = false;
+        for addr in self.dma_page_addr..self.dma_page_addr + 0x100 {
+            let value = self.cpu_read_byte(addr)?;
+            self.cpu_write_ppu_register(0x2004, value)?;
+        }
+        Ok(())
+    }
+
+    pub fn cpu_read_byte(&mut self, address: usize) -> Result<u8, &'static str> {
+        if let 0x4000..=0x4017 = address {
+            self.record_register_access(address, Access::Read);
+        }
+        match address {
+            (0..=0x1FFF) => Ok(self.cpu_ram[address % 0x0800]),
+            (0x2000..=0x3FFF) => self.cpu_read_ppu_register(address, true),
+            (0x4000..=0x4015) => Ok(0x0), // TODO: APU
+            0x4016 => Ok(Bus::CONTROLLER_OPEN_BUS | self.controller.read_from_controller()),
+            0x4017 if self.zapper.connected => {
+                Ok(Bus::CONTROLLER_OPEN_BUS | self.zapper.read(self.ppu_dot))
+            }
+            0x4017 => Ok(Bus::CONTROLLER_OPEN_BUS | self.controller_two.read_from_controller()),
+            (0x8000..=0xFFFF) => Ok(self.cheats.apply(address, self.mapper.prg_read(address)?)),
+            (0x4020..=0x7FFF) => self.mapper.prg_read(address),
+            _ => Err("Bad address read on Bus"),
+        }
+    }
+
+    // Sometimes reading from the CPU bus can cause side effects, eg with PPU registers
+    // We need a way to query memory while ensuring this doesn't happen
+    pub fn cpu_read_byte_no_modify(&mut self, address: usize) -> Result<u8, &'static str> {
+        match address {
+            (0..=0x1FFF) => Ok(self.cpu_ram[address % 0x0800]),
+            (0x2000..=0x3FFF) => self.cpu_read_ppu_register(address, false),
+            (0x4000..=0x4017) => Ok(0x0), // TODO: APU
+            // TODO: Controller
+            (0x8000..=0xFFFF) => Ok(self.cheats.apply(address, self.mapper.prg_read(address)?)),
+            (0x4020..=0x7FFF) => self.mapper.prg_read(address),
+            _ => Err("Bad address read on Bus"),
+        }
+    }
+
+    /// Gets the banks the cartridge currently has selected
+    pub(crate) fn banks(&self) -> Banks {
+        self.mapper.banks()
+    }
+
+    /// Translates a CPU address into an offset into PRG ROM, if the cartridge maps PRG ROM there
+    pub(crate) fn prg_rom_offset(&self, address: usize) -> Option<usize> {
+        self.mapper.prg_rom_offset(address)
+    }
+
+    /// Gets the address of the last write to the cartridge since the last call, if there was one
+    pub(crate) fn take_mapper_write(&mut self) -> Option<usize> {
+        self.last_mapper_write.take()
+    }
+
+    /// Takes the writes to the APU's registers made since the last call, in the order they were made
+    pub(crate) fn take_apu_writes(&mut self) -> std::vec::Drain<'_, (u16, u8)> {
+        self.apu_writes.drain(..)
+    }
+
+    /// Takes the accesses to registers made since the last call, in the order they were made
+    pub(crate) fn take_register_accesses(&mut self) -> std::vec::Drain<'_, (usize, Access)> {
+        self.register_accesses.drain(..)
+    }
+
+    /// Takes the writes the CPU made since the last call, in the order they were made
+    pub(crate) fn take_writes(&mut self) -> std::vec::Drain<'_, (usize, u8)> {
+        self.writes.drain(..)
+    }
+
+    fn record_register_access(&mut self, address: usize, access: Access) {
+        if self.watch_registers {
+            self.register_accesses
+                .push((register_address(address), access));
+        }
+    }
+
+    /// Puts an address on the PPU address bus as of the given PPU dot, counted since power on, letting the
+    /// cartridge know when A12 rises
+    pub fn ppu_address_bus(&mut self, address: u16, dot: u64) {
+        if self.a12_filter.update(address, dot) {
+            self.mapper.ppu_a12_rise();
+        }
+    }
+
+    /// Records how many dots the PPU has run for since power on, so that the CPU moving the VRAM address can be
+    /// placed in time
+    pub fn set_ppu_dot(&mut self, dot: u64) {
+        self.ppu_dot = dot;
+    }
+
+    pub fn cpu_read_exact(&mut self, address: usize, buf: &mut [u8]) -> Result<(), &'static str> {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte = self.cpu_read_byte(address + i)?;
+        }
+        Ok(())
+    }
+
+    pub fn cpu_write_byte(&mut self, address: usize, value: u8) -> Result<(), &'static str> {
+        if is_apu_register(address) {
+            self.apu_writes.push((address as u16, value));
+        }
+        if let 0x4000..=0x4017 = address {
+            self.record_register_access(address, Access::Write);
+        }
+        if self.record_writes {
+            self.writes.push((address, value));
+        }
+        match address {
+            (0..=0x1FFF) => Ok(self.cpu_ram[address % 0x0800] = value),
+            (0x4000..=0x4013) => Ok(()), // TODO: APU
+            0x4014 => {
+                self.dma_page_addr = (value as usize) << 8;
+                Ok(self.pending_dma = true)
+            }
+            0x4015 => Ok(()), // TODO: APU?
+            // Both controllers share the same strobe line
+            0x4016 => {
+                self.controller.write_to_controller(value.bit(0));
+                Ok(self.controller_two.write_to_controller(value.bit(0)))
+            }
+            0x4017 => Ok(()), // Currently not supported
+            (0x2000..=0x3FFF) => self.cpu_write_ppu_register(address, value),
+            (0x4020..=0xFFFF) => self.mapper_write(address, value),
+            _ => Err("Bad address write on Bus"),
+        }
+    }
+
+    fn mapper_write(&mut self, address: usize, value: u8) -> Result<(), &'static str> {
+        // Some boards, like Bandai's FCG, have registers below $8000 too, so any write may switch CHR banks
+        let chr_banks = self.mapper.banks().chr;
+        self.mapper.prg_write(address, value)?;
+        if self.mapper.banks().chr != chr_banks {
+            self.tile_cache.invalidate_all();
+        }
+        self.last_mapper_write = Some(address);
+        if self.log_mapper_writes {
+            log::info!(
+                "Mapper write ${:04X} = ${:02X}: {}",
+                address,
+                value,
+                self.mapper.describe_state()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn cpu_read_ppu_register(
+        &mut self,
+        address: usize,
+        modify: bool,
+    ) -> Result<u8, &'static str> {
+        // Reads that don't modify anything are the debugger looking, rather than the CPU
+        if modify {
+            self.record_register_access(address, Access::Read);
+        }
+        // The eight registers are mirrored all the way up to 0x3FFF
+        match 0x2000 + address % 8 {
+            0x2000 => Ok(self.ppu_registers.ppuctrl.get()),
+            0x2001 => Ok(self.ppu_registers.ppumask.get()),
+            0x2002 => {
+                let val = self.ppu_registers.ppustatus.get();
+                if modify {
+                    self.ppu_registers
+                        .ppustatus
+                        .modify(PPUSTATUS::VBLANK::CLEAR); // Clear VBLANK
+                    self.ppu_registers.write_latch = false; // Clear write latch
+                }
+                Ok(val)
+            }
+            0x2003 => Ok(self.oam_addr),
+            0x2004 => Ok(self.oam_ram[self.oam_addr as usize]),
+            0x2005 => Ok(0x0), // TODO
+            0x2006 => Ok(0x0), // Write only
+            // Peeking at PPUDATA must not advance the VRAM pointer or refill the read buffer
+            0x2007 if !modify => Ok(self.ppu_registers.ppudata),
+            0x2007 => {
+                let final_res = match self.ppu_registers.ppuaddr {
+                    (0..=0x1FFF) => {
+                        // Buffered read
+                        let res = self.ppu_registers.ppudata;
+                        // Then fetch new data
+                        self.ppu_registers.ppudata =
+                            self.mapper.chr_read(self.ppu_registers.ppuaddr as usize)?;
+                        Ok(res)
+                    }
+                    (0x2000..=0x2FFF) => {
+                        // Buffered read
+                        let res = self.ppu_registers.ppudata;
+                        // Then fetch new data
+                        self.ppu_registers.ppudata =
+                            self.ppu_ram[self.translate_nametable_addr(self.ppu_registers.ppuaddr)];
+                        Ok(res)
+                    }
+                    (0x3F00..=0x3FFF) => {
+                        // No buffered read
+                        Ok(self
+                            .palette_memory
+                            .get_entry(0x3F00 | (self.ppu_registers.ppuaddr as usize % 0x20)))
+                    }
+                    _ => return Err("Bad read from PPU Bus by CPU"),
+                };
+
+                self.ppu_increment_vram_ptr();
+                final_res
+            }
+            _ => Err("Bad Read on PPU register"),
+        }
+    }
+
+    pub fn cpu_write_ppu_register(
+        &mut self,
+        address: usize,
+        value: u8,
+    ) -> Result<(), &'static str> {
+        self.record_register_access(address, Access::Write);
+        // The eight registers are mirrored all the way up to 0x3FFF
+        let address = 0x2000 + address % 8;
+        if self.ppu_registers.writes_ignored && matches!(address, 0x2000 | 0x2001 | 0x2005 | 0x2006)
+        {
+            return Ok(());
+        }
+        match address {
+            0x2000 => Ok(self.ppu_registers.ppuctrl.set(value)),
+            0x2001 => Ok(self.ppu_registers.ppumask.set(value)),
+            0x2002 => Ok(()),                    // Read only
+            0x2003 => Ok(self.oam_addr = value), // TODO: Needs to be set to 0 during vblank (?)
+            0x2004 => {
+                self.oam_ram[self.oam_addr as usize] = value;
+                Ok(self.oam_addr = self.oam_addr.wrapping_add(1))
+            }
+            0x2005 => {
+                if !self.ppu_registers.write_latch {
+                    self.ppu_registers.fine_x = value;
+                    self.ppu_registers.write_latch = true;
+                } else {
+                    self.ppu_registers.fine_y = value;
+                    self.ppu_registers.write_latch = false;
+                }
+                Ok(())
+            }
+            0x2006 => {
+                if !self.ppu_registers.write_latch {
+                    self.ppu_registers.ppuaddr.set_bit_range(15, 8, value);
+                    self.ppu_registers.write_latch = true;
+                } else {
+                    self.ppu_registers.ppuaddr.set_bit_range(7, 0, value);
+                    self.ppu_registers.write_latch = false;
+                    // Addresses higher than 0x3FFF get mirrored
+                    self.ppu_registers.ppuaddr %= 0x4000;
+                    // Outside of rendering, the VRAM address is what's on the PPU address bus
+                    self.ppu_address_bus(self.ppu_registers.ppuaddr, self.ppu_dot);
+                }
+                Ok(())
+            }
+            0x2007 => {
+                match self.ppu_registers.ppuaddr {
+                    (0x0000..=0x1FFF) => {
+                        self.mapper
+                            .chr_write(self.ppu_registers.ppuaddr as usize, value)?;
+                        self.tile_cache
+                            .invalidate(self.ppu_registers.ppuaddr as usize);
+                    }
+                    (0x2000..=0x2FFF) => {
+                        self.ppu_ram[self.translate_nametable_addr(self.ppu_registers.ppuaddr)] =
+                            value;
+                    }
+                    (0x3000..=0x3EFF) => {
+                        let addr_mirrored = self.ppu_registers.ppuaddr - 0x1000;
+                        self.ppu_ram[self.translate_nametable_addr(addr_mirrored)] = value;
+                    }
+                    (0x3F00..=0x3FFF) => {
+                        self.palette_memory.set_entry(
+                            0x3F00 | (self.ppu_registers.ppuaddr as usize % 0x20),
+                            value,
+                        );
+                    }
+                    _ => return Err("Bad write to PPU Bus by CPU"),
+                };
+
+                self.ppu_increment_vram_ptr();
+
+                Ok(())
+            }
+            _ => Err("Bad Write on PPU Register"),
+        }
+    }
+
+    fn ppu_increment_vram_ptr(&mut self) {
+        if self.ppu_registers.ppuctrl.is_set(PPUCTRL::VRAM_INC) {
+            self.ppu_registers.ppuaddr = (self.ppu_registers.ppuaddr + 32) % 0x4000;
+        } else {
+            self.ppu_registers.ppuaddr = (self.ppu_registers.ppuaddr + 1) % 0x4000;
+        }
+        self.ppu_address_bus(self.ppu_registers.ppuaddr, self.ppu_dot);
+    }
+
+    /// Gets the 16 bytes of a pattern entry
+    ///
+    /// Fails if the cartridge has no CHR memory there
+    pub fn ppu_get_pattern_entry(
+        &self,
+        pattern_idx: u8,
+        background: bool,
+    ) -> Result<&[u8], &'static str> {
+        self.mapper
+            .chr_read_pattern(self.ppu_pattern_table_addr(background), pattern_idx)
+            .ok_or(Bus::NO_PATTERN)
+    }
+
+    /// Gets a single row of a pattern entry, decoded into the palette index of each pixel from left to right
+    ///
+    /// Fails if the cartridge has no CHR memory there
+    pub fn ppu_get_pattern_row(
+        &mut self,
+        pattern_idx: u8,
+        background: bool,
+        row: u8,
+    ) -> Result<TileRow, &'static str> {
+        let base_addr = self.ppu_pattern_table_addr(background);
+        let mapper = &self.mapper;
+        self.tile_cache
+            .get_row(base_addr + pattern_idx as usize * 16, row, || {
+                mapper
+                    .chr_read_pattern(base_addr, pattern_idx)
+                    .and_then(|pattern| pattern.try_into().ok())
+                    .ok_or(Bus::NO_PATTERN)
+            })
+    }
+
+    fn ppu_pattern_table_addr(&self, background: bool) -> usize {
+        match background {
+            true => {
+                if self.ppu_registers.ppuctrl.is_set(PPUCTRL::BPTNTABLE_ADDR) {
+                    0x1000
+                } else {
+                    0x0000
+                }
+            }
+            false => {
+                if self.ppu_registers.ppuctrl.is_set(PPUCTRL::SPTNTABLE_ADDR) {
+                    0x1000
+                } else {
+                    0x0000
+                }
+            }
+        }
+    }
+
+    pub fn ppu_get_registers_mut(&mut self) -> &mut PPURegisters {
+        &mut self.ppu_registers
+    }
+
+    pub fn ppu_get_registers(&self) -> &PPURegisters {
+        &self.ppu_registers
+    }
+
+    pub fn ppu_read_nametable(&self, addr: usize) -> Result<u8, &'static str> {
+        if !(0x2000..0x3000).contains(&addr) {
+            Err("Invalid address lookup into nametable")
+        } else {
+            Ok(self.ppu_ram[self.translate_nametable_addr(addr as u16)])
+        }
+    }
+
+    pub fn ppu_get_nametable_base_addr(&self) -> usize {
+        let name_table_addr: PPUCTRL::NTABLE_ADDR::Value = self
+            .ppu_registers
+            .ppuctrl
+            .read_as_enum(PPUCTRL::NTABLE_ADDR)
+            .unwrap();
+        match name_table_addr {
+            PPUCTRL::NTABLE_ADDR::Value::Addr2000 => 0x2000,
+            PPUCTRL::NTABLE_ADDR::Value::Addr2400 => 0x2400,
+            PPUCTRL::NTABLE_ADDR::Value::Addr2800 => 0x2800,
+            PPUCTRL::NTABLE_ADDR::Value::Addr2C00 => 0x2C00,
+        }
+    }
+
+    /// Translates an address in one of the four nametables at $2000-$2FFF into an offset into PPU RAM, which
+    /// only has room for two of them
+    pub fn translate_nametable_addr(&self, addr: u16) -> usize {
+        let nametable = (addr as usize >> 10) & 3;
+        let offset = addr as usize % 0x400;
+        let page = match self.mapper.current_mirroring_mode() {
+            // $2000 and $2800 share the first page, and $2400 and $2C00 the second
+            MirrorMode::VERT => nametable & 1,
+            // $2000 and $2400 share the first page, and $2800 and $2C00 the second
+            MirrorMode::HORZ => nametable >> 1,
+            MirrorMode::LOWER => 0,
+            MirrorMode::UPPER => 1,
+        };
+        page * 0x400 + offset
+    }
+}
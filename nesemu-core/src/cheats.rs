@@ -0,0 +1,151 @@
+//! The Game Genie was a cheat cartridge that sat between the console and the game cartridge. It intercepted
+//! reads from PRG ROM, substituting a different value for up to three addresses. Codes are entered as a
+//! string of 6 or 8 letters, which encode the address, the new value and, for 8 letter codes, a compare value
+//! that must match the original ROM value before the substitution is made.
+
+use std::{
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+const ALPHABET: &str = "APZLGITYEOXUKSVN";
+
+pub struct GameGenieCode {
+    pub address: usize,
+    pub value: u8,
+    pub compare: Option<u8>,
+}
+
+impl GameGenieCode {
+    /// Decodes a 6 or 8 letter Game Genie code
+    ///
+    /// Fails if the code is the wrong length or contains letters which are not part of the Game Genie alphabet
+    pub fn decode(code: &str) -> Result<Self, &'static str> {
+        let mut n = [0u16; 8];
+        let letters = code.trim().to_ascii_uppercase();
+        if letters.len() != 6 && letters.len() != 8 {
+            return Err("Game Genie codes must be 6 or 8 letters long");
+        }
+        for (i, letter) in letters.chars().enumerate() {
+            n[i] = ALPHABET
+                .find(letter)
+                .ok_or("Game Genie code contains an invalid letter")? as u16;
+        }
+
+        // The bits of each field are scattered across the letters of the code
+        let address = 0x8000
+            + (((n[3] & 7) << 12)
+                | ((n[5] & 7) << 8)
+                | ((n[4] & 8) << 8)
+                | ((n[2] & 7) << 4)
+                | ((n[1] & 8) << 4)
+                | (n[4] & 7)
+                | (n[3] & 8)) as usize;
+        let value_high = ((n[1] & 7) << 4) | ((n[0] & 8) << 4) | (n[0] & 7);
+        if letters.len() == 6 {
+            Ok(Self {
+                address,
+                value: (value_high | (n[5] & 8)) as u8,
+                compare: None,
+            })
+        } else {
+            Ok(Self {
+                address,
+                value: (value_high | (n[7] & 8)) as u8,
+                compare: Some(
+                    (((n[7] & 7) << 4) | ((n[6] & 8) << 4) | (n[6] & 7) | (n[5] & 8)) as u8,
+                ),
+            })
+        }
+    }
+}
+
+pub struct Cheat {
+    pub name: String,
+    pub code: String,
+    pub enabled: bool,
+    decoded: GameGenieCode,
+}
+
+/// Stores the cheats for the currently loaded game, and applies the enabled ones to reads from PRG ROM
+///
+/// Cheats are persisted to a file alongside the ROM, so they are remembered the next time the game is loaded
+#[derive(Default)]
+pub struct CheatEngine {
+    cheats: Vec<Cheat>,
+    /// Where the cheats are saved, unless the ROM didn't come from a file
+    path: Option<PathBuf>,
+}
+
+impl CheatEngine {
+    /// Creates an empty set of cheats that is never saved, for a ROM that didn't come from a file
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the cheats previously saved for the given ROM. A missing or malformed cheat file is ignored
+    pub fn load(rom_path: &str) -> Self {
+        let path = Path::new(rom_path).with_extension("cht");
+        let contents = fs::read_to_string(&path);
+        let mut this = Self {
+            cheats: Vec::new(),
+            path: Some(path),
+        };
+
+        if let Ok(contents) = contents {
+            for line in contents.lines() {
+                // Each line is of the form CODE<tab>ENABLED<tab>NAME
+                let mut fields = line.splitn(3, '\t');
+                if let (Some(code), Some(enabled), Some(name)) =
+                    (fields.next(), fields.next(), fields.next())
+                {
+                    let _ = this.add(name, code, enabled == "1");
+                }
+            }
+        }
+
+        this
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents: String = self
+            .cheats
+            .iter()
+            .map(|cheat| format!("{}\t{}\t{}\n", cheat.code, cheat.enabled as u8, cheat.name))
+            .collect();
+        fs::write(path, contents)
+    }
+
+    /// Adds a new Game Genie cheat. Fails if the code cannot be decoded
+    pub fn add(&mut self, name: &str, code: &str, enabled: bool) -> Result<(), &'static str> {
+        let decoded = GameGenieCode::decode(code)?;
+        self.cheats.push(Cheat {
+            name: name.trim().to_string(),
+            code: code.trim().to_ascii_uppercase(),
+            enabled,
+            decoded,
+        });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        self.cheats.remove(idx);
+    }
+
+    pub fn cheats_mut(&mut self) -> &mut [Cheat] {
+        &mut self.cheats
+    }
+
+    /// Substitutes the value read from PRG ROM at the given address if an enabled cheat applies to it
+    pub fn apply(&self, address: usize, value: u8) -> u8 {
+        self.cheats
+            .iter()
+            .filter(|cheat| cheat.enabled && cheat.decoded.address == address)
+            .find(|cheat| cheat.decoded.compare.is_none_or(|cmp| cmp == value))
+            .map_or(value, |cheat| cheat.decoded.value)
+    }
+}
@@ -0,0 +1,161 @@
+use bitfield::Bit;
+
+use super::screen::{Color, FrameBuffer};
+
+pub struct InputEvent {
+    pub input_state: u8,
+}
+
+impl InputEvent {
+    pub const A: u8 = 0;
+    pub const B: u8 = 1;
+    pub const SELECT: u8 = 2;
+    pub const START: u8 = 3;
+    pub const UP: u8 = 4;
+    pub const DOWN: u8 = 5;
+    pub const LEFT: u8 = 6;
+    pub const RIGHT: u8 = 7;
+    pub const END: u8 = 8;
+}
+
+/// The kind of controller a game expects to be plugged in. Only the standard controller and the Zapper are
+/// emulated so far, but frontends can use this to tell the player when a game needs something else
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ControllerType {
+    #[default]
+    Standard,
+    Zapper,
+    PowerPad,
+    Arkanoid,
+}
+
+/// A standard controller, which reports its buttons one at a time through an 8 bit shift register
+pub struct Controller {
+    /// While the strobe is held high, the shift register is continuously reloaded with the current buttons
+    strobe: bool,
+    input_state: u8,
+    /// The buttons still to be read out, starting from the lowest bit. Each read shifts in a one, so reads past
+    /// the eighth button return 1, like on the real hardware
+    shift_register: u8,
+}
+
+impl Controller {
+    pub fn new() -> Self {
+        Self {
+            strobe: true,
+            input_state: 0,
+            shift_register: 0,
+        }
+    }
+
+    pub fn set_state_from_window(&mut self, event: InputEvent) {
+        self.input_state = event.input_state;
+    }
+
+    pub fn write_to_controller(&mut self, strobe: bool) {
+        // Releasing the strobe latches the buttons as they were at that moment
+        if self.strobe || strobe {
+            self.shift_register = self.input_state;
+        }
+        self.strobe = strobe;
+    }
+
+    pub fn read_from_controller(&mut self) -> u8 {
+        if self.strobe {
+            // Reading while the strobe is held always returns the current state of A
+            self.shift_register = self.input_state;
+            return u8::from(self.shift_register.bit(InputEvent::A as usize));
+        }
+
+        let res = self.shift_register & 1;
+        self.shift_register = (self.shift_register >> 1) | 0x80;
+        res
+    }
+}
+
+/// The Zapper light gun, plugged into the second controller port in place of a controller. Its photodiode sees a
+/// small area of the screen around where it is aimed, and reports light for a while after the PPU draws something
+/// bright there, which is how games tell whether a target was hit
+#[derive(Default)]
+pub struct Zapper {
+    /// Whether the Zapper is plugged in, which is the case by default for games that expect one
+    pub connected: bool,
+    /// The screen pixel the Zapper is aimed at, or `None` when it is pointed away from the screen
+    pub aim: Option<(usize, usize)>,
+    pub trigger: bool,
+    /// The PPU dot, counted since power on, when the photodiode last saw light
+    lit_at: Option<u64>,
+}
+
+impl Zapper {
+    /// How far from the aim the photodiode sees, in pixels either way
+    const VIEW_RADIUS: usize = 3;
+    /// The photodiode keeps reporting light for about 20 scanlines after seeing it
+    const LIGHT_DOTS: u64 = 20 * 341;
+    /// The average luma, out of 255, of the pixels in view that counts as light
+    const LIGHT_THRESHOLD: u32 = 0x80;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the Zapper's state: bit 3 is clear while light is seen, and bit 4 is set while the trigger is pulled
+    pub(crate) fn read(&self, dot: u64) -> u8 {
+        let lit = self
+            .lit_at
+            .is_some_and(|lit_at| dot.saturating_sub(lit_at) < Zapper::LIGHT_DOTS);
+        (u8::from(!lit) << 3) | (u8::from(self.trigger) << 4)
+    }
+
+    /// Whether the photodiode can see any of the given scanline, so it needs to watch it being drawn
+    pub(crate) fn sees_scanline(&self, scanline: usize) -> bool {
+        self.connected
+            && self
+                .aim
+                .is_some_and(|(_, y)| scanline.abs_diff(y) <= Zapper::VIEW_RADIUS)
+    }
+
+    /// Wraps the framebuffer a scanline is about to be drawn into, so that the pixels in view can be checked
+    pub(crate) fn view<'a, T: FrameBuffer>(&self, fb: &'a mut T) -> ZapperView<'a, T> {
+        let x = self.aim.map_or(0, |(x, _)| x);
+        ZapperView {
+            fb,
+            left: x.saturating_sub(Zapper::VIEW_RADIUS),
+            pixels: [Color::BLACK; 2 * Zapper::VIEW_RADIUS + 1],
+        }
+    }
+
+    /// Checks the pixels in view on the scanline just drawn, as of the given PPU dot
+    pub(crate) fn sense<T: FrameBuffer>(&mut self, view: ZapperView<T>, dot: u64) {
+        let luma: u32 = view
+            .pixels
+            .iter()
+            .map(|color| {
+                (299 * color.r as u32 + 587 * color.g as u32 + 114 * color.b as u32) / 1000
+            })
+            .sum();
+        if luma / view.pixels.len() as u32 >= Zapper::LIGHT_THRESHOLD {
+            self.lit_at = Some(dot);
+        }
+    }
+}
+
+/// Passes pixels through to a framebuffer, keeping the last color drawn to each pixel in the Zapper's view
+pub(crate) struct ZapperView<'a, T: FrameBuffer> {
+    fb: &'a mut T,
+    /// The leftmost pixel in view
+    left: usize,
+    pixels: [Color; 2 * Zapper::VIEW_RADIUS + 1],
+}
+
+impl<T: FrameBuffer> FrameBuffer for ZapperView<'_, T> {
+    fn plot_pixel(&mut self, x: usize, y: usize, color: Color) {
+        if let Some(pixel) = x
+            .checked_sub(self.left)
+            .and_then(|offset| self.pixels.get_mut(offset))
+        {
+            *pixel = color;
+        }
+        self.fb.plot_pixel(x, y, color);
+    }
+}
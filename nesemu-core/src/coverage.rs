@@ -0,0 +1,125 @@
+//! Records which bytes of PRG ROM the CPU has executed, so that developers can see which code paths a play
+//! session reached. Addresses are tracked by their offset into PRG ROM rather than by CPU address, so code in
+//! banks that are switched in and out is told apart.
+//!
+//! Coverage can be exported as a code/data log in the format used by FCEUX and Mesen, for use with their
+//! disassemblers and other tools that read it, or as a plain text list of the ranges that were executed.
+
+use std::{fmt::Write, io::Error, ops::Range, path::Path};
+
+/// Marks a byte that was executed as part of an instruction, opcode or operand
+const CDL_CODE: u8 = 0x01;
+
+pub struct Coverage {
+    enabled: bool,
+    /// One byte of code/data log flags for every byte of PRG ROM
+    prg: Vec<u8>,
+    chr_rom_size: usize,
+}
+
+impl Coverage {
+    pub fn new(prg_rom_size: usize, chr_rom_size: usize) -> Self {
+        Self {
+            enabled: false,
+            prg: vec![0; prg_rom_size],
+            chr_rom_size,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts or stops recording. What was recorded so far is kept either way
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Forgets everything that was recorded
+    pub fn clear(&mut self) {
+        self.prg.fill(0);
+    }
+
+    /// Records that the byte at the given offset into PRG ROM was executed, while mapped to the given CPU address
+    pub(crate) fn mark_code(&mut self, prg_rom_offset: usize, cpu_bus_address: usize) {
+        if let Some(flags) = self.prg.get_mut(prg_rom_offset) {
+            // Bits 2-3 give which 8KB window of $8000-$FFFF the byte was mapped into when it was executed
+            *flags |= CDL_CODE | (((cpu_bus_address >> 13) & 0x03) as u8) << 2;
+        }
+    }
+
+    /// Checks whether the byte at the given offset into PRG ROM has been executed
+    pub fn executed(&self, prg_rom_offset: usize) -> bool {
+        self.prg
+            .get(prg_rom_offset)
+            .is_some_and(|flags| flags & CDL_CODE != 0)
+    }
+
+    /// Gets the number of bytes of PRG ROM that have been executed
+    pub fn executed_bytes(&self) -> usize {
+        self.prg
+            .iter()
+            .filter(|&&flags| flags & CDL_CODE != 0)
+            .count()
+    }
+
+    pub fn prg_rom_size(&self) -> usize {
+        self.prg.len()
+    }
+
+    /// Gets the ranges of PRG ROM offsets that have been executed, in order
+    pub fn executed_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for offset in (0..self.prg.len()).filter(|&offset| self.executed(offset)) {
+            match ranges.last_mut() {
+                Some(range) if range.end == offset => range.end += 1,
+                _ => ranges.push(offset..offset + 1),
+            }
+        }
+        ranges
+    }
+
+    /// Builds a code/data log: a byte of flags for every byte of PRG ROM, followed by one for every byte of CHR
+    /// ROM. Only executed code is recorded, so the CHR flags are all clear
+    pub fn to_cdl(&self) -> Vec<u8> {
+        let mut cdl = self.prg.clone();
+        cdl.resize(self.prg.len() + self.chr_rom_size, 0);
+        cdl
+    }
+
+    /// Builds a text report of how much of PRG ROM was executed, followed by the executed ranges of PRG ROM
+    /// offsets, one per line
+    pub fn to_report(&self) -> String {
+        let executed = self.executed_bytes();
+        let mut report = format!(
+            "Executed {} of {} bytes of PRG ROM ({:.1}%)\n",
+            executed,
+            self.prg.len(),
+            100.0 * executed as f64 / self.prg.len().max(1) as f64
+        );
+        for range in self.executed_ranges() {
+            // Writing to a String can't fail
+            let _ = writeln!(
+                report,
+                "{:06X}-{:06X} ({} bytes)",
+                range.start,
+                range.end - 1,
+                range.len()
+            );
+        }
+        report
+    }
+
+    /// Writes coverage to a file, as a code/data log if the file has a .cdl extension and as a text report
+    /// otherwise
+    pub fn export(&self, path: &Path) -> Result<(), Error> {
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cdl"))
+        {
+            std::fs::write(path, self.to_cdl())
+        } else {
+            std::fs::write(path, self.to_report())
+        }
+    }
+}
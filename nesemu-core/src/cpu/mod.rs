@@ -16,9 +16,9 @@ use tock_registers::{
     registers::InMemoryRegister,
 };
 
-use super::{bus::Bus, util::OptionalFile};
+use super::{bus::Bus, debugger::Interrupt, util::OptionalFile};
 
-mod opcodes;
+pub(crate) mod opcodes;
 
 register_bitfields!(
     u8,
@@ -40,6 +40,15 @@ pub struct CPU {
     old_register_state: CPURegisters, // State for the CPU at the end of the PREVIOUS instruction
     total_cycles: usize,              // For debug printing only
     log_file: OptionalFile,
+    trace: bool,
+    /// Reused for every line of the trace log
+    trace_line: String,
+    /// Whether indexing the current instruction's operand address crossed into another page
+    page_crossed: bool,
+    /// The interrupt taken by the last step, if any, and the address of the instruction it interrupted
+    interrupt_taken: Option<(Interrupt, usize)>,
+    /// The length in bytes of the instruction executed by the last step, or 0 if it took an interrupt instead
+    instruction_len: u8,
 }
 
 impl CPU {
@@ -55,6 +64,11 @@ impl CPU {
             old_register_state: CPURegisters::new(),
             total_cycles: 0,
             log_file: OptionalFile::new("nesemu.log"),
+            trace: false,
+            trace_line: String::new(),
+            page_crossed: false,
+            interrupt_taken: None,
+            instruction_len: 0,
         };
 
         this.reset(bus)?;
@@ -62,18 +76,12 @@ impl CPU {
     }
 
     /// Performs a reset of the CPU, for example in order to begin running a new cartridge
+    ///
+    /// Like the real hardware, this runs the same sequence as an interrupt, through the reset vector. The CPU
+    /// holds the bus in read mode while resetting, so the stack pushes become reads that only move the stack
+    /// pointer, and the other registers keep their values apart from the interrupt disable flag
     pub fn reset(&mut self, bus: &mut Bus) -> Result<(), &'static str> {
-        // Get start program counter from reset vector
-        let mut buf = [0u8; 2];
-        bus.cpu_read_exact(0xFFFC, &mut buf)?;
-        self.registers.program_counter = u16::from_le_bytes(buf) as usize;
-
-        self.total_cycles += 7;
-        self.registers.stack_ptr -= 3;
-        self.registers
-            .status_register
-            .modify(Status::INT_DISABLE::SET);
-        Ok(())
+        self.interrupt(0xFFFC, true, bus)
     }
 
     /// Steps the CPU simulation by one instruction
@@ -90,26 +98,72 @@ impl CPU {
         // the instruction, so we copy the current state of the registers
         // for later, when we print to the log
         self.old_register_state = self.registers.clone();
+        let from = self.registers.program_counter;
+        self.instruction_len = 0;
         if *pending_interrupt {
             *pending_interrupt = false;
+            self.interrupt_taken = Some((Interrupt::Nmi, from));
             return self.handle_irq(bus);
         }
+        // The cartridge's IRQ line is level triggered, so it is taken for as long as the mapper holds it and
+        // interrupts are enabled
+        if bus.irq_pending() && !self.registers.status_register.is_set(Status::INT_DISABLE) {
+            self.interrupt_taken = Some((Interrupt::Irq, from));
+            self.interrupt(Interrupt::Irq.vector(), false, bus)?;
+            return Ok(7);
+        }
         // Fetch the opcode
         let opcode = bus.cpu_read_byte(self.registers.program_counter)?;
+        if opcode == 0x00 {
+            self.interrupt_taken = Some((Interrupt::Brk, from));
+        }
         // We increment by one to skip over the opcode identifier byte, but processing the opcode
         // will handle adjusting the program counter to skip operand bytes
         self.registers.program_counter += 1;
         // TODO: Consider allowing debug logging of instructions via a keybind
-        let cycle_count = self.execute_opcode(opcode, bus, false)?;
+        let cycle_count = self.execute_opcode(opcode, bus, self.trace)?;
         self.total_cycles += cycle_count as usize;
         Ok(cycle_count)
     }
 
+    /// Enables logging of every executed instruction to the given file, in the same format as the nestest log
+    pub fn set_trace_file(&mut self, path: &str) {
+        self.log_file = OptionalFile::new(path);
+        self.trace = true;
+    }
+
+    /// Gets the interrupt taken by the last step, if any, along with the address of the instruction it
+    /// interrupted, and forgets about it
+    pub(crate) fn take_interrupt(&mut self) -> Option<(Interrupt, usize)> {
+        self.interrupt_taken.take()
+    }
+
+    /// Gets the address and length of the instruction executed by the last step, unless it took an interrupt
+    pub(crate) fn last_instruction(&self) -> Option<(usize, u8)> {
+        (self.instruction_len != 0).then_some((
+            self.old_register_state.program_counter,
+            self.instruction_len,
+        ))
+    }
+
+    /// Gets the address of the next instruction to be executed
+    pub fn program_counter(&self) -> usize {
+        self.registers.program_counter
+    }
+
+    pub fn registers(&self) -> &CPURegisters {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut CPURegisters {
+        &mut self.registers
+    }
+
     /// Push bytes onto the stack, decrementing the stack pointer as necessary
     fn push_stack(&mut self, data: &[u8], bus: &mut Bus) -> Result<(), &'static str> {
         for byte in data {
             bus.cpu_write_byte(self.registers.stack_ptr + CPU::STACK_PG_START, *byte)?;
-            self.registers.stack_ptr -= 1;
+            self.registers.stack_ptr = (self.registers.stack_ptr as u8).wrapping_sub(1) as usize;
         }
 
         Ok(())
@@ -118,7 +172,7 @@ impl CPU {
     /// Pop bytes off of the stack, incrementing the stack pointer as necessary
     fn pop_stack(&mut self, data: &mut [u8], bus: &mut Bus) -> Result<(), &'static str> {
         for byte in &mut *data {
-            self.registers.stack_ptr += 1;
+            self.registers.stack_ptr = (self.registers.stack_ptr as u8).wrapping_add(1) as usize;
             *byte = bus.cpu_read_byte(self.registers.stack_ptr + CPU::STACK_PG_START)?;
         }
 
@@ -135,28 +189,45 @@ impl CPU {
         self.registers.status_register.set(val);
     }
 
-    /// Instructs the CPU to handle an interrupt request
+    /// Instructs the CPU to handle a non-maskable interrupt. Maskable interrupts from the cartridge are handled by
+    /// [`CPU::step`]
     pub fn handle_irq(&mut self, bus: &mut Bus) -> Result<u8, &'static str> {
-        // TODO: This doesn't support IRQs which arent NMIs
+        self.interrupt(Interrupt::Nmi.vector(), false, bus)?;
+        Ok(8)
+    }
 
+    /// Saves the program counter and status register to the stack and jumps through the given vector, which
+    /// takes 7 cycles. With `suppress_writes`, nothing is written to the stack, but the stack pointer still
+    /// moves as if it were
+    fn interrupt(
+        &mut self,
+        vector: usize,
+        suppress_writes: bool,
+        bus: &mut Bus,
+    ) -> Result<(), &'static str> {
         // Push the necessary bookkeeping information to return from interrupt vector onto the stack
         // big endian because we need to push to the stack in reverse order of how they should be
-        self.push_stack(
-            &u16::to_be_bytes((self.registers.program_counter) as u16),
-            bus,
-        )?;
-        let sr = [self.registers.status_register.get()];
+        let pc = u16::to_be_bytes(self.registers.program_counter as u16);
+        let sr = self.registers.status_register.get();
+        for byte in [pc[0], pc[1], sr] {
+            if suppress_writes {
+                bus.cpu_read_byte(self.registers.stack_ptr + CPU::STACK_PG_START)?;
+                self.registers.stack_ptr =
+                    (self.registers.stack_ptr as u8).wrapping_sub(1) as usize;
+            } else {
+                self.push_stack(&[byte], bus)?;
+            }
+        }
         self.registers
             .status_register
             .modify(Status::INT_DISABLE::SET);
-        self.push_stack(&sr, bus)?;
 
-        // Jump to the program's interrupt vector for the next instruction
-        let mut interrupt_vector = [0u8; 2];
-        bus.cpu_read_exact(0xFFFA, &mut interrupt_vector)?;
-        self.registers.program_counter = u16::from_le_bytes(interrupt_vector) as usize;
+        // Jump to the program's vector for the next instruction
+        let mut buf = [0u8; 2];
+        bus.cpu_read_exact(vector, &mut buf)?;
+        self.registers.program_counter = u16::from_le_bytes(buf) as usize;
         self.total_cycles += 7;
-        Ok(8)
+        Ok(())
     }
 
     /// Checks if an adjustment to the program counter will cross a page boundary
@@ -203,8 +274,8 @@ impl CPURegisters {
             accumulator: Default::default(),
             x_reg: Default::default(),
             y_reg: Default::default(),
-            // Technically starts at 0xFD, but calling new() on CPU triggers reset() which decrements by 3
-            stack_ptr: 0xFF,
+            // Starts at 0x00, but calling new() on CPU triggers reset() which decrements by 3, wrapping to 0xFD
+            stack_ptr: 0x00,
             program_counter: 0x0, // Will be set to the reset vector by reset(),
             status_register: InMemoryRegister::new(0x24), // Match nestest
         }
@@ -28,10 +28,10 @@ pub enum AddressMode {
 }
 
 pub struct Opcode {
-    mnemonic: &'static str,
-    mode: AddressMode,
+    pub(crate) mnemonic: &'static str,
+    pub(crate) mode: AddressMode,
     bytes: [u8; 3],
-    num_bytes: u8,
+    pub(crate) num_bytes: u8,
     cycles: u8,
     execute: for<'a> fn(&'a mut CPU, usize, &'a Opcode, &'a mut Bus) -> Result<u8, &'static str>,
 }
@@ -45,12 +45,15 @@ impl CPU {
         log_opcode: bool,
     ) -> Result<u8, &'static str> {
         let opcode = self.lookup_opcode(opcode_val, bus)?;
+        self.instruction_len = opcode.num_bytes;
+        // Indirect modes read their pointer from memory, so the operand address is only resolved once, to
+        // avoid repeating those reads when logging or counting cycles
+        let addr = self.fetch_operand_address(&opcode, bus)?;
         if log_opcode {
             // We don't care if this succeeds or not, since the logging info is optional
-            let _ = self.write_opcode(&opcode, bus);
+            let _ = self.write_opcode(&opcode, addr, bus);
         }
 
-        let addr = self.fetch_operand_address(&opcode, bus)?;
         (opcode.execute)(self, addr, &opcode, bus)
     }
 
@@ -80,6 +83,7 @@ impl CPU {
         opcode: &Opcode,
         bus: &mut Bus,
     ) -> Result<usize, &'static str> {
+        self.page_crossed = false;
         match opcode.mode {
             AddressMode::IMPLIED | AddressMode::ACCUMULATOR => Ok(0x0), // Address is irrelevant for implied and ACC
             AddressMode::IMMEDIATE => Ok(self.registers.program_counter - 1),
@@ -102,15 +106,17 @@ impl CPU {
                 Ok(u16::from_le_bytes(addr_bytes) as usize)
             }
             AddressMode::INDIRECTY => {
-                let addr = self.fetch_indirect_y_base_addr(opcode, bus)? as u16;
-                Ok(addr.wrapping_add(self.registers.y_reg as u16) as usize)
+                let base_addr = self.fetch_indirect_y_base_addr(opcode, bus)?;
+                Ok(self.index_address(base_addr, self.registers.y_reg))
+            }
+            AddressMode::ABSOLUTEX => {
+                let base_addr = self.fetch_absolute_base_addr(opcode);
+                Ok(self.index_address(base_addr, self.registers.x_reg))
+            }
+            AddressMode::ABSOLUTEY => {
+                let base_addr = self.fetch_absolute_base_addr(opcode);
+                Ok(self.index_address(base_addr, self.registers.y_reg))
             }
-            AddressMode::ABSOLUTEX => Ok((self.fetch_absolute_base_addr(opcode) as u16)
-                .wrapping_add(self.registers.x_reg as u16)
-                as usize),
-            AddressMode::ABSOLUTEY => Ok((self.fetch_absolute_base_addr(opcode) as u16)
-                .wrapping_add(self.registers.y_reg as u16)
-                as usize),
             AddressMode::INDIRECT => {
                 let base_addr = self.fetch_absolute_base_addr(opcode);
                 // Have to emulate a cpu bug with indirect mode
@@ -153,26 +159,58 @@ impl CPU {
         u16::from_le_bytes([opcode.bytes[1], opcode.bytes[2]]) as usize
     }
 
+    /// Adds an index register to a base address, noting whether doing so crossed into another page, which
+    /// costs some instructions an extra cycle
+    fn index_address(&mut self, base_addr: usize, index: u8) -> usize {
+        let addr = (base_addr as u16).wrapping_add(index as u16) as usize;
+        self.page_crossed = CPU::will_cross_boundary(base_addr, addr);
+        addr
+    }
+
     /// Given an opcode and CPU state, potentially adjust the number of CPU cycles this instruction took to
     /// account for instructions of variable cycle length
-    fn adjust_cycles(
-        &mut self,
+    fn adjust_cycles(&self, opcode: &Opcode) -> u8 {
+        match opcode.mode {
+            AddressMode::INDIRECTY | AddressMode::ABSOLUTEX | AddressMode::ABSOLUTEY
+                if self.page_crossed =>
+            {
+                opcode.cycles + 1
+            }
+            _ => opcode.cycles,
+        }
+    }
+
+    /// Reads the value operated on by an instruction. Immediate values were already read along with the
+    /// opcode, so they aren't read from the bus a second time
+    fn read_operand(
+        &self,
         addr: usize,
         opcode: &Opcode,
         bus: &mut Bus,
     ) -> Result<u8, &'static str> {
-        let mut cycles = opcode.cycles;
-        let base_addr = match opcode.mode {
-            AddressMode::INDIRECTY => self.fetch_indirect_y_base_addr(opcode, bus)?,
-            AddressMode::ABSOLUTEX => self.fetch_absolute_base_addr(opcode),
-            AddressMode::ABSOLUTEY => self.fetch_absolute_base_addr(opcode),
-            _ => return Ok(cycles),
-        };
-
-        if CPU::will_cross_boundary(base_addr, addr) {
-            cycles += 1;
+        match opcode.mode {
+            AddressMode::IMMEDIATE => Ok(opcode.bytes[1]),
+            _ => bus.cpu_read_byte(addr),
         }
-        Ok(cycles)
+    }
+
+    fn brk(&mut self, _: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
+        // Like an IRQ, but the status pushed to the stack has BFLAG set, so the handler can tell them apart
+        self.push_stack(
+            &u16::to_be_bytes(self.registers.program_counter as u16),
+            bus,
+        )?;
+        let mut copy = self.registers.status_register.extract();
+        copy.modify(Status::BFLAG::SET);
+        self.push_stack(&[copy.get()], bus)?;
+        self.registers
+            .status_register
+            .modify(Status::INT_DISABLE::SET);
+
+        let mut vector = [0u8; 2];
+        bus.cpu_read_exact(0xFFFE, &mut vector)?;
+        self.registers.program_counter = u16::from_le_bytes(vector) as usize;
+        Ok(opcode.cycles)
     }
 
     fn rti(&mut self, _: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
@@ -189,7 +227,7 @@ impl CPU {
 
     fn sbc(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
         let old_accumulator = self.registers.accumulator;
-        let mut mem = bus.cpu_read_byte(addr)?;
+        let mut mem = self.read_operand(addr, opcode, bus)?;
         mem ^= 0xFF; // Only difference from ADC is that we xor the memory byte thanks to two's complement
         let val16bit: u16 = self.registers.accumulator as u16
             + mem as u16
@@ -203,12 +241,12 @@ impl CPU {
         );
         self.set_status_bit_if(7, self.registers.accumulator.bit(7));
 
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn adc(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
         let old_accumulator = self.registers.accumulator;
-        let mem = bus.cpu_read_byte(addr)?;
+        let mem = self.read_operand(addr, opcode, bus)?;
         let val16bit: u16 = self.registers.accumulator as u16
             + mem as u16
             + self.registers.status_register.is_set(Status::CARRY) as u16;
@@ -221,7 +259,7 @@ impl CPU {
         );
         self.set_status_bit_if(7, self.registers.accumulator.bit(7));
 
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn plp(&mut self, _: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
@@ -304,24 +342,24 @@ impl CPU {
     }
 
     fn and(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        self.registers.accumulator &= bus.cpu_read_byte(addr)?;
+        self.registers.accumulator &= self.read_operand(addr, opcode, bus)?;
         self.set_status_bit_if(1, self.registers.accumulator == 0);
         self.set_status_bit_if(7, self.registers.accumulator.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn ora(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        self.registers.accumulator |= bus.cpu_read_byte(addr)?;
+        self.registers.accumulator |= self.read_operand(addr, opcode, bus)?;
         self.set_status_bit_if(1, self.registers.accumulator == 0);
         self.set_status_bit_if(7, self.registers.accumulator.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn eor(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        self.registers.accumulator ^= bus.cpu_read_byte(addr)?;
+        self.registers.accumulator ^= self.read_operand(addr, opcode, bus)?;
         self.set_status_bit_if(1, self.registers.accumulator == 0);
         self.set_status_bit_if(7, self.registers.accumulator.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn jsr(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
@@ -344,7 +382,7 @@ impl CPU {
     }
 
     fn bit(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        let byte = bus.cpu_read_byte(addr)?;
+        let byte = self.read_operand(addr, opcode, bus)?;
         self.set_status_bit_if(1, self.registers.accumulator & byte == 0);
         self.set_status_bit_if(6, byte.bit(6));
         self.set_status_bit_if(7, byte.bit(7));
@@ -371,11 +409,11 @@ impl CPU {
         opcode: &Opcode,
         bus: &mut Bus,
     ) -> Result<u8, &'static str> {
-        let byte = bus.cpu_read_byte(addr)?;
+        let byte = self.read_operand(addr, opcode, bus)?;
         self.set_status_bit_if(0, reg_val >= byte);
         self.set_status_bit_if(1, reg_val == byte);
         self.set_status_bit_if(7, reg_val.wrapping_sub(byte).bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn tay(&mut self, _: usize, opcode: &Opcode, _: &mut Bus) -> Result<u8, &'static str> {
@@ -468,44 +506,44 @@ impl CPU {
     }
 
     fn stx(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        bus.cpu_write_byte(addr as usize, self.registers.x_reg)?;
+        bus.cpu_write_byte(addr, self.registers.x_reg)?;
         Ok(opcode.cycles)
     }
 
     fn sty(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        bus.cpu_write_byte(addr as usize, self.registers.y_reg)?;
+        bus.cpu_write_byte(addr, self.registers.y_reg)?;
         Ok(opcode.cycles)
     }
 
     fn ldy(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        let byte = bus.cpu_read_byte(addr)?;
+        let byte = self.read_operand(addr, opcode, bus)?;
         self.registers.y_reg = byte;
         self.set_status_bit_if(1, byte == 0);
         self.set_status_bit_if(7, byte.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn ldx(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        let byte = bus.cpu_read_byte(addr)?;
+        let byte = self.read_operand(addr, opcode, bus)?;
         self.registers.x_reg = byte;
         self.set_status_bit_if(1, byte == 0);
         self.set_status_bit_if(7, byte.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn lda(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
-        let byte = bus.cpu_read_byte(addr)?;
+        let byte = self.read_operand(addr, opcode, bus)?;
         self.registers.accumulator = byte;
         self.set_status_bit_if(1, byte == 0);
         self.set_status_bit_if(7, byte.bit(7));
-        self.adjust_cycles(addr, opcode, bus)
+        Ok(self.adjust_cycles(opcode))
     }
 
     fn lsr(&mut self, addr: usize, opcode: &Opcode, bus: &mut Bus) -> Result<u8, &'static str> {
         match opcode.mode {
             AddressMode::ACCUMULATOR => {
                 self.set_status_bit_if(0, self.registers.accumulator.bit(0));
-                self.registers.accumulator = self.registers.accumulator >> 1;
+                self.registers.accumulator >>= 1;
                 self.set_status_bit_if(1, self.registers.accumulator == 0);
                 self.registers
                     .status_register
@@ -514,7 +552,7 @@ impl CPU {
             _ => {
                 let mut byte = bus.cpu_read_byte(addr)?;
                 self.set_status_bit_if(0, byte.bit(0));
-                byte = byte >> 1;
+                byte >>= 1;
                 bus.cpu_write_byte(addr, byte)?;
                 self.set_status_bit_if(1, byte == 0);
                 self.registers
@@ -530,14 +568,14 @@ impl CPU {
         match opcode.mode {
             AddressMode::ACCUMULATOR => {
                 self.set_status_bit_if(0, self.registers.accumulator.bit(7));
-                self.registers.accumulator = self.registers.accumulator << 1;
+                self.registers.accumulator <<= 1;
                 self.set_status_bit_if(1, self.registers.accumulator == 0);
                 self.set_status_bit_if(7, self.registers.accumulator.bit(7));
             }
             _ => {
                 let mut byte = bus.cpu_read_byte(addr)?;
                 self.set_status_bit_if(0, byte.bit(7));
-                byte = byte << 1;
+                byte <<= 1;
                 bus.cpu_write_byte(addr, byte)?;
                 self.set_status_bit_if(1, byte == 0);
                 self.set_status_bit_if(7, byte.bit(7));
@@ -550,7 +588,7 @@ impl CPU {
         match opcode.mode {
             AddressMode::ACCUMULATOR => {
                 let new_carry = self.registers.accumulator.bit(0);
-                self.registers.accumulator = self.registers.accumulator >> 1;
+                self.registers.accumulator >>= 1;
                 self.registers
                     .accumulator
                     .set_bit(7, self.registers.status_register.is_set(Status::CARRY));
@@ -561,7 +599,7 @@ impl CPU {
             _ => {
                 let mut byte = bus.cpu_read_byte(addr)?;
                 let new_carry = byte.bit(0);
-                byte = byte >> 1;
+                byte >>= 1;
                 byte.set_bit(7, self.registers.status_register.is_set(Status::CARRY));
                 bus.cpu_write_byte(addr, byte)?;
                 self.set_status_bit_if(0, new_carry);
@@ -576,7 +614,7 @@ impl CPU {
         match opcode.mode {
             AddressMode::ACCUMULATOR => {
                 let new_carry = self.registers.accumulator.bit(7);
-                self.registers.accumulator = self.registers.accumulator << 1;
+                self.registers.accumulator <<= 1;
                 self.registers
                     .accumulator
                     .set_bit(0, self.registers.status_register.is_set(Status::CARRY));
@@ -587,7 +625,7 @@ impl CPU {
             _ => {
                 let mut byte = bus.cpu_read_byte(addr)?;
                 let new_carry = byte.bit(7);
-                byte = byte << 1;
+                byte <<= 1;
                 byte.set_bit(0, self.registers.status_register.is_set(Status::CARRY));
                 bus.cpu_write_byte(addr, byte)?;
                 self.set_status_bit_if(0, new_carry);
@@ -599,7 +637,7 @@ impl CPU {
     }
 
     fn jmp(&mut self, addr: usize, opcode: &Opcode, _: &mut Bus) -> Result<u8, &'static str> {
-        self.registers.program_counter = addr as usize;
+        self.registers.program_counter = addr;
         Ok(opcode.cycles)
     }
 
@@ -662,15 +700,152 @@ impl CPU {
         Ok(cycle_count)
     }
 
+    /// Decodes an opcode and reads its operand bytes, leaving the program counter just past them
     pub fn lookup_opcode(&mut self, opcode: u8, bus: &mut Bus) -> Result<Opcode, &'static str> {
+        let mut decoded = Opcode::decode(opcode)?;
+        decoded.bytes = match decoded.num_bytes {
+            1 => self.fetch_zero_more_bytes(opcode),
+            2 => self.fetch_one_more_bytes(opcode, bus)?,
+            _ => self.fetch_two_more_bytes(opcode, bus)?,
+        };
+        Ok(decoded)
+    }
+
+    /// Logs an instruction in the same format as the nestest log
+    ///
+    /// Each line is formatted into a buffer that is reused between instructions, so that tracing doesn't
+    /// allocate
+    pub fn write_opcode(
+        &mut self,
+        opcode: &Opcode,
+        addr: usize,
+        bus: &mut Bus,
+    ) -> Result<(), &'static str> {
+        // The log shows the value at the operand address for every mode that accesses memory
+        let accesses_memory = !matches!(
+            opcode.mode,
+            AddressMode::IMPLIED
+                | AddressMode::ACCUMULATOR
+                | AddressMode::IMMEDIATE
+                | AddressMode::RELATIVE
+                | AddressMode::INDIRECT
+                | AddressMode::ABSOLUTE(false)
+        );
+        let value = if accesses_memory {
+            bus.cpu_read_byte_no_modify(addr)?
+        } else {
+            0
+        };
+
+        let mut line = std::mem::take(&mut self.trace_line);
+        line.clear();
+        self.format_opcode(&mut line, opcode, addr, value)
+            .map_err(|_| "Failed to format log line")?;
+        // Name the variable the instruction accesses, if the RAM map has one there
+        if let Some(entry) = bus.ram_map.entry_at(addr).filter(|_| accesses_memory) {
+            line.push_str("  ; ");
+            line.push_str(&entry.name);
+        }
+        let result = writeln!(self.log_file, "{}", line).map_err(|_| "Failed to write to log file");
+        log::info!("{}", line);
+        self.trace_line = line;
+        result
+    }
+
+    fn format_opcode(
+        &self,
+        line: &mut String,
+        opcode: &Opcode,
+        addr: usize,
+        value: u8,
+    ) -> std::fmt::Result {
+        use std::fmt::Write;
+
+        write!(line, "{:04X}  ", self.old_register_state.program_counter)?;
+        let [op, lsb, msb] = opcode.bytes;
+        match opcode.num_bytes {
+            1 => write!(line, "{:02X}{:<8}{} ", op, "", opcode.mnemonic)?,
+            2 => write!(line, "{:02X} {:02X}{:<5}{} ", op, lsb, "", opcode.mnemonic)?,
+            _ => write!(
+                line,
+                "{:02X} {:02X} {:02X}  {} ",
+                op, lsb, msb, opcode.mnemonic
+            )?,
+        }
+
+        let base_addr = u16::from_le_bytes([lsb, msb]);
+        match opcode.mode {
+            AddressMode::IMPLIED => {}
+            AddressMode::ACCUMULATOR => write!(line, "A ")?,
+            AddressMode::IMMEDIATE => write!(line, "#${:02X}", lsb)?,
+            AddressMode::RELATIVE => write!(line, "${:02X}", addr)?,
+            AddressMode::ZEROPAGE => write!(line, "${:02X} = {:02X}", lsb, value)?,
+            AddressMode::INDIRECTX => write!(
+                line,
+                "(${:02X},X) @ {:02X} = {:04X} = {:02X}",
+                lsb,
+                lsb.wrapping_add(self.registers.x_reg),
+                addr,
+                value
+            )?,
+            AddressMode::INDIRECTY => write!(
+                line,
+                "(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
+                lsb,
+                (addr as u16).wrapping_sub(self.registers.y_reg as u16),
+                addr,
+                value
+            )?,
+            AddressMode::ZEROPAGEX => {
+                write!(line, "${:02X},X @ {:02X} = {:02X}", lsb, addr, value)?
+            }
+            AddressMode::ZEROPAGEY => {
+                write!(line, "${:02X},Y @ {:02X} = {:02X}", lsb, addr, value)?
+            }
+            AddressMode::ABSOLUTE(mem_modify) => {
+                write!(line, "${:04X}", base_addr)?;
+                if mem_modify {
+                    write!(line, " = {:02X}", value)?;
+                }
+            }
+            AddressMode::INDIRECT => write!(line, "(${:04X}) = {:04X}", base_addr, addr)?,
+            AddressMode::ABSOLUTEY => {
+                write!(line, "${:04X},Y @ {:04X} = {:02X}", base_addr, addr, value)?
+            }
+            AddressMode::ABSOLUTEX => {
+                write!(line, "${:04X},X @ {:04X} = {:02X}", base_addr, addr, value)?
+            }
+        }
+
+        // Line the registers up in a column
+        let padding = 42usize.saturating_sub(line.len());
+        write!(
+            line,
+            "{:padding$}     {} CYC:{}",
+            "", self.old_register_state, self.total_cycles
+        )
+    }
+}
+
+impl Opcode {
+    /// Looks up how an opcode is executed, without its operand bytes, which are left as zero
+    pub(crate) fn decode(opcode: u8) -> Result<Self, &'static str> {
         match opcode {
-            0x00 => todo!(),
+            0x00 => Ok(Opcode {
+                mnemonic: "BRK",
+                mode: AddressMode::IMPLIED,
+                // The byte after BRK is skipped, and is often used to tell the handler why it was called
+                num_bytes: 2,
+                cycles: 7,
+                bytes: [opcode, 0, 0],
+                execute: CPU::brk,
+            }),
             0x01 => Ok(Opcode {
                 mnemonic: "ORA",
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x05 => Ok(Opcode {
@@ -678,7 +853,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x06 => Ok(Opcode {
@@ -686,7 +861,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::asl,
             }),
             0x08 => Ok(Opcode {
@@ -694,7 +869,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 3,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::php,
             }),
             0x09 => Ok(Opcode {
@@ -702,7 +877,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x0A => Ok(Opcode {
@@ -710,7 +885,7 @@ impl CPU {
                 mode: AddressMode::ACCUMULATOR,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::asl,
             }),
             0x0D => Ok(Opcode {
@@ -718,7 +893,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x0E => Ok(Opcode {
@@ -726,7 +901,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::asl,
             }),
             0x10 => Ok(Opcode {
@@ -734,7 +909,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bpl,
             }),
             0x11 => Ok(Opcode {
@@ -742,7 +917,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x15 => Ok(Opcode {
@@ -750,7 +925,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x16 => Ok(Opcode {
@@ -758,7 +933,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::asl,
             }),
             0x18 => Ok(Opcode {
@@ -766,7 +941,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::clc,
             }),
             0x19 => Ok(Opcode {
@@ -774,7 +949,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x1D => Ok(Opcode {
@@ -782,7 +957,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ora,
             }),
             0x1E => Ok(Opcode {
@@ -790,7 +965,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::asl,
             }),
             0x20 => Ok(Opcode {
@@ -798,7 +973,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(false),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::jsr,
             }),
             0x21 => Ok(Opcode {
@@ -806,7 +981,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x24 => Ok(Opcode {
@@ -814,7 +989,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bit,
             }),
             0x25 => Ok(Opcode {
@@ -822,7 +997,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x26 => Ok(Opcode {
@@ -830,7 +1005,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::rol,
             }),
             0x28 => Ok(Opcode {
@@ -838,7 +1013,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 4,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::plp,
             }),
             0x29 => Ok(Opcode {
@@ -846,7 +1021,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x2A => Ok(Opcode {
@@ -854,7 +1029,7 @@ impl CPU {
                 mode: AddressMode::ACCUMULATOR,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::rol,
             }),
             0x2C => Ok(Opcode {
@@ -862,7 +1037,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bit,
             }),
             0x2D => Ok(Opcode {
@@ -870,7 +1045,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x2E => Ok(Opcode {
@@ -878,7 +1053,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::rol,
             }),
             0x30 => Ok(Opcode {
@@ -886,7 +1061,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bmi,
             }),
             0x31 => Ok(Opcode {
@@ -894,7 +1069,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x35 => Ok(Opcode {
@@ -902,7 +1077,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x36 => Ok(Opcode {
@@ -910,7 +1085,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::rol,
             }),
             0x38 => Ok(Opcode {
@@ -918,7 +1093,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::sec,
             }),
             0x39 => Ok(Opcode {
@@ -926,7 +1101,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x3D => Ok(Opcode {
@@ -934,7 +1109,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::and,
             }),
             0x3E => Ok(Opcode {
@@ -942,7 +1117,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::rol,
             }),
             0x40 => Ok(Opcode {
@@ -950,7 +1125,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 6,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::rti,
             }),
             0x41 => Ok(Opcode {
@@ -958,7 +1133,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x45 => Ok(Opcode {
@@ -966,7 +1141,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x46 => Ok(Opcode {
@@ -974,7 +1149,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lsr,
             }),
             0x48 => Ok(Opcode {
@@ -982,7 +1157,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 3,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::pha,
             }),
             0x49 => Ok(Opcode {
@@ -990,7 +1165,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x4A => Ok(Opcode {
@@ -998,7 +1173,7 @@ impl CPU {
                 mode: AddressMode::ACCUMULATOR,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::lsr,
             }),
             0x4C => Ok(Opcode {
@@ -1006,7 +1181,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(false),
                 num_bytes: 3,
                 cycles: 3,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::jmp,
             }),
             0x4D => Ok(Opcode {
@@ -1014,7 +1189,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x4E => Ok(Opcode {
@@ -1022,7 +1197,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lsr,
             }),
             0x50 => Ok(Opcode {
@@ -1030,7 +1205,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bvc,
             }),
             0x51 => Ok(Opcode {
@@ -1038,7 +1213,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x55 => Ok(Opcode {
@@ -1046,7 +1221,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x56 => Ok(Opcode {
@@ -1054,7 +1229,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lsr,
             }),
             0x58 => Ok(Opcode {
@@ -1062,7 +1237,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::cli,
             }),
             0x59 => Ok(Opcode {
@@ -1070,7 +1245,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x5D => Ok(Opcode {
@@ -1078,7 +1253,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::eor,
             }),
             0x5E => Ok(Opcode {
@@ -1086,7 +1261,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lsr,
             }),
             0x60 => Ok(Opcode {
@@ -1094,7 +1269,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 6,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::rts,
             }),
             0x61 => Ok(Opcode {
@@ -1102,7 +1277,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x65 => Ok(Opcode {
@@ -1110,7 +1285,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x66 => Ok(Opcode {
@@ -1118,7 +1293,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ror,
             }),
             0x68 => Ok(Opcode {
@@ -1126,7 +1301,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 4,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::pla,
             }),
             0x69 => Ok(Opcode {
@@ -1134,7 +1309,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x6A => Ok(Opcode {
@@ -1142,7 +1317,7 @@ impl CPU {
                 mode: AddressMode::ACCUMULATOR,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::ror,
             }),
             0x6C => Ok(Opcode {
@@ -1150,7 +1325,7 @@ impl CPU {
                 mode: AddressMode::INDIRECT,
                 num_bytes: 3,
                 cycles: 5,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::jmp,
             }),
             0x6D => Ok(Opcode {
@@ -1158,7 +1333,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x6E => Ok(Opcode {
@@ -1166,7 +1341,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ror,
             }),
             0x70 => Ok(Opcode {
@@ -1174,7 +1349,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bvs,
             }),
             0x71 => Ok(Opcode {
@@ -1182,7 +1357,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x75 => Ok(Opcode {
@@ -1190,7 +1365,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x76 => Ok(Opcode {
@@ -1198,7 +1373,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ror,
             }),
             0x78 => Ok(Opcode {
@@ -1206,7 +1381,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::sei,
             }),
             0x79 => Ok(Opcode {
@@ -1214,7 +1389,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x7D => Ok(Opcode {
@@ -1222,7 +1397,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::adc,
             }),
             0x7E => Ok(Opcode {
@@ -1230,7 +1405,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ror,
             }),
             0x81 => Ok(Opcode {
@@ -1238,7 +1413,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x84 => Ok(Opcode {
@@ -1246,7 +1421,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sty,
             }),
             0x85 => Ok(Opcode {
@@ -1254,7 +1429,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x86 => Ok(Opcode {
@@ -1262,7 +1437,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::stx,
             }),
             0x88 => Ok(Opcode {
@@ -1270,7 +1445,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::dey,
             }),
             0x8A => Ok(Opcode {
@@ -1278,7 +1453,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::txa,
             }),
             0x8C => Ok(Opcode {
@@ -1286,7 +1461,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sty,
             }),
             0x8D => Ok(Opcode {
@@ -1294,7 +1469,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x8E => Ok(Opcode {
@@ -1302,7 +1477,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::stx,
             }),
             0x90 => Ok(Opcode {
@@ -1310,7 +1485,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bcc,
             }),
             0x91 => Ok(Opcode {
@@ -1318,7 +1493,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x94 => Ok(Opcode {
@@ -1326,7 +1501,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sty,
             }),
             0x95 => Ok(Opcode {
@@ -1334,7 +1509,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x96 => Ok(Opcode {
@@ -1342,7 +1517,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEY,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::stx,
             }),
             0x98 => Ok(Opcode {
@@ -1350,7 +1525,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::tya,
             }),
             0x99 => Ok(Opcode {
@@ -1358,7 +1533,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 5,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0x9A => Ok(Opcode {
@@ -1366,7 +1541,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::txs,
             }),
             0x9D => Ok(Opcode {
@@ -1374,7 +1549,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 5,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sta,
             }),
             0xA0 => Ok(Opcode {
@@ -1382,7 +1557,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldy,
             }),
             0xA1 => Ok(Opcode {
@@ -1390,7 +1565,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xA2 => Ok(Opcode {
@@ -1398,7 +1573,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldx,
             }),
             0xA4 => Ok(Opcode {
@@ -1406,7 +1581,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldy,
             }),
             0xA5 => Ok(Opcode {
@@ -1414,7 +1589,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xA6 => Ok(Opcode {
@@ -1422,7 +1597,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldx,
             }),
             0xA8 => Ok(Opcode {
@@ -1430,7 +1605,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::tay,
             }),
             0xA9 => Ok(Opcode {
@@ -1438,7 +1613,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xAA => Ok(Opcode {
@@ -1446,7 +1621,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::tax,
             }),
             0xAC => Ok(Opcode {
@@ -1454,7 +1629,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldy,
             }),
             0xAD => Ok(Opcode {
@@ -1462,7 +1637,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xAE => Ok(Opcode {
@@ -1470,7 +1645,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldx,
             }),
             0xB0 => Ok(Opcode {
@@ -1478,7 +1653,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bcs,
             }),
             0xB1 => Ok(Opcode {
@@ -1486,7 +1661,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xB4 => Ok(Opcode {
@@ -1494,7 +1669,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldy,
             }),
             0xB5 => Ok(Opcode {
@@ -1502,7 +1677,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xB6 => Ok(Opcode {
@@ -1510,7 +1685,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEY,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldx,
             }),
             0xB8 => Ok(Opcode {
@@ -1518,7 +1693,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::clv,
             }),
             0xB9 => Ok(Opcode {
@@ -1526,7 +1701,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xBA => Ok(Opcode {
@@ -1534,7 +1709,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::tsx,
             }),
             0xBC => Ok(Opcode {
@@ -1542,7 +1717,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldy,
             }),
             0xBD => Ok(Opcode {
@@ -1550,7 +1725,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::lda,
             }),
             0xBE => Ok(Opcode {
@@ -1558,7 +1733,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::ldx,
             }),
             0xC0 => Ok(Opcode {
@@ -1566,7 +1741,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpy,
             }),
             0xC1 => Ok(Opcode {
@@ -1574,7 +1749,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xC4 => Ok(Opcode {
@@ -1582,7 +1757,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpy,
             }),
             0xC5 => Ok(Opcode {
@@ -1590,7 +1765,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xC6 => Ok(Opcode {
@@ -1598,7 +1773,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::dec,
             }),
             0xC8 => Ok(Opcode {
@@ -1606,7 +1781,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::iny,
             }),
             0xC9 => Ok(Opcode {
@@ -1614,7 +1789,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xCA => Ok(Opcode {
@@ -1622,7 +1797,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::dex,
             }),
             0xCC => Ok(Opcode {
@@ -1630,7 +1805,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpy,
             }),
             0xCD => Ok(Opcode {
@@ -1638,7 +1813,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xCE => Ok(Opcode {
@@ -1646,7 +1821,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::dec,
             }),
             0xD0 => Ok(Opcode {
@@ -1654,7 +1829,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::bne,
             }),
             0xD1 => Ok(Opcode {
@@ -1662,7 +1837,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xD5 => Ok(Opcode {
@@ -1670,7 +1845,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xD6 => Ok(Opcode {
@@ -1678,7 +1853,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::dec,
             }),
             0xD8 => Ok(Opcode {
@@ -1686,7 +1861,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::cld,
             }),
             0xD9 => Ok(Opcode {
@@ -1694,7 +1869,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xDD => Ok(Opcode {
@@ -1702,7 +1877,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cmp,
             }),
             0xDE => Ok(Opcode {
@@ -1710,7 +1885,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::dec,
             }),
             0xE0 => Ok(Opcode {
@@ -1718,7 +1893,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpx,
             }),
             0xE1 => Ok(Opcode {
@@ -1726,7 +1901,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xE4 => Ok(Opcode {
@@ -1734,7 +1909,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpx,
             }),
             0xE5 => Ok(Opcode {
@@ -1742,7 +1917,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 3,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xE6 => Ok(Opcode {
@@ -1750,7 +1925,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGE,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::inc,
             }),
             0xE8 => Ok(Opcode {
@@ -1758,7 +1933,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::inx,
             }),
             0xE9 => Ok(Opcode {
@@ -1766,7 +1941,7 @@ impl CPU {
                 mode: AddressMode::IMMEDIATE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xEA => Ok(Opcode {
@@ -1774,7 +1949,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::nop,
             }),
             0xEC => Ok(Opcode {
@@ -1782,7 +1957,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::cpx,
             }),
             0xED => Ok(Opcode {
@@ -1790,7 +1965,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xEE => Ok(Opcode {
@@ -1798,7 +1973,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTE(true),
                 num_bytes: 3,
                 cycles: 6,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::inc,
             }),
             0xF0 => Ok(Opcode {
@@ -1806,7 +1981,7 @@ impl CPU {
                 mode: AddressMode::RELATIVE,
                 num_bytes: 2,
                 cycles: 2,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::beq,
             }),
             0xF1 => Ok(Opcode {
@@ -1814,7 +1989,7 @@ impl CPU {
                 mode: AddressMode::INDIRECTY,
                 num_bytes: 2,
                 cycles: 5,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xF5 => Ok(Opcode {
@@ -1822,7 +1997,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 4,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xF6 => Ok(Opcode {
@@ -1830,7 +2005,7 @@ impl CPU {
                 mode: AddressMode::ZEROPAGEX,
                 num_bytes: 2,
                 cycles: 6,
-                bytes: self.fetch_one_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::inc,
             }),
             0xF8 => Ok(Opcode {
@@ -1838,7 +2013,7 @@ impl CPU {
                 mode: AddressMode::IMPLIED,
                 num_bytes: 1,
                 cycles: 2,
-                bytes: self.fetch_zero_more_bytes(opcode),
+                bytes: [opcode, 0, 0],
                 execute: CPU::sed,
             }),
             0xF9 => Ok(Opcode {
@@ -1846,7 +2021,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEY,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xFD => Ok(Opcode {
@@ -1854,7 +2029,7 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 4,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::sbc,
             }),
             0xFE => Ok(Opcode {
@@ -1862,155 +2037,10 @@ impl CPU {
                 mode: AddressMode::ABSOLUTEX,
                 num_bytes: 3,
                 cycles: 7,
-                bytes: self.fetch_two_more_bytes(opcode, bus)?,
+                bytes: [opcode, 0, 0],
                 execute: CPU::inc,
             }),
             _ => Err("Invalid opcode"),
         }
     }
-
-    // TODO: This is really slow
-    // TODO: Causes issues because it does destructive reads on memory mapped IO, can be fixed when we
-    // refactor the bus
-    pub fn write_opcode(&mut self, opcode: &Opcode, bus: &mut Bus) -> Result<(), &'static str> {
-        let mut fmt_string = format!("{:04X}  ", self.old_register_state.program_counter);
-
-        if opcode.num_bytes == 1 {
-            fmt_string = format!(
-                "{}{:02X}{:<8}{} ",
-                fmt_string, opcode.bytes[0], "", opcode.mnemonic
-            );
-
-            match opcode.mode {
-                AddressMode::ACCUMULATOR => {
-                    fmt_string = format!("{}A ", fmt_string);
-                }
-                _ => {}
-            }
-        } else if opcode.num_bytes == 2 {
-            fmt_string = format!(
-                "{}{:02X} {:02X}{:<5}{} ",
-                fmt_string, opcode.bytes[0], opcode.bytes[1], "", opcode.mnemonic
-            );
-
-            match opcode.mode {
-                AddressMode::IMMEDIATE => {
-                    fmt_string = format!("{}#${:02X}", fmt_string, opcode.bytes[1]);
-                }
-                AddressMode::RELATIVE => {
-                    fmt_string = format!(
-                        "{}${:02X}",
-                        fmt_string,
-                        self.fetch_operand_address(opcode, bus)?
-                    );
-                }
-                AddressMode::ZEROPAGE => {
-                    let address_value = bus.cpu_read_byte_no_modify(opcode.bytes[1] as usize)?;
-                    fmt_string = format!(
-                        "{}${:02X} = {:02X}",
-                        fmt_string, opcode.bytes[1], address_value
-                    );
-                }
-                AddressMode::INDIRECTX => {
-                    let lsb_addr = opcode.bytes[1].wrapping_add(self.registers.x_reg);
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    fmt_string = format!(
-                        "{}(${:02X},X) @ {:02X} = {:04X} = {:02X}",
-                        fmt_string,
-                        opcode.bytes[1],
-                        lsb_addr,
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-                AddressMode::INDIRECTY => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    fmt_string = format!(
-                        "{}(${:02X}),Y = {:04X} @ {:04X} = {:02X}",
-                        fmt_string,
-                        opcode.bytes[1],
-                        self.fetch_indirect_y_base_addr(opcode, bus)?,
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-                AddressMode::ZEROPAGEX => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    fmt_string = format!(
-                        "{}${:02X},X @ {:02X} = {:02X}",
-                        fmt_string,
-                        opcode.bytes[1],
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-                AddressMode::ZEROPAGEY => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    fmt_string = format!(
-                        "{}${:02X},Y @ {:02X} = {:02X}",
-                        fmt_string,
-                        opcode.bytes[1],
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-                _ => {} // should never happen
-            }
-        } else if opcode.num_bytes == 3 {
-            fmt_string = format!(
-                "{}{:02X} {:02X} {:02X}  {} ",
-                fmt_string, opcode.bytes[0], opcode.bytes[1], opcode.bytes[2], opcode.mnemonic
-            );
-
-            match opcode.mode {
-                AddressMode::ABSOLUTE(mem_modify) => {
-                    let operand = u16::from_le_bytes(opcode.bytes[1..].try_into().unwrap());
-                    fmt_string = format!("{}${:04X}", fmt_string, operand);
-
-                    if mem_modify {
-                        let byte = bus.cpu_read_byte_no_modify(operand as usize)?;
-                        fmt_string = format!("{} = {:02X}", fmt_string, byte);
-                    }
-                }
-                AddressMode::INDIRECT => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    let base_addr = self.fetch_absolute_base_addr(opcode);
-                    fmt_string = format!("{}(${:04X}) = {:04X}", fmt_string, base_addr, addr);
-                }
-                AddressMode::ABSOLUTEY => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    let base_addr = self.fetch_absolute_base_addr(opcode);
-                    fmt_string = format!(
-                        "{}${:04X},Y @ {:04X} = {:02X}",
-                        fmt_string,
-                        base_addr,
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-                AddressMode::ABSOLUTEX => {
-                    let addr = self.fetch_operand_address(opcode, bus)?;
-                    let base_addr = self.fetch_absolute_base_addr(opcode);
-                    fmt_string = format!(
-                        "{}${:04X},X @ {:04X} = {:02X}",
-                        fmt_string,
-                        base_addr,
-                        addr,
-                        bus.cpu_read_byte_no_modify(addr)?
-                    );
-                }
-
-                _ => {} // should never happen
-            }
-        }
-
-        fmt_string = format!("{:<42}", fmt_string);
-        fmt_string = format!(
-            "{}     {} CYC:{}",
-            fmt_string, self.old_register_state, self.total_cycles
-        );
-        write!(self.log_file, "{}\n", fmt_string).map_err(|_| "Failed to write to log file")?;
-        log::info!("{}", fmt_string);
-        Ok(())
-    }
 }
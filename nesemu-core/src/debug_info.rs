@@ -0,0 +1,280 @@
+//! Debug info written by the cc65 linker with `ld65 --dbgfile`, which maps the code in a homebrew ROM back to
+//! the source lines and labels it was built from. This lets the debugger show which line of source is running,
+//! step a line at a time, and set breakpoints by label.
+//!
+//! The file has one record per line, a kind followed by a tab and its fields, such as `line` and
+//! `id=3,file=0,line=12,span=4`. A line of source refers to the spans of bytes it produced, and each span to the
+//! segment it was placed in. Only the records needed to place lines and labels are read, and the rest are skipped.
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+/// Line records from C source, which are preferred over the assembly generated from them
+const LINE_TYPE_EXTERNAL: u32 = 1;
+/// Line records from inside macro expansions, which are only used when nothing else covers an address
+const LINE_TYPE_MACRO: u32 = 2;
+/// The iNES header before PRG ROM in a .nes file
+const INES_HEADER_SIZE: usize = 16;
+
+/// The bytes produced by a single line of source
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SourceLine {
+    /// An index into [`DebugInfo::files`]
+    pub file: usize,
+    /// Counting from one, like an editor does
+    pub line: u32,
+    /// The CPU address of the first byte
+    pub address: usize,
+    pub size: usize,
+    /// The offset of the first byte into PRG ROM, if the linker wrote it straight into the ROM, which tells
+    /// apart code in banks that share the same addresses
+    pub prg_rom_offset: Option<usize>,
+    /// How well the line describes the code, higher being better: C over assembly over macro expansions
+    rank: u8,
+}
+
+/// A label, and the CPU address it was placed at
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub address: usize,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct DebugInfo {
+    files: Vec<String>,
+    /// Sorted by address
+    lines: Vec<SourceLine>,
+    /// Sorted by address
+    symbols: Vec<Symbol>,
+}
+
+struct Segment {
+    start: usize,
+    /// The offset into PRG ROM the segment was written at, if it was written into a .nes file
+    prg_rom_offset: Option<usize>,
+}
+
+struct Span {
+    segment: usize,
+    start: usize,
+    size: usize,
+}
+
+impl DebugInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads debug info from a file written by `ld65 --dbgfile`
+    ///
+    /// Fails if the file can't be read or has a malformed record
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        DebugInfo::parse(&text).map_err(|error| {
+            let message = format!("{}: {}", path.display(), error);
+            Error::new(ErrorKind::InvalidData, message)
+        })
+    }
+
+    /// Parses the contents of a file written by `ld65 --dbgfile`
+    ///
+    /// Fails with a description of the first malformed record found
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut files = HashMap::new();
+        let mut segments = HashMap::new();
+        let mut spans = HashMap::new();
+        // The file, line, type and spans of each line record, placed once every span has been read
+        let mut line_records = Vec::new();
+        let mut symbols = Vec::new();
+
+        for (idx, record) in text.lines().enumerate() {
+            let Some((kind, fields)) = record.split_once(char::is_whitespace) else {
+                continue;
+            };
+            let fields = parse_fields(fields);
+            let error = |message: &str| format!("line {}: {}", idx + 1, message);
+            let number = |key: &str| {
+                fields
+                    .get(key)
+                    .and_then(|value| parse_number(value))
+                    .ok_or_else(|| error(&format!("{} record has no valid {}", kind, key)))
+            };
+            match kind {
+                "file" => {
+                    let name = fields
+                        .get("name")
+                        .ok_or_else(|| error("file has no name"))?;
+                    files.insert(number("id")?, name.to_string());
+                }
+                "seg" => {
+                    let writes_rom = fields
+                        .get("oname")
+                        .is_some_and(|name| name.to_ascii_lowercase().ends_with(".nes"));
+                    let prg_rom_offset = match fields.get("ooffs").and_then(|o| parse_number(o)) {
+                        Some(offset) if writes_rom => offset.checked_sub(INES_HEADER_SIZE),
+                        _ => None,
+                    };
+                    let segment = Segment {
+                        start: number("start")?,
+                        prg_rom_offset,
+                    };
+                    segments.insert(number("id")?, segment);
+                }
+                "span" => {
+                    let span = Span {
+                        segment: number("seg")?,
+                        start: number("start")?,
+                        size: number("size")?,
+                    };
+                    spans.insert(number("id")?, span);
+                }
+                "line" => {
+                    // Lines that didn't produce any bytes, such as comments, have no spans
+                    let Some(span_ids) = fields.get("span") else {
+                        continue;
+                    };
+                    let span_ids = span_ids
+                        .split('+')
+                        .map(|id| parse_number(id).ok_or_else(|| error("invalid span id")))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let line_type = fields.get("type").and_then(|t| parse_number(t));
+                    let rank = match line_type.map(|t| t as u32) {
+                        Some(LINE_TYPE_EXTERNAL) => 2,
+                        Some(LINE_TYPE_MACRO) => 0,
+                        _ => 1,
+                    };
+                    line_records.push((number("file")?, number("line")? as u32, rank, span_ids));
+                }
+                // Only labels are addresses. Other symbols are constants, or imports of labels defined elsewhere
+                "sym" if fields.get("type").is_some_and(|t| *t == "lab") => {
+                    let name = fields.get("name").ok_or_else(|| error("sym has no name"))?;
+                    symbols.push(Symbol {
+                        name: name.to_string(),
+                        address: number("val")?,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut file_ids: Vec<usize> = files.keys().copied().collect();
+        file_ids.sort_unstable();
+        let file_index: HashMap<usize, usize> = file_ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index))
+            .collect();
+        let mut lines = Vec::new();
+        for (file, line, rank, span_ids) in line_records {
+            let file = *file_index
+                .get(&file)
+                .ok_or_else(|| format!("line {} refers to unknown file {}", line, file))?;
+            for span_id in span_ids {
+                let span = spans
+                    .get(&span_id)
+                    .ok_or_else(|| format!("line refers to unknown span {}", span_id))?;
+                let segment = segments
+                    .get(&span.segment)
+                    .ok_or_else(|| format!("span refers to unknown segment {}", span.segment))?;
+                lines.push(SourceLine {
+                    file,
+                    line,
+                    address: segment.start + span.start,
+                    size: span.size,
+                    prg_rom_offset: segment.prg_rom_offset.map(|offset| offset + span.start),
+                    rank,
+                });
+            }
+        }
+        lines.sort_by_key(|line| line.address);
+        symbols.sort_by_key(|symbol| symbol.address);
+
+        Ok(Self {
+            files: file_ids.into_iter().map(|id| files[&id].clone()).collect(),
+            lines,
+            symbols,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.symbols.is_empty()
+    }
+
+    /// The source files, as named to the assembler
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// Every line of source that produced bytes, in order of address
+    pub fn lines(&self) -> &[SourceLine] {
+        &self.lines
+    }
+
+    /// Every label, in order of address
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Finds the line of source that produced the byte at the given CPU address. When the offset into PRG ROM
+    /// mapped there is given, lines in other banks at the same address are ignored
+    pub fn line_at(&self, address: usize, prg_rom_offset: Option<usize>) -> Option<&SourceLine> {
+        let end = self.lines.partition_point(|line| line.address <= address);
+        self.lines[..end]
+            .iter()
+            .filter(|line| address < line.address + line.size)
+            .filter(|line| match (line.prg_rom_offset, prg_rom_offset) {
+                (Some(start), Some(offset)) => (start..start + line.size).contains(&offset),
+                _ => true,
+            })
+            .max_by_key(|line| line.rank)
+    }
+
+    /// Finds the label with the given name
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// Finds a label placed at the given address
+    pub fn symbol_at(&self, address: usize) -> Option<&Symbol> {
+        let start = self
+            .symbols
+            .partition_point(|symbol| symbol.address < address);
+        self.symbols
+            .get(start)
+            .filter(|symbol| symbol.address == address)
+    }
+}
+
+/// Splits a record's comma separated `key=value` fields, removing the quotes around strings
+fn parse_fields(text: &str) -> HashMap<&str, &str> {
+    let mut fields = HashMap::new();
+    let mut rest = text.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                let after = quoted.get(end + 1..).unwrap_or("");
+                (&quoted[..end], after)
+            }
+            None => match after.find(',') {
+                Some(end) => (&after[..end], &after[end..]),
+                None => (after, ""),
+            },
+        };
+        fields.insert(key.trim(), value);
+        rest = after.trim_start_matches(',');
+    }
+    fields
+}
+
+/// Parses a number written in decimal, or in hex with a leading 0x
+fn parse_number(text: &str) -> Option<usize> {
+    match text.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
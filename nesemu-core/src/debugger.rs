@@ -0,0 +1,314 @@
+//! Support for pausing emulation when certain conditions are met, so that the state of the console can be
+//! inspected at a precise moment in time.
+
+use std::{collections::BTreeSet, fmt::Display};
+
+use super::mappers::Banks;
+
+/// The ways the CPU can be interrupted
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interrupt {
+    /// The non-maskable interrupt, which the PPU raises at the start of VBLANK
+    Nmi,
+    /// The maskable interrupt, which cartridges raise through their IRQ line
+    Irq,
+    /// The BRK instruction, which jumps through the same vector as an IRQ
+    Brk,
+}
+
+impl Interrupt {
+    /// The address of the vector holding where the CPU jumps to
+    pub fn vector(self) -> usize {
+        match self {
+            Interrupt::Nmi => 0xFFFA,
+            Interrupt::Irq | Interrupt::Brk => 0xFFFE,
+        }
+    }
+}
+
+impl Display for Interrupt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Interrupt::Nmi => write!(f, "NMI from the PPU"),
+            Interrupt::Irq => write!(f, "IRQ from the cartridge"),
+            Interrupt::Brk => write!(f, "BRK instruction"),
+        }
+    }
+}
+
+/// The two kinds of memory that mappers switch banks of
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BankMemory {
+    Prg,
+    Chr,
+}
+
+impl Display for BankMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BankMemory::Prg => write!(f, "PRG"),
+            BankMemory::Chr => write!(f, "CHR"),
+        }
+    }
+}
+
+/// The ways the CPU can access a register
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::Read => write!(f, "Read of"),
+            Access::Write => write!(f, "Write to"),
+        }
+    }
+}
+
+/// Checks whether a CPU address is one of the PPU registers or their mirrors, or one of the APU and I/O registers
+pub fn is_register(address: usize) -> bool {
+    matches!(address, 0x2000..=0x4017)
+}
+
+/// Gets the address a register is known by, since the PPU's eight registers are mirrored all the way up to $3FFF
+pub(crate) fn register_address(address: usize) -> usize {
+    match address {
+        0x2000..=0x3FFF => 0x2000 + address % 8,
+        _ => address,
+    }
+}
+
+/// The reason emulation was paused before the end of a frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BreakReason {
+    /// The CPU is about to execute the instruction at the contained address
+    Breakpoint(usize),
+    /// The CPU just took an interrupt, and is about to execute the first instruction of its handler
+    Interrupt {
+        kind: Interrupt,
+        /// The address of the instruction that was interrupted, or of the BRK instruction
+        from: usize,
+        handler: usize,
+    },
+    /// A write to a mapper register just switched a bank. Only the first bank changed by the write is reported
+    BankSwitch {
+        memory: BankMemory,
+        /// Which of the mapper's switchable windows of memory the bank is in, counting from the lowest address
+        window: usize,
+        old: usize,
+        new: usize,
+        /// The address of the register written
+        address: usize,
+    },
+    /// An instruction just read or wrote a PPU, APU or I/O register with a breakpoint on it. Only the first such
+    /// access made by the instruction is reported
+    RegisterAccess {
+        access: Access,
+        /// The address of the register, with PPU registers given by their address in $2000-$2007
+        address: usize,
+        /// The address of the instruction that made the access
+        from: usize,
+    },
+    /// One of the [`Hooks`](super::hooks::Hooks) registered on the console asked to break
+    Hook,
+}
+
+impl Display for BreakReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreakReason::Breakpoint(address) => write!(f, "Breakpoint hit at ${:04X}", address),
+            BreakReason::Interrupt {
+                kind,
+                from,
+                handler,
+            } => write!(
+                f,
+                "{} at ${:04X}, jumping through the vector at ${:04X} to ${:04X}",
+                kind,
+                from,
+                kind.vector(),
+                handler
+            ),
+            BreakReason::BankSwitch {
+                memory,
+                window,
+                old,
+                new,
+                address,
+            } => write!(
+                f,
+                "{} bank {} switched from ${:02X} to ${:02X} by a write to ${:04X}",
+                memory, window, old, new, address
+            ),
+            BreakReason::RegisterAccess {
+                access,
+                address,
+                from,
+            } => write!(
+                f,
+                "{} ${:04X} by the instruction at ${:04X}",
+                access, address, from
+            ),
+            BreakReason::Hook => write!(f, "Break requested by a hook"),
+        }
+    }
+}
+
+pub struct Debugger {
+    breakpoints: BTreeSet<usize>,
+    register_breakpoints: BTreeSet<(usize, Access)>,
+    resuming: bool,
+    pub break_on_nmi: bool,
+    pub break_on_irq: bool,
+    pub break_on_brk: bool,
+    pub break_on_bank_switch: bool,
+    /// A break on an interrupt or bank switch, waiting to be reported before the next instruction
+    pending_break: Option<BreakReason>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeSet::new(),
+            register_breakpoints: BTreeSet::new(),
+            resuming: false,
+            break_on_nmi: false,
+            break_on_irq: false,
+            break_on_brk: false,
+            break_on_bank_switch: false,
+            pending_break: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) {
+        self.breakpoints.remove(&address);
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &usize> {
+        self.breakpoints.iter()
+    }
+
+    /// Breaks after any instruction that makes the given kind of access to the register at the given address,
+    /// which is one of the PPU registers at $2000-$3FFF or the APU and I/O registers at $4000-$4017. Accesses
+    /// made by OAM DMA don't count, only those made by instructions
+    pub fn add_register_breakpoint(&mut self, address: usize, access: Access) {
+        self.register_breakpoints
+            .insert((register_address(address), access));
+    }
+
+    pub fn remove_register_breakpoint(&mut self, address: usize, access: Access) {
+        self.register_breakpoints
+            .remove(&(register_address(address), access));
+    }
+
+    /// Gets the register breakpoints, with PPU registers given by their address in $2000-$2007
+    pub fn register_breakpoints(&self) -> impl Iterator<Item = &(usize, Access)> {
+        self.register_breakpoints.iter()
+    }
+
+    pub(crate) fn has_register_breakpoints(&self) -> bool {
+        !self.register_breakpoints.is_empty()
+    }
+
+    /// Marks emulation as paused at the current instruction, so that it is skipped by the next check
+    pub(crate) fn pause(&mut self) {
+        self.resuming = true;
+        self.pending_break = None;
+    }
+
+    /// Records that the CPU took an interrupt, breaking before the first instruction of its handler if breaking
+    /// on that kind of interrupt is enabled
+    pub(crate) fn interrupt_taken(&mut self, kind: Interrupt, from: usize, handler: usize) {
+        let enabled = match kind {
+            Interrupt::Nmi => self.break_on_nmi,
+            Interrupt::Irq => self.break_on_irq,
+            Interrupt::Brk => self.break_on_brk,
+        };
+        if enabled {
+            self.pending_break = Some(BreakReason::Interrupt {
+                kind,
+                from,
+                handler,
+            });
+        }
+    }
+
+    /// Records that a write to the mapper register at the given address changed the selected banks from `old` to
+    /// `new`, breaking before the next instruction if breaking on bank switches is enabled
+    pub(crate) fn banks_switched(&mut self, address: usize, old: &Banks, new: &Banks) {
+        if !self.break_on_bank_switch {
+            return;
+        }
+        let changes = |memory, old: &[usize], new: &[usize]| {
+            old.iter()
+                .zip(new)
+                .enumerate()
+                .find(|(_, (old, new))| old != new)
+                .map(|(window, (&old, &new))| BreakReason::BankSwitch {
+                    memory,
+                    window,
+                    old,
+                    new,
+                    address,
+                })
+        };
+        if let Some(reason) = changes(BankMemory::Prg, &old.prg, &new.prg)
+            .or_else(|| changes(BankMemory::Chr, &old.chr, &new.chr))
+        {
+            self.pending_break = Some(reason);
+        }
+    }
+
+    /// Records the registers accessed by the instruction at `from`, breaking before the next instruction if any of
+    /// the accesses has a breakpoint on it
+    pub(crate) fn registers_accessed(
+        &mut self,
+        accesses: impl IntoIterator<Item = (usize, Access)>,
+        from: usize,
+    ) {
+        let hit = accesses
+            .into_iter()
+            .find(|access| self.register_breakpoints.contains(access));
+        if let Some((address, access)) = hit {
+            self.pending_break = Some(BreakReason::RegisterAccess {
+                access,
+                address,
+                from,
+            });
+        }
+    }
+
+    /// Breaks before the next instruction on behalf of a hook, unless there is already a break to report
+    pub(crate) fn hook_break(&mut self) {
+        self.pending_break.get_or_insert(BreakReason::Hook);
+    }
+
+    /// Checks whether execution should break before running the instruction at the given address
+    ///
+    /// After a break, the first instruction executed when emulation resumes never breaks, otherwise we would
+    /// immediately break on the same breakpoint again.
+    pub(crate) fn check_instruction(&mut self, program_counter: usize) -> Option<BreakReason> {
+        if let Some(reason) = self.pending_break.take() {
+            // A breakpoint on the next instruction would break on it again when resuming
+            self.resuming = true;
+            return Some(reason);
+        }
+        if std::mem::take(&mut self.resuming) {
+            return None;
+        }
+
+        if self.breakpoints.contains(&program_counter) {
+            self.resuming = true;
+            Some(BreakReason::Breakpoint(program_counter))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Disassembles code straight from the CPU's view of memory, for debuggers that want to show what is about to
+//! run. Instructions are decoded with the same table the CPU executes them from, so the two always agree.
+//!
+//! Memory is read without side effects, so disassembling over hardware registers doesn't disturb them.
+
+use super::{
+    bus::Bus,
+    cpu::opcodes::{AddressMode, Opcode},
+};
+
+/// The vectors the CPU jumps through at $FFFA-$FFFF, with a name for each, in the order they appear in memory
+pub const VECTORS: [(&str, usize); 3] = [("NMI", 0xFFFA), ("Reset", 0xFFFC), ("IRQ/BRK", 0xFFFE)];
+
+/// A single disassembled instruction
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Instruction {
+    pub address: usize,
+    /// The opcode followed by its operands
+    pub bytes: Vec<u8>,
+    /// The instruction in assembly, such as `LDA $0300,X`. Bytes that aren't a known opcode are shown as data
+    pub text: String,
+}
+
+/// Disassembles the instruction at the given address
+///
+/// Fails if memory at the address can't be read
+pub fn disassemble(bus: &mut Bus, address: usize) -> Result<Instruction, &'static str> {
+    let opcode = bus.cpu_read_byte_no_modify(address)?;
+    let Ok(decoded) = Opcode::decode(opcode) else {
+        return Ok(Instruction {
+            address,
+            bytes: vec![opcode],
+            text: format!(".db ${:02X}", opcode),
+        });
+    };
+
+    let mut bytes = vec![opcode];
+    for i in 1..decoded.num_bytes as usize {
+        bytes.push(bus.cpu_read_byte_no_modify((address + i) & 0xFFFF)?);
+    }
+    let byte = bytes.get(1).copied().unwrap_or(0);
+    let word = u16::from_le_bytes([byte, bytes.get(2).copied().unwrap_or(0)]);
+    let operand = match decoded.mode {
+        // BRK's second byte is skipped rather than used, so it isn't shown as an operand
+        AddressMode::IMPLIED => String::new(),
+        AddressMode::ACCUMULATOR => String::from("A"),
+        AddressMode::IMMEDIATE => format!("#${:02X}", byte),
+        AddressMode::ZEROPAGE => format!("${:02X}", byte),
+        AddressMode::ZEROPAGEX => format!("${:02X},X", byte),
+        AddressMode::ZEROPAGEY => format!("${:02X},Y", byte),
+        AddressMode::ABSOLUTE(_) => format!("${:04X}", word),
+        AddressMode::ABSOLUTEX => format!("${:04X},X", word),
+        AddressMode::ABSOLUTEY => format!("${:04X},Y", word),
+        AddressMode::INDIRECT => format!("(${:04X})", word),
+        AddressMode::INDIRECTX => format!("(${:02X},X)", byte),
+        AddressMode::INDIRECTY => format!("(${:02X}),Y", byte),
+        // Branches are shown with the address they jump to, rather than the offset to it
+        AddressMode::RELATIVE => {
+            let target = (address as i32 + 2 + byte as i8 as i32) & 0xFFFF;
+            format!("${:04X}", target)
+        }
+    };
+    let text = if operand.is_empty() {
+        decoded.mnemonic.to_string()
+    } else {
+        format!("{} {}", decoded.mnemonic, operand)
+    };
+    Ok(Instruction {
+        address,
+        bytes,
+        text,
+    })
+}
+
+/// Disassembles the given number of instructions, one after another from the given address
+///
+/// Stops early if memory can't be read, or at the end of the address space
+pub fn disassemble_range(bus: &mut Bus, address: usize, count: usize) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(count);
+    let mut address = address;
+    while instructions.len() < count && address <= 0xFFFF {
+        let Ok(instruction) = disassemble(bus, address) else {
+            break;
+        };
+        address += instruction.bytes.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Reads the address a vector points to, such as one of [`VECTORS`]
+pub fn read_vector(bus: &mut Bus, vector: usize) -> Result<usize, &'static str> {
+    let low = bus.cpu_read_byte_no_modify(vector)?;
+    let high = bus.cpu_read_byte_no_modify(vector + 1)?;
+    Ok(u16::from_le_bytes([low, high]) as usize)
+}
@@ -0,0 +1,68 @@
+//! A gym-style interface for driving the emulator from code, such as when training reinforcement learning agents.
+//!
+//! Each step holds the given buttons on the first controller for exactly one frame, and returns the rendered
+//! frame, the contents of CPU RAM and the number of frames run since the last reset. Emulation is deterministic,
+//! so the same sequence of steps from a reset always produces the same observations. Nothing is drawn to a
+//! window, so no frontend is needed.
+
+use std::io::Error;
+
+use super::{controller::InputEvent, screen::Frame, RunResult, NES};
+
+pub struct NesEnv {
+    nes: NES,
+    frame: Frame,
+    frame_count: u64,
+}
+
+impl NesEnv {
+    pub fn new(rom_path: String) -> Result<Self, Error> {
+        Ok(Self {
+            nes: NES::new(rom_path)?,
+            frame: Frame::new(),
+            frame_count: 0,
+        })
+    }
+
+    /// Power cycles the console, returning the initial observation with a blank frame
+    pub fn reset(&mut self) -> Result<(&Frame, &[u8], u64), Error> {
+        self.nes.power_cycle()?;
+        self.frame = Frame::new();
+        self.frame_count = 0;
+        Ok(self.observation())
+    }
+
+    /// Runs one frame with the given buttons held on the first controller, using the bit layout of
+    /// [`InputEvent`]
+    ///
+    /// Any breakpoints set on the debugger are run through, so that a step is always exactly one frame
+    pub fn step(&mut self, controller_state: u8) -> Result<(&Frame, &[u8], u64), &'static str> {
+        self.nes
+            .bus_mut()
+            .controller
+            .set_state_from_window(InputEvent {
+                input_state: controller_state,
+            });
+        while let RunResult::Break(_) = self.nes.run_frame(&mut self.frame)? {}
+        self.frame_count += 1;
+        Ok(self.observation())
+    }
+
+    fn observation(&self) -> (&Frame, &[u8], u64) {
+        (&self.frame, self.nes.bus().cpu_ram(), self.frame_count)
+    }
+
+    /// Hashes the outcome of the last step, as a cheap way to check that two runs have stayed in sync
+    pub fn frame_hash(&self) -> u64 {
+        self.nes.frame_hash(&self.frame)
+    }
+
+    /// Gives access to the underlying console, for anything beyond what the observations include
+    pub fn nes(&self) -> &NES {
+        &self.nes
+    }
+
+    pub fn nes_mut(&mut self) -> &mut NES {
+        &mut self.nes
+    }
+}
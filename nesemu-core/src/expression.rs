@@ -0,0 +1,310 @@
+//! Expressions over the CPU registers and memory, such as `[$0300]+[$0301]*256`, for watching values that are
+//! spread over several addresses or need some arithmetic to make sense of.
+//!
+//! Numbers are decimal, or hexadecimal with a `$` prefix. `[address]` reads the byte at an address, and the
+//! registers are `A`, `X`, `Y`, `P`, `SP` and `PC`. Any other name is looked up in the RAM map, and reads the
+//! value of that entry. The operators are those of C: unary `-`, `~` and `!`, then
+//! `*`, `/`, `%`, `+`, `-`, `<<`, `>>`, the comparisons, `&`, `^` and `|`, from highest to lowest precedence.
+//! Comparisons and `!` give 1 for true and 0 for false.
+
+use tock_registers::interfaces::Readable;
+
+use super::NES;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    P,
+    SP,
+    PC,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+    LogicalNot,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOp {
+    Multiply,
+    Divide,
+    Remainder,
+    Add,
+    Subtract,
+    ShiftLeft,
+    ShiftRight,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Xor,
+    Or,
+}
+
+impl BinaryOp {
+    /// Higher binds tighter
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Remainder => 8,
+            BinaryOp::Add | BinaryOp::Subtract => 7,
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight => 6,
+            BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => 5,
+            BinaryOp::Equal | BinaryOp::NotEqual => 4,
+            BinaryOp::And => 3,
+            BinaryOp::Xor => 2,
+            BinaryOp::Or => 1,
+        }
+    }
+
+    fn apply(self, left: i64, right: i64) -> Result<i64, &'static str> {
+        Ok(match self {
+            BinaryOp::Multiply => left.wrapping_mul(right),
+            BinaryOp::Divide => left.checked_div(right).ok_or("Division by zero")?,
+            BinaryOp::Remainder => left.checked_rem(right).ok_or("Division by zero")?,
+            BinaryOp::Add => left.wrapping_add(right),
+            BinaryOp::Subtract => left.wrapping_sub(right),
+            BinaryOp::ShiftLeft => left.wrapping_shl(right as u32),
+            BinaryOp::ShiftRight => left.wrapping_shr(right as u32),
+            BinaryOp::Less => (left < right) as i64,
+            BinaryOp::LessEqual => (left <= right) as i64,
+            BinaryOp::Greater => (left > right) as i64,
+            BinaryOp::GreaterEqual => (left >= right) as i64,
+            BinaryOp::Equal => (left == right) as i64,
+            BinaryOp::NotEqual => (left != right) as i64,
+            BinaryOp::And => left & right,
+            BinaryOp::Xor => left ^ right,
+            BinaryOp::Or => left | right,
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Expression {
+    Number(i64),
+    Register(Register),
+    /// The value of the RAM map entry with the given name
+    Name(String),
+    /// The byte at the address given by the inner expression
+    Memory(Box<Expression>),
+    Unary(UnaryOp, Box<Expression>),
+    Binary(BinaryOp, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Parses an expression, as described in the module documentation
+    ///
+    /// Fails if the expression is malformed, such as having unbalanced brackets or a trailing operator
+    pub fn parse(text: &str) -> Result<Self, &'static str> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            position: 0,
+        };
+        let expression = parser.binary(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.text.len() {
+            return Err("Unexpected character in expression");
+        }
+        Ok(expression)
+    }
+
+    /// Evaluates the expression against the current state of the console. Memory is read without side effects,
+    /// so watching a register such as PPUSTATUS doesn't disturb the game
+    ///
+    /// Fails if the expression divides by zero, reads from an address that can't be read or uses a name that
+    /// isn't in the RAM map
+    pub fn evaluate(&self, nes: &mut NES) -> Result<i64, &'static str> {
+        Ok(match self {
+            Expression::Number(value) => *value,
+            Expression::Register(register) => {
+                let registers = nes.cpu().registers();
+                match register {
+                    Register::A => registers.accumulator as i64,
+                    Register::X => registers.x_reg as i64,
+                    Register::Y => registers.y_reg as i64,
+                    Register::P => registers.status_register.get() as i64,
+                    Register::SP => registers.stack_ptr as i64,
+                    Register::PC => registers.program_counter as i64,
+                }
+            }
+            Expression::Name(name) => {
+                let bus = nes.bus_mut();
+                let entry = bus.ram_map.find(name).ok_or("Unknown name")?.clone();
+                entry.read(bus)?
+            }
+            Expression::Memory(address) => {
+                let address = address.evaluate(nes)? as u16;
+                nes.bus_mut().cpu_read_byte_no_modify(address as usize)? as i64
+            }
+            Expression::Unary(op, operand) => {
+                let operand = operand.evaluate(nes)?;
+                match op {
+                    UnaryOp::Negate => operand.wrapping_neg(),
+                    UnaryOp::Not => !operand,
+                    UnaryOp::LogicalNot => (operand == 0) as i64,
+                }
+            }
+            Expression::Binary(op, left, right) => {
+                let left = left.evaluate(nes)?;
+                op.apply(left, right.evaluate(nes)?)?
+            }
+        })
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.position).copied()
+    }
+
+    /// Consumes the given text if it comes next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let matches = self.text[self.position..].starts_with(token.as_bytes());
+        if matches {
+            self.position += token.len();
+        }
+        matches
+    }
+
+    /// Parses operators binding at least as tightly as the given precedence, by precedence climbing
+    fn binary(&mut self, min_precedence: u8) -> Result<Expression, &'static str> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.peek_binary_op() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.binary_op();
+            let right = self.binary(op.precedence() + 1)?;
+            left = Expression::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn peek_binary_op(&mut self) -> Option<BinaryOp> {
+        let position = self.position;
+        let op = self.binary_op();
+        self.position = position;
+        op
+    }
+
+    fn binary_op(&mut self) -> Option<BinaryOp> {
+        // Longer operators first, so that `<<` isn't taken for `<`
+        const OPERATORS: [(&str, BinaryOp); 16] = [
+            ("<<", BinaryOp::ShiftLeft),
+            (">>", BinaryOp::ShiftRight),
+            ("<=", BinaryOp::LessEqual),
+            (">=", BinaryOp::GreaterEqual),
+            ("==", BinaryOp::Equal),
+            ("!=", BinaryOp::NotEqual),
+            ("*", BinaryOp::Multiply),
+            ("/", BinaryOp::Divide),
+            ("%", BinaryOp::Remainder),
+            ("+", BinaryOp::Add),
+            ("-", BinaryOp::Subtract),
+            ("<", BinaryOp::Less),
+            (">", BinaryOp::Greater),
+            ("&", BinaryOp::And),
+            ("^", BinaryOp::Xor),
+            ("|", BinaryOp::Or),
+        ];
+        OPERATORS
+            .iter()
+            .find(|(token, _)| self.eat(token))
+            .map(|(_, op)| *op)
+    }
+
+    fn unary(&mut self) -> Result<Expression, &'static str> {
+        let op = if self.eat("-") {
+            UnaryOp::Negate
+        } else if self.eat("~") {
+            UnaryOp::Not
+        } else if self.eat("!") {
+            UnaryOp::LogicalNot
+        } else {
+            return self.primary();
+        };
+        Ok(Expression::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expression, &'static str> {
+        if self.eat("(") {
+            let inner = self.binary(0)?;
+            return if self.eat(")") {
+                Ok(inner)
+            } else {
+                Err("Expected a closing parenthesis")
+            };
+        }
+        if self.eat("[") {
+            let address = self.binary(0)?;
+            return if self.eat("]") {
+                Ok(Expression::Memory(Box::new(address)))
+            } else {
+                Err("Expected a closing bracket")
+            };
+        }
+        if self.eat("$") {
+            let digits = self.take_while(|c| c.is_ascii_hexdigit());
+            return i64::from_str_radix(digits, 16)
+                .map(Expression::Number)
+                .map_err(|_| "Expected a hexadecimal number after $");
+        }
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                digits
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| "Number is too large")
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == b'_');
+                let register = match name.to_ascii_uppercase().as_str() {
+                    "A" => Register::A,
+                    "X" => Register::X,
+                    "Y" => Register::Y,
+                    "P" => Register::P,
+                    "SP" => Register::SP,
+                    "PC" => Register::PC,
+                    // Names are only looked up when evaluating, since the RAM map can change in the meantime
+                    _ => return Ok(Expression::Name(name.to_string())),
+                };
+                Ok(Expression::Register(register))
+            }
+            Some(_) => Err("Unexpected character in expression"),
+            None => Err("Expression ended unexpectedly"),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &str {
+        let start = self.position;
+        while self.text.get(self.position).is_some_and(|&c| predicate(c)) {
+            self.position += 1;
+        }
+        // Only ASCII characters are ever taken
+        std::str::from_utf8(&self.text[start..self.position]).unwrap_or_default()
+    }
+}
@@ -0,0 +1,25 @@
+//! Entry points for fuzzing the parts of the core that handle untrusted input. Only built with the `fuzzing`
+//! feature, for use by the fuzz targets in `fuzz/`.
+
+use std::io::Error;
+
+use super::mappers::new_mapper_from_bytes;
+
+/// Parses the data as a ROM file, and if that succeeds, reads the cartridge's entire PRG and CHR address space
+///
+/// Any panic, or any allocation far larger than the input, is a bug in the loader or mapper.
+pub fn load_rom(data: &[u8]) -> Result<(), Error> {
+    let (mapper, _) = new_mapper_from_bytes(data)?;
+    for address in 0x8000..=0xFFFF {
+        let _ = mapper.prg_read(address);
+    }
+    for address in 0x0000..=0x1FFF {
+        let _ = mapper.chr_read(address);
+    }
+    for pattern_idx in 0..=255 {
+        mapper.chr_read_pattern(0x0000, pattern_idx);
+        mapper.chr_read_pattern(0x1000, pattern_idx);
+    }
+    mapper.current_mirroring_mode();
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Many iNES dumps in circulation have wrong header bytes, from tools that wrote their name into the unused bytes
+//! or from headers written by hand before the mapper numbers settled. Known bad headers are replaced with corrected
+//! ones from `data/header_corrections.txt`, which describes its format at the top.
+//!
+//! Corrections are keyed by the CRC32 of everything after the header, rather than the PRG and CHR data that
+//! [`rom_db`](crate::rom_db) uses, since a wrong header can give the wrong sizes for those.
+
+use std::sync::OnceLock;
+
+const TABLE: &str = include_str!("../data/header_corrections.txt");
+
+/// Finds the corrected header for a ROM in a table of corrections, given the CRC32 of everything after its
+/// header
+pub fn lookup(table: &[(u32, [u8; 16])], crc32: u32) -> Option<[u8; 16]> {
+    table
+        .iter()
+        .find(|(entry_crc32, _)| *entry_crc32 == crc32)
+        .map(|(_, header)| *header)
+}
+
+/// The corrections bundled with the emulator, which are applied to every ROM that is loaded
+pub fn bundled() -> &'static [(u32, [u8; 16])] {
+    static ENTRIES: OnceLock<Vec<(u32, [u8; 16])>> = OnceLock::new();
+    ENTRIES.get_or_init(|| parse(TABLE))
+}
+
+/// Parses a table of header corrections in the format of `data/header_corrections.txt`, into the CRC32 and
+/// corrected header of each entry. Invalid lines are skipped with a warning
+pub fn parse(table: &str) -> Vec<(u32, [u8; 16])> {
+    table
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(line, _)| line).trim())
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let entry = parse_entry(line);
+            if entry.is_none() {
+                log::warn!("Skipping invalid header correction '{}'", line);
+            }
+            entry
+        })
+        .collect()
+}
+
+fn parse_entry(line: &str) -> Option<(u32, [u8; 16])> {
+    let mut fields = line.split_whitespace();
+    let crc32 = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let header_hex = fields.next()?;
+    if header_hex.len() != 32 || fields.next().is_some() {
+        return None;
+    }
+    let mut header = [0u8; 16];
+    for (idx, byte) in header.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(header_hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+    }
+    // A correction that isn't an iNES header at all would only make the ROM fail to load
+    header.starts_with(b"NES\x1A").then_some((crc32, header))
+}
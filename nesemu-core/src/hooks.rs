@@ -0,0 +1,124 @@
+//! Callbacks that programs embedding the emulator can register to observe emulation as it runs, and to pause it,
+//! without having to change the core. They are registered on [`NES::hooks`], and are given the console itself,
+//! so they can inspect or change any of its state.
+//!
+//! Hooks run once the instruction or DMA transfer that triggered them has finished, in the order: instruction,
+//! memory writes, NMI, then frame complete. A hook returning [`Control::Break`] pauses emulation before the next
+//! instruction, which is reported as [`BreakReason::Hook`](super::debugger::BreakReason::Hook).
+//!
+//! With no hooks registered, the only cost to emulation is checking for them after each instruction.
+
+use super::NES;
+
+/// What a hook wants emulation to do next
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Control {
+    Continue,
+    /// Pause emulation before the next instruction, like a breakpoint does
+    Break,
+}
+
+type FrameHook = Box<dyn FnMut(&mut NES) -> Control + Send>;
+type InstructionHook = Box<dyn FnMut(&mut NES, usize) -> Control + Send>;
+type MemoryWriteHook = Box<dyn FnMut(&mut NES, usize, u8) -> Control + Send>;
+
+#[derive(Default)]
+pub struct Hooks {
+    frame_complete: Vec<FrameHook>,
+    instruction: Vec<InstructionHook>,
+    memory_write: Vec<MemoryWriteHook>,
+    nmi: Vec<FrameHook>,
+}
+
+impl Hooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls the hook every time the PPU finishes drawing a frame
+    pub fn on_frame_complete(&mut self, hook: impl FnMut(&mut NES) -> Control + Send + 'static) {
+        self.frame_complete.push(Box::new(hook));
+    }
+
+    /// Calls the hook after every instruction the CPU runs, with the address of the instruction
+    pub fn on_instruction(
+        &mut self,
+        hook: impl FnMut(&mut NES, usize) -> Control + Send + 'static,
+    ) {
+        self.instruction.push(Box::new(hook));
+    }
+
+    /// Calls the hook after every instruction that writes to the CPU bus, once for each write, with the address
+    /// and value written. This includes writes to registers and the cartridge as well as to RAM, but not the
+    /// writes OAM DMA makes to OAM
+    pub fn on_memory_write(
+        &mut self,
+        hook: impl FnMut(&mut NES, usize, u8) -> Control + Send + 'static,
+    ) {
+        self.memory_write.push(Box::new(hook));
+    }
+
+    /// Calls the hook every time the CPU takes an NMI, before the first instruction of its handler runs
+    pub fn on_nmi(&mut self, hook: impl FnMut(&mut NES) -> Control + Send + 'static) {
+        self.nmi.push(Box::new(hook));
+    }
+
+    /// Removes every hook. The hooks that are running are set aside while they run, so a hook can't remove them
+    pub fn clear(&mut self) {
+        *self = Hooks::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frame_complete.is_empty()
+            && self.instruction.is_empty()
+            && self.memory_write.is_empty()
+            && self.nmi.is_empty()
+    }
+
+    pub(crate) fn wants_memory_writes(&self) -> bool {
+        !self.memory_write.is_empty()
+    }
+
+    /// Calls the hooks for what happened during a step of emulation, returning whether any of them asked to
+    /// break
+    pub(crate) fn run(
+        &mut self,
+        nes: &mut NES,
+        instruction: Option<usize>,
+        writes: &[(usize, u8)],
+        nmi: bool,
+        frame_complete: bool,
+    ) -> bool {
+        // Every hook runs, even once one of them has asked to break
+        let mut break_requested = false;
+        if let Some(address) = instruction {
+            for hook in &mut self.instruction {
+                break_requested |= hook(nes, address) == Control::Break;
+            }
+        }
+        for &(address, value) in writes {
+            for hook in &mut self.memory_write {
+                break_requested |= hook(nes, address, value) == Control::Break;
+            }
+        }
+        if nmi {
+            for hook in &mut self.nmi {
+                break_requested |= hook(nes) == Control::Break;
+            }
+        }
+        if frame_complete {
+            for hook in &mut self.frame_complete {
+                break_requested |= hook(nes) == Control::Break;
+            }
+        }
+        break_requested
+    }
+
+    /// Adds the hooks from another set after these ones
+    pub(crate) fn append(&mut self, other: &mut Hooks) {
+        self.frame_complete.append(&mut other.frame_complete);
+        self.instruction.append(&mut other.instruction);
+        self.memory_write.append(&mut other.memory_write);
+        self.nmi.append(&mut other.nmi);
+    }
+}
@@ -0,0 +1,435 @@
+//! The emulation core of NESEmu. This crate contains the CPU, PPU, bus and mappers, and has no dependency on
+//! any particular frontend. Frontends drive the emulation one frame at a time, and receive the rendered
+//! frame through the [`screen::FrameBuffer`] trait.
+
+use std::{
+    hash::Hasher,
+    io::{Error, ErrorKind},
+};
+
+use tock_registers::interfaces::Readable;
+use xxhash_rust::xxh3::Xxh3Default;
+
+use self::{
+    apu_log::{ApuLog, ApuWrite},
+    audio::Audio,
+    bus::Bus,
+    coverage::Coverage,
+    cpu::CPU,
+    debug_info::{DebugInfo, SourceLine},
+    debugger::{BreakReason, Debugger, Interrupt},
+    hooks::Hooks,
+    ppu::PPU,
+    profiler::Profiler,
+    screen::{Frame, FrameBuffer},
+};
+
+pub mod apu_log;
+pub mod archive;
+pub mod audio;
+pub mod bus;
+pub mod cheats;
+pub mod controller;
+pub mod coverage;
+pub mod cpu;
+pub mod debug_info;
+pub mod debugger;
+pub mod disassembly;
+pub mod env;
+pub mod expression;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+pub mod header_db;
+pub mod hooks;
+mod mappers;
+pub mod overrides;
+pub mod patch;
+pub mod ppu;
+pub mod profiler;
+pub mod ram_map;
+pub mod rom_db;
+pub mod screen;
+pub mod snapshot;
+mod util;
+
+pub struct NES {
+    cpu: CPU,
+    ppu: PPU,
+    bus: Bus,
+    pending_interrupt: bool,
+    dma_read_cycle: bool,
+    cycles: u64,
+    /// The number of frames the PPU has finished drawing since power on
+    frames: u64,
+    rom_path: String,
+    patch_path: Option<String>,
+    /// The ROM image, if it was loaded from memory rather than from a file, kept so a power cycle can reload it
+    rom: Option<Vec<u8>>,
+    pub debugger: Debugger,
+    pub profiler: Profiler,
+    pub coverage: Coverage,
+    pub apu_log: ApuLog,
+    pub audio: Audio,
+    /// Maps code back to the source it was built from, if the ROM's debug info has been loaded
+    pub debug_info: DebugInfo,
+    /// Callbacks registered by a program embedding the emulator
+    pub hooks: Hooks,
+}
+
+/// The outcome of running the emulation for a frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunResult {
+    FrameComplete,
+    /// The debugger paused emulation partway through the frame. Running again resumes from where it left off
+    Break(BreakReason),
+}
+
+impl NES {
+    pub const FRAME_TIME: f64 = 1.0 / 60.098814;
+    /// The most instructions [`NES::step_source_line`] runs before giving up on reaching another line
+    pub const MAX_SOURCE_STEP_INSTRUCTIONS: usize = 100_000;
+    /// Loads the given ROM, along with a patch of the same name next to it if there is one, as found by
+    /// [`patch::find_patch`]
+    pub fn new(rom_path: String) -> Result<Self, Error> {
+        let patch_path = patch::find_patch(&rom_path);
+        NES::with_patch(rom_path, patch_path)
+    }
+
+    /// Loads the given ROM with the given IPS or BPS patch applied to it, or with no patch at all
+    pub fn with_patch(rom_path: String, patch_path: Option<String>) -> Result<Self, Error> {
+        let bus = Bus::new(rom_path.as_str(), patch_path.as_deref())?;
+        NES::with_bus(bus, rom_path, patch_path, None)
+    }
+
+    /// Loads an iNES ROM image that is already in memory, such as one piped in or built by a program embedding
+    /// the emulator. No patch is applied, and nothing is read from or saved to files next to a ROM, such as its
+    /// save file or cheats
+    pub fn from_bytes(rom: Vec<u8>) -> Result<Self, Error> {
+        let bus = Bus::from_bytes(&rom)?;
+        NES::with_bus(bus, String::new(), None, Some(rom))
+    }
+
+    fn with_bus(
+        mut bus: Bus,
+        rom_path: String,
+        patch_path: Option<String>,
+        rom: Option<Vec<u8>>,
+    ) -> Result<Self, Error> {
+        let cpu = CPU::new(&mut bus).map_err(|_| Error::from(ErrorKind::AddrNotAvailable))?;
+        let rom_info = bus.rom_info();
+        let coverage = Coverage::new(rom_info.prg_rom_size, rom_info.chr_rom_size);
+        Ok(Self {
+            rom_path,
+            patch_path,
+            rom,
+            cpu,
+            ppu: PPU::new(),
+            bus,
+            pending_interrupt: false,
+            dma_read_cycle: true,
+            cycles: 0,
+            frames: 0,
+            debugger: Debugger::new(),
+            profiler: Profiler::new(),
+            coverage,
+            apu_log: ApuLog::new(),
+            audio: Audio::new(),
+            debug_info: DebugInfo::new(),
+            hooks: Hooks::new(),
+        })
+    }
+
+    /// Equivalent to pressing the console's Reset button
+    ///
+    /// The CPU jumps back to the reset vector and the PPU registers are cleared, but the contents of RAM are
+    /// left untouched, which is what allows some games to implement "soft reset" features
+    pub fn soft_reset(&mut self) -> Result<(), &'static str> {
+        self.bus.reset();
+        self.ppu = PPU::new();
+        self.pending_interrupt = false;
+        self.dma_read_cycle = true;
+        self.cpu.reset(&mut self.bus)
+    }
+
+    /// Equivalent to pressing the console's Power button twice
+    ///
+    /// All of the console's state is thrown away and rebuilt from the cartridge, as if the console had just
+    /// been turned on
+    pub fn power_cycle(&mut self) -> Result<(), Error> {
+        // The save is what survives the console being turned off
+        if let Err(err) = self.bus.save() {
+            log::warn!("Failed to write save file: {err}");
+        }
+        let mut bus = match &self.rom {
+            Some(rom) => Bus::from_bytes(rom)?,
+            None => Bus::new(self.rom_path.as_str(), self.patch_path.as_deref())?,
+        };
+        let cpu = CPU::new(&mut bus).map_err(|_| Error::from(ErrorKind::AddrNotAvailable))?;
+        // The RAM map describes the game rather than the console, so it survives too
+        bus.ram_map = std::mem::take(&mut self.bus.ram_map);
+        self.bus = bus;
+        self.cpu = cpu;
+        self.ppu = PPU::new();
+        self.pending_interrupt = false;
+        self.dma_read_cycle = true;
+        self.cycles = 0;
+        self.frames = 0;
+        Ok(())
+    }
+
+    /// Runs the emulation until the PPU has finished drawing a full frame into the framebuffer, or until the
+    /// debugger requests a break
+    ///
+    /// Fails if the CPU encounters an error, in which case the frame is left partially drawn
+    pub fn run_frame<T: FrameBuffer>(&mut self, fb: &mut T) -> Result<RunResult, &'static str> {
+        loop {
+            if !self.is_dma_cycle() {
+                if let Some(reason) = self.debugger.check_instruction(self.cpu.program_counter()) {
+                    return Ok(RunResult::Break(reason));
+                }
+            }
+
+            if self.advance(fb)? {
+                return Ok(RunResult::FrameComplete);
+            }
+        }
+    }
+
+    /// Runs exactly one CPU instruction, first completing any OAM DMA transfer that is due
+    ///
+    /// Breakpoints are not checked, and afterwards the debugger considers emulation to be paused at the new
+    /// program counter, so that running again does not immediately break on it
+    pub fn step_instruction<T: FrameBuffer>(&mut self, fb: &mut T) -> Result<(), &'static str> {
+        loop {
+            let dma_cycle = self.is_dma_cycle();
+            self.advance(fb)?;
+            if !dma_cycle {
+                break;
+            }
+        }
+        self.debugger.pause();
+        Ok(())
+    }
+
+    /// Runs instructions until the program counter reaches a different line of source in the debug info, such
+    /// as the next line, or the first line of a subroutine that was called. Code with no debug info, such as a
+    /// library, is run through without stopping
+    ///
+    /// Without any debug info this runs a single instruction. Stops anyway after
+    /// [`NES::MAX_SOURCE_STEP_INSTRUCTIONS`], so that a line that loops forever doesn't hang the caller
+    pub fn step_source_line<T: FrameBuffer>(&mut self, fb: &mut T) -> Result<(), &'static str> {
+        if self.debug_info.is_empty() {
+            return self.step_instruction(fb);
+        }
+        let location = |nes: &Self| {
+            nes.source_line(nes.cpu.program_counter())
+                .map(|line| (line.file, line.line))
+        };
+        let start = location(self);
+        for _ in 0..Self::MAX_SOURCE_STEP_INSTRUCTIONS {
+            self.step_instruction(fb)?;
+            let current = location(self);
+            if current.is_some() && current != start {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the line of source that produced the code at the given CPU address, in whichever bank is mapped
+    /// there
+    pub fn source_line(&self, address: usize) -> Option<&SourceLine> {
+        self.debug_info
+            .line_at(address, self.bus.prg_rom_offset(address))
+    }
+
+    fn is_dma_cycle(&self) -> bool {
+        self.dma_read_cycle && self.bus.pending_dma()
+    }
+
+    /// Runs either a DMA transfer or a single CPU instruction, and catches the PPU up to the CPU
+    ///
+    /// Returns whether the PPU finished drawing a frame in the process
+    fn advance<T: FrameBuffer>(&mut self, fb: &mut T) -> Result<bool, &'static str> {
+        let dma_cycle = self.is_dma_cycle();
+        self.pending_interrupt = self.ppu.generated_interrupt();
+
+        // Comparing the banks before and after every instruction is slow, so it's only done when it's needed
+        let banks = self.debugger.break_on_bank_switch.then(|| self.bus.banks());
+        self.bus.watch_registers = self.debugger.has_register_breakpoints();
+        self.bus.record_writes = self.hooks.wants_memory_writes();
+        let start = self.profiler.start();
+        let cycles: u16 = if dma_cycle {
+            self.bus.process_dma()?;
+            513 // Number of cycles it takes for a DMA transfer
+        } else {
+            self.cpu.step(&mut self.bus, &mut self.pending_interrupt)? as u16
+        };
+        let mut nmi_taken = false;
+        if let Some((kind, from)) = self.cpu.take_interrupt() {
+            self.debugger
+                .interrupt_taken(kind, from, self.cpu.program_counter());
+            nmi_taken = kind == Interrupt::Nmi;
+        }
+        if self.coverage.enabled() {
+            if let Some((address, len)) = self.cpu.last_instruction() {
+                for address in address..address + len as usize {
+                    if let Some(offset) = self.bus.prg_rom_offset(address) {
+                        self.coverage.mark_code(offset, address);
+                    }
+                }
+            }
+        }
+        for (address, value) in self.bus.take_apu_writes() {
+            if self.apu_log.enabled() {
+                self.apu_log.record(ApuWrite {
+                    frame: self.frames,
+                    cycle: self.cycles,
+                    address,
+                    value,
+                });
+            }
+        }
+        // OAM DMA writes to $2004 on its own, rather than an instruction doing so
+        let accesses = self.bus.take_register_accesses();
+        match self.cpu.last_instruction() {
+            Some((from, _)) if !dma_cycle => self.debugger.registers_accessed(accesses, from),
+            _ => drop(accesses),
+        }
+        if let (Some(address), Some(banks)) = (self.bus.take_mapper_write(), banks) {
+            self.debugger
+                .banks_switched(address, &banks, &self.bus.banks());
+        }
+        self.cycles += cycles as u64;
+        self.bus.clock_mapper(cycles);
+        self.audio.clock(cycles, self.bus.expansion_audio());
+        let cpu_done = self.profiler.cpu_done(start, dma_cycle);
+
+        // 3 cycles per CPU cycle
+        // Detect when the GPU finished all of its scanlines and looped back over to scanline 0
+        let did_finish_frame = self
+            .ppu
+            .step_cycles(fb, &mut self.bus, 3 * cycles as usize)?;
+        self.profiler.ppu_done(cpu_done);
+        if did_finish_frame {
+            self.frames += 1;
+        } else {
+            self.dma_read_cycle = !self.dma_read_cycle;
+        }
+        if !self.hooks.is_empty() {
+            let instruction = self.cpu.last_instruction().filter(|_| !dma_cycle);
+            self.run_hooks(
+                instruction.map(|(address, _)| address),
+                nmi_taken,
+                did_finish_frame,
+            );
+        }
+        Ok(did_finish_frame)
+    }
+
+    /// Calls the hooks for what happened during the last call to [`NES::advance`]
+    fn run_hooks(&mut self, instruction: Option<usize>, nmi: bool, frame_complete: bool) {
+        let writes: Vec<_> = self.bus.take_writes().collect();
+        // The hooks are set aside while they run, so that they can be given the whole console
+        let mut hooks = std::mem::take(&mut self.hooks);
+        let break_requested = hooks.run(self, instruction, &writes, nmi, frame_complete);
+        // Any hooks registered by the ones that ran go after them
+        hooks.append(&mut self.hooks);
+        self.hooks = hooks;
+        if break_requested {
+            self.debugger.hook_break();
+        }
+    }
+
+    /// Gets the number of CPU cycles run since the console was powered on, including those spent on DMA
+    pub fn cycle_count(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Gets the number of frames the PPU has finished drawing since the console was powered on
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    /// Computes a hash of the console state that determines how emulation proceeds: the CPU registers, CPU RAM
+    /// and OAM
+    ///
+    /// Two consoles started from the same ROM that received the same inputs always have equal hashes, so
+    /// comparing them is a cheap way to detect that emulation has diverged
+    pub fn state_hash(&self) -> u64 {
+        let registers = self.cpu.registers();
+        let mut hasher = Xxh3Default::new();
+        hasher.write(&[
+            registers.accumulator,
+            registers.x_reg,
+            registers.y_reg,
+            registers.status_register.get(),
+            registers.stack_ptr as u8,
+        ]);
+        hasher.write(&(registers.program_counter as u16).to_le_bytes());
+        hasher.write(self.bus.cpu_ram());
+        hasher.write(&self.bus.oam_ram);
+        hasher.finish()
+    }
+
+    /// Computes a hash identifying the outcome of a frame, from both the rendered image and the console state
+    ///
+    /// Like [`NES::state_hash`], it is the same on every machine and build, so hashes logged at the end of each
+    /// frame can be compared against those from another run to find exactly where emulation diverged
+    pub fn frame_hash(&self, frame: &Frame) -> u64 {
+        let mut hasher = Xxh3Default::new();
+        hasher.write(&frame.hash().to_le_bytes());
+        hasher.write(&self.state_hash().to_le_bytes());
+        hasher.finish()
+    }
+
+    /// Writes the cartridge's battery backed RAM or EEPROM to the save file next to the ROM, as described in
+    /// [`Bus::save`]
+    pub fn save(&mut self) -> Result<(), Error> {
+        self.bus.save()
+    }
+
+    /// Enables logging of every executed instruction to the given file, in the same format as the nestest log
+    pub fn set_trace_file(&mut self, path: &str) {
+        self.cpu.set_trace_file(path);
+    }
+
+    /// Gets the path of the patch applied to the ROM, if any
+    pub fn patch_path(&self) -> Option<&str> {
+        self.patch_path.as_deref()
+    }
+
+    /// Gets the path the ROM was loaded from, which is empty if it was loaded with [`NES::from_bytes`]
+    pub fn rom_path(&self) -> &str {
+        &self.rom_path
+    }
+
+    /// Gets what is known about the loaded ROM, such as its hash and canonical title
+    pub fn rom_info(&self) -> &rom_db::RomInfo {
+        self.bus.rom_info()
+    }
+
+    pub fn cpu(&self) -> &CPU {
+        &self.cpu
+    }
+
+    pub fn cpu_mut(&mut self) -> &mut CPU {
+        &mut self.cpu
+    }
+
+    pub fn bus(&self) -> &Bus {
+        &self.bus
+    }
+
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+
+    pub fn ppu(&self) -> &PPU {
+        &self.ppu
+    }
+
+    pub fn ppu_mut(&mut self) -> &mut PPU {
+        &mut self.ppu
+    }
+}
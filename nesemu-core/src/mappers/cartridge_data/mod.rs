@@ -0,0 +1,227 @@
+//! Stores the cartridge data, including header information. Normally you would not access this directly,
+//! and would instead make queries through the associated Mapper.
+
+use core::slice;
+use std::io::{Error, ErrorKind, Read};
+
+use tock_registers::interfaces::{ReadWriteable, Readable, Writeable};
+
+use self::ines::{Flags1, Flags2, INESHeader};
+use crate::{
+    header_db,
+    overrides::{Mirroring, Overrides},
+    rom_db::{Region, RomHash},
+    util::crc32,
+};
+
+pub(super) mod ines;
+
+enum CHR {
+    ROM(Vec<u8>),
+    RAM(Vec<u8>),
+}
+
+pub struct CartridgeData {
+    pub(super) header: INESHeader,
+    pub(super) mapper_id: u16,
+    pub(super) submapper: u8,
+    _trainer: Option<[u8; 512]>,
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr_data: CHR,
+}
+
+impl CartridgeData {
+    const VALID_MAGIC: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
+    const HEADER_SIZE: u8 = 16;
+    const PRG_ROM_BLOCK_SZ: usize = 16384;
+    const CHR_ROM_BLOCK_SZ: usize = 8192;
+    const PRG_RAM_BLOCK_SZ: usize = 8192;
+
+    /// Parses an iNES ROM image that is already in memory
+    pub fn from_bytes(rom: &[u8]) -> Result<Self, Error> {
+        CartridgeData::from_reader(rom)
+    }
+
+    /// Parses an iNES ROM image from any source of bytes
+    ///
+    /// Fails rather than panicking if the data is malformed, including when the header claims more data than
+    /// is actually present.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, Error> {
+        // Look for a correction to the header before parsing it, since a bad header can throw off where the
+        // rest of the data is read from
+        let mut header_bytes = [0u8; CartridgeData::HEADER_SIZE as usize];
+        reader.read_exact(&mut header_bytes)?;
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body)?;
+        let body_crc32 = crc32(&body);
+        if let Some(corrected) = header_db::lookup(header_db::bundled(), body_crc32)
+            .filter(|header| *header != header_bytes)
+        {
+            log::info!("Corrected the header of ROM {:08x}", body_crc32);
+            header_bytes = corrected;
+        }
+        let mut reader = header_bytes.as_slice().chain(body.as_slice());
+
+        // Validate the magic number string
+        let mut magic = [0u8; CartridgeData::VALID_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != CartridgeData::VALID_MAGIC {
+            return Err(Error::from(ErrorKind::InvalidInput));
+        }
+        // Read in header data
+        let mut header = INESHeader::default();
+        reader.read_exact(slice::from_mut(&mut header.prg_rom_size))?;
+        reader.read_exact(slice::from_mut(&mut header.chr_rom_size))?;
+        let mut flags1 = 0;
+        reader.read_exact(slice::from_mut(&mut flags1))?;
+        header.flags1.set(flags1);
+        let mut flags2 = 0;
+        reader.read_exact(slice::from_mut(&mut flags2))?;
+        header.flags2.set(flags2);
+        reader.read_exact(slice::from_mut(&mut header.prg_ram_size))?;
+        reader.read_exact(slice::from_mut(&mut header.tv_system))?;
+        // Skip the rest of the header
+        let mut padding = [0u8; CartridgeData::HEADER_SIZE as usize - 10];
+        reader.read_exact(&mut padding)?;
+        // Every cartridge needs at least some PRG ROM to hold the CPU's vectors
+        if header.prg_rom_size == 0 {
+            return Err(Error::from(ErrorKind::InvalidData));
+        }
+        // read trainer, if it exists
+        let mut _trainer = None;
+        if header.flags1.is_set(Flags1::HAS_TRAINER) {
+            let mut trainer_data = [0u8; 512];
+            reader.read_exact(&mut trainer_data)?;
+            _trainer = Some(trainer_data);
+        }
+        // Read PRG ROM
+        let prg_rom = CartridgeData::read_block(
+            &mut reader,
+            header.prg_rom_size as usize * CartridgeData::PRG_ROM_BLOCK_SZ,
+        )?;
+        // Read CHR ROM or RAM, depending on which this cartridge has
+        let chr_data = if header.chr_rom_size != 0 {
+            let chr_rom = CartridgeData::read_block(
+                &mut reader,
+                header.chr_rom_size as usize * CartridgeData::CHR_ROM_BLOCK_SZ,
+            )?;
+            CHR::ROM(chr_rom)
+        } else {
+            let chr_ram = vec![0; CartridgeData::CHR_ROM_BLOCK_SZ];
+            CHR::RAM(chr_ram)
+        };
+        // A size of zero is meant to be read as a single block, for compatibility with older ROM images
+        let prg_ram =
+            vec![0; header.prg_ram_size.max(1) as usize * CartridgeData::PRG_RAM_BLOCK_SZ];
+        let mapper_id = (header.flags1.read(Flags1::MAPPER_LOWER)
+            + (header.flags2.read(Flags2::MAPPER_UPPER) << 4))
+            .into();
+        Ok(Self {
+            header,
+            mapper_id,
+            // Submappers only exist in NES 2.0 headers, which aren't parsed yet
+            submapper: 0,
+            _trainer,
+            prg_rom,
+            prg_ram,
+            chr_data,
+        })
+    }
+
+    /// Reads exactly `size` bytes
+    ///
+    /// The buffer grows as data is actually read, so a header claiming a huge size doesn't cause a huge
+    /// allocation up front
+    fn read_block(reader: &mut impl Read, size: usize) -> Result<Vec<u8>, Error> {
+        let mut block = Vec::new();
+        reader.take(size as u64).read_to_end(&mut block)?;
+        if block.len() != size {
+            return Err(Error::from(ErrorKind::UnexpectedEof));
+        }
+        Ok(block)
+    }
+
+    /// The region given by the header, which iNES headers can only give as NTSC or PAL
+    pub fn region(&self) -> Region {
+        match self.header.tv_system & 1 {
+            0 => Region::Ntsc,
+            _ => Region::Pal,
+        }
+    }
+
+    /// Replaces the header's settings for the board with the given overrides, wherever they give one. The region
+    /// and controller belong to the console rather than the board, so they are left to the caller
+    pub fn apply_overrides(&mut self, overrides: &Overrides) {
+        if let Some(mapper) = overrides.mapper {
+            self.mapper_id = mapper;
+        }
+        if let Some(submapper) = overrides.submapper {
+            self.submapper = submapper;
+        }
+        if let Some(mirroring) = overrides.mirroring {
+            let mirroring = match mirroring {
+                Mirroring::Horizontal => Flags1::MIRRORING::HORZ,
+                Mirroring::Vertical => Flags1::MIRRORING::VERT,
+            };
+            self.header
+                .flags1
+                .modify(mirroring + Flags1::IGNORE_MIRRORING::CLEAR);
+        }
+    }
+
+    /// Hashes the cartridge's PRG and CHR ROM, to identify it
+    pub fn hash(&self) -> RomHash {
+        match &self.chr_data {
+            CHR::ROM(chr_rom) => RomHash::new(&self.prg_rom, chr_rom),
+            CHR::RAM(_) => RomHash::new(&self.prg_rom, &[]),
+        }
+    }
+
+    pub fn get_prg_rom(&self) -> &[u8] {
+        &self.prg_rom
+    }
+
+    pub fn get_prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+
+    pub fn get_prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.prg_ram
+    }
+
+    /// Gets the PRG RAM if the header says it's battery backed, so that it needs saving
+    pub fn battery_ram(&self) -> Option<&[u8]> {
+        self.header
+            .flags1
+            .is_set(Flags1::HAS_PRG_RAM)
+            .then_some(self.prg_ram.as_slice())
+    }
+
+    /// Restores the contents of PRG RAM, such as from a save file. Data past the end of PRG RAM is ignored
+    pub fn load_prg_ram(&mut self, data: &[u8]) {
+        let len = data.len().min(self.prg_ram.len());
+        self.prg_ram[..len].copy_from_slice(&data[..len]);
+    }
+
+    pub fn get_chr_ram(&mut self) -> Option<&mut [u8]> {
+        match &mut self.chr_data {
+            CHR::ROM(_) => None,
+            CHR::RAM(data) => Some(data),
+        }
+    }
+
+    /// The size of CHR ROM, which is zero for cartridges with CHR RAM instead
+    pub fn chr_rom_size(&self) -> usize {
+        match &self.chr_data {
+            CHR::ROM(data) => data.len(),
+            CHR::RAM(_) => 0,
+        }
+    }
+
+    pub fn get_chr_rom(&self) -> &[u8] {
+        match &self.chr_data {
+            CHR::ROM(data) | CHR::RAM(data) => data,
+        }
+    }
+}
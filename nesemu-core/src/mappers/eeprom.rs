@@ -0,0 +1,219 @@
+//! Serial EEPROMs, which some boards save games to instead of battery backed RAM. The game drives the clock (SCL)
+//! and data (SDA) lines of an I2C bus one bit at a time through a mapper register, and reads the EEPROM's
+//! replies back through another.
+//!
+//! Two chips are used. The 24C02 holds 256 bytes, and follows the I2C standard: each transfer starts with a
+//! device address byte, and bytes are sent most significant bit first. The X24C01 holds 128 bytes, and skips the
+//! device address: the first byte is the word address and the read/write bit, and bytes are sent least
+//! significant bit first.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EepromKind {
+    C24C01,
+    C24C02,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    /// Receiving the device address, which only the 24C02 has
+    DeviceAddress,
+    /// Receiving the word address to read or write from
+    WordAddress,
+    /// Receiving bytes to write
+    Write,
+    /// Sending bytes from the current address
+    Read,
+}
+
+pub struct Eeprom {
+    kind: EepromKind,
+    memory: Vec<u8>,
+    phase: Phase,
+    address: u8,
+    /// The byte being received or sent
+    data: u8,
+    /// How many bits of the current byte have been clocked
+    bit: u8,
+    /// Whether the EEPROM is acknowledging the byte it just received, by holding SDA low for a clock
+    acking: bool,
+    scl: bool,
+    sda: bool,
+    output: bool,
+}
+
+impl Eeprom {
+    pub fn new(kind: EepromKind) -> Self {
+        let size = match kind {
+            EepromKind::C24C01 => 128,
+            EepromKind::C24C02 => 256,
+        };
+        Self {
+            kind,
+            memory: vec![0; size],
+            phase: Phase::Idle,
+            address: 0,
+            data: 0,
+            bit: 0,
+            acking: false,
+            scl: false,
+            sda: false,
+            output: true,
+        }
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Restores the contents of the EEPROM, such as from a save file
+    pub fn load(&mut self, data: &[u8]) {
+        let len = data.len().min(self.memory.len());
+        self.memory[..len].copy_from_slice(&data[..len]);
+    }
+
+    /// The level the EEPROM is driving SDA to. The line is pulled high whenever nothing drives it low
+    pub fn output(&self) -> bool {
+        self.output
+    }
+
+    /// Sets the levels of the two lines, as driven by the game
+    pub fn write(&mut self, scl: bool, sda: bool) {
+        if self.scl && scl {
+            // SDA only changes while SCL is high to signal the start or end of a transfer
+            if self.sda && !sda {
+                self.start();
+            } else if !self.sda && sda {
+                self.phase = Phase::Idle;
+                self.output = true;
+            }
+        } else if !self.scl && scl {
+            self.clock_rise(sda);
+        } else if self.scl && !scl {
+            self.clock_fall();
+        }
+        self.scl = scl;
+        self.sda = sda;
+    }
+
+    fn start(&mut self) {
+        self.phase = match self.kind {
+            EepromKind::C24C01 => Phase::WordAddress,
+            EepromKind::C24C02 => Phase::DeviceAddress,
+        };
+        self.data = 0;
+        self.bit = 0;
+        self.acking = false;
+        self.output = true;
+    }
+
+    /// Data is sampled while SCL is high
+    fn clock_rise(&mut self, sda: bool) {
+        if self.acking {
+            // The clock the acknowledgement was held for. A read has already loaded the byte to send
+            self.acking = false;
+            if self.phase != Phase::Read {
+                self.data = 0;
+            }
+            self.bit = 0;
+            return;
+        }
+        match self.phase {
+            Phase::Idle => {}
+            Phase::Read => {
+                if self.bit < 8 {
+                    self.bit += 1;
+                } else if sda {
+                    // The game didn't acknowledge the byte, so it's done reading
+                    self.phase = Phase::Idle;
+                } else {
+                    self.address = self.next_address();
+                    self.data = self.memory[self.address as usize];
+                    self.bit = 0;
+                }
+            }
+            _ => {
+                if self.bit < 8 {
+                    let sda = u8::from(sda);
+                    self.data = match self.kind {
+                        EepromKind::C24C01 => self.data | (sda << self.bit),
+                        EepromKind::C24C02 => (self.data << 1) | sda,
+                    };
+                    self.bit += 1;
+                }
+            }
+        }
+    }
+
+    /// The EEPROM changes what it drives SDA to while SCL is low
+    fn clock_fall(&mut self) {
+        match self.phase {
+            Phase::Idle => self.output = true,
+            Phase::Read => {
+                self.output = if self.bit < 8 {
+                    let bit = match self.kind {
+                        EepromKind::C24C01 => self.bit,
+                        EepromKind::C24C02 => 7 - self.bit,
+                    };
+                    self.data & (1 << bit) != 0
+                } else {
+                    // Let go of SDA so the game can acknowledge
+                    true
+                };
+            }
+            _ if self.bit == 8 && !self.acking => {
+                if self.receive(self.data) {
+                    self.acking = true;
+                    self.output = false;
+                } else {
+                    self.phase = Phase::Idle;
+                    self.output = true;
+                }
+            }
+            _ => self.output = true,
+        }
+    }
+
+    /// Handles a whole byte received from the game, returning whether to acknowledge it
+    fn receive(&mut self, byte: u8) -> bool {
+        match (self.phase, self.kind) {
+            (Phase::DeviceAddress, _) => {
+                if byte & 0xF0 != 0xA0 {
+                    return false;
+                }
+                if byte & 1 != 0 {
+                    self.start_read();
+                } else {
+                    self.phase = Phase::WordAddress;
+                }
+            }
+            (Phase::WordAddress, EepromKind::C24C01) => {
+                self.address = byte & 0x7F;
+                if byte & 0x80 != 0 {
+                    self.start_read();
+                } else {
+                    self.phase = Phase::Write;
+                }
+            }
+            (Phase::WordAddress, EepromKind::C24C02) => {
+                self.address = byte;
+                self.phase = Phase::Write;
+            }
+            (Phase::Write, _) => {
+                self.memory[self.address as usize] = byte;
+                self.address = self.next_address();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn start_read(&mut self) {
+        self.phase = Phase::Read;
+        self.data = self.memory[self.address as usize];
+    }
+
+    fn next_address(&self) -> u8 {
+        ((self.address as usize + 1) % self.memory.len()) as u8
+    }
+}
@@ -20,6 +20,10 @@ impl Mapper000 {
 impl Mapper for Mapper000 {
     fn prg_read(&self, cpu_bus_address: usize) -> Result<u8, &'static str> {
         let internal_addr = match cpu_bus_address {
+            (0x6000..=0x7FFF) => {
+                let prg_ram = self.cartridge_data.get_prg_ram();
+                return Ok(prg_ram[(cpu_bus_address - 0x6000) % prg_ram.len()]);
+            }
             (0x8000..=0xBFFF) => Ok(0x8000),
             (0xC000..=0xFFFF) => {
                 // This mapper comes in two flavours: NROM-128 and NROM-256
@@ -38,9 +42,24 @@ impl Mapper for Mapper000 {
         Ok(self.cartridge_data.get_prg_rom()[cpu_bus_address % internal_addr?])
     }
 
-    fn prg_write(&mut self, _: usize, _: u8) -> Result<(), &'static str> {
+    fn prg_rom_offset(&self, cpu_bus_address: usize) -> Option<usize> {
+        // NROM-128 mirrors its single 16KB bank into both halves
+        let prg_rom = self.cartridge_data.get_prg_rom();
+        (0x8000..=0xFFFF)
+            .contains(&cpu_bus_address)
+            .then(|| (cpu_bus_address - 0x8000) % prg_rom.len())
+    }
+
+    fn prg_write(&mut self, cpu_bus_address: usize, val: u8) -> Result<(), &'static str> {
+        // Few NROM boards actually have PRG RAM, but emulators conventionally provide it, and test ROMs rely
+        // on it to report their results
+        if let (0x6000..=0x7FFF) = cpu_bus_address {
+            let prg_ram = self.cartridge_data.get_prg_ram_mut();
+            let len = prg_ram.len();
+            prg_ram[(cpu_bus_address - 0x6000) % len] = val;
+        }
         // Mapper zero means writing to prg rom is a no-op
-        return Ok(());
+        Ok(())
     }
 
     fn chr_read(&self, ppu_bus_address: usize) -> Result<u8, &'static str> {
@@ -51,9 +70,8 @@ impl Mapper for Mapper000 {
     }
 
     fn chr_read_pattern(&self, base_addr: usize, pattern_idx: u8) -> Option<&[u8]> {
-        self.cartridge_data.get_chr_rom()[base_addr..]
-            .chunks(16)
-            .nth(pattern_idx as usize)
+        let start = base_addr + pattern_idx as usize * 16;
+        self.cartridge_data.get_chr_rom().get(start..start + 16)
     }
 
     fn chr_write(&mut self, ppu_bus_address: usize, value: u8) -> Result<(), &'static str> {
@@ -82,4 +100,12 @@ impl Mapper for Mapper000 {
             Flags1::MIRRORING::Value::VERT => MirrorMode::VERT,
         }
     }
+
+    fn save_data(&self) -> Option<&[u8]> {
+        self.cartridge_data.battery_ram()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        self.cartridge_data.load_prg_ram(data);
+    }
 }
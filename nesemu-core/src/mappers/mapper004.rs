@@ -0,0 +1,274 @@
+//! Mapper004 - Nintendo's MMC3, used by Super Mario Bros. 3, Kirby's Adventure and hundreds of others. It switches
+//! PRG ROM in 8KB banks and CHR in 1KB and 2KB banks, selects the nametable mirroring, and counts scanlines for
+//! IRQs by watching A12 of the PPU address bus.
+//!
+//! The IRQ counter comes in two revisions that differ in when it requests an interrupt:
+//!
+//! - Sharp's "new" MMC3 requests one every time the counter is clocked while it is zero, so a latch of zero
+//!   interrupts on every scanline.
+//! - NEC's "old" MMC3A only requests one when the counter reaches zero by counting down, or by being reloaded
+//!   after a write to $C001, so a latch of zero interrupts once. Submapper 4 selects this revision.
+
+use super::{cartridge_data::CartridgeData, Banks, Mapper, MirrorMode};
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x400;
+/// The submapper of MMC3A boards, with the old IRQ behaviour
+const SUBMAPPER_MMC3A: u8 = 4;
+
+pub struct Mapper004 {
+    cartridge_data: CartridgeData,
+    /// Which of the bank registers $8001 writes to
+    bank_select: u8,
+    /// R0-R5 select CHR banks and R6-R7 select PRG banks
+    bank_registers: [u8; 8],
+    /// Swaps the banks at $8000 and $C000
+    prg_mode: bool,
+    /// Swaps the 2KB and 1KB CHR banks between the two pattern tables
+    chr_inversion: bool,
+    mirroring: u8,
+    prg_ram_enabled: bool,
+    prg_ram_write_protected: bool,
+    irq: Mmc3Irq,
+}
+
+impl Mapper004 {
+    pub fn new(cartridge_data: CartridgeData) -> Self {
+        let old_irq = cartridge_data.submapper == SUBMAPPER_MMC3A;
+        Self {
+            cartridge_data,
+            bank_select: 0,
+            bank_registers: [0; 8],
+            prg_mode: false,
+            chr_inversion: false,
+            mirroring: 0,
+            prg_ram_enabled: false,
+            prg_ram_write_protected: false,
+            irq: Mmc3Irq {
+                old: old_irq,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Gets the 8KB PRG ROM bank at each of $8000, $A000, $C000 and $E000
+    fn prg_banks(&self) -> [usize; 4] {
+        let bank_count = self.cartridge_data.get_prg_rom().len() / PRG_BANK_SIZE;
+        let r6 = self.bank_registers[6] as usize & 0x3F;
+        let r7 = self.bank_registers[7] as usize & 0x3F;
+        let second_last = bank_count.saturating_sub(2);
+        let banks = if self.prg_mode {
+            [second_last, r7, r6, bank_count - 1]
+        } else {
+            [r6, r7, second_last, bank_count - 1]
+        };
+        banks.map(|bank| bank % bank_count)
+    }
+
+    /// Gets the 1KB CHR bank at each of $0000-$1C00. The 2KB banks ignore the lowest bit of their register
+    fn chr_banks(&self) -> [usize; 8] {
+        let registers = self.bank_registers.map(|register| register as usize);
+        let two_kb = [
+            registers[0] & !1,
+            registers[0] | 1,
+            registers[1] & !1,
+            registers[1] | 1,
+        ];
+        let one_kb = [registers[2], registers[3], registers[4], registers[5]];
+        let (low, high) = if self.chr_inversion {
+            (one_kb, two_kb)
+        } else {
+            (two_kb, one_kb)
+        };
+        let bank_count = (self.cartridge_data.get_chr_rom().len() / CHR_BANK_SIZE).max(1);
+        let mut banks = [0; 8];
+        for (bank, register) in banks.iter_mut().zip(low.iter().chain(&high)) {
+            *bank = register % bank_count;
+        }
+        banks
+    }
+
+    /// Translates a CHR address into an offset into the cartridge's CHR data, through the 1KB bank it falls in
+    fn chr_offset(&self, ppu_bus_address: usize) -> usize {
+        let bank = self.chr_banks()[ppu_bus_address / CHR_BANK_SIZE];
+        bank * CHR_BANK_SIZE + ppu_bus_address % CHR_BANK_SIZE
+    }
+}
+
+impl Mapper for Mapper004 {
+    fn prg_read(&self, cpu_bus_address: usize) -> Result<u8, &'static str> {
+        if let 0x6000..=0x7FFF = cpu_bus_address {
+            if !self.prg_ram_enabled {
+                // Nothing drives the data bus, so it keeps the high byte of the address
+                return Ok((cpu_bus_address >> 8) as u8);
+            }
+            let prg_ram = self.cartridge_data.get_prg_ram();
+            return Ok(prg_ram[(cpu_bus_address - 0x6000) % prg_ram.len()]);
+        }
+        self.prg_rom_offset(cpu_bus_address)
+            .map(|offset| self.cartridge_data.get_prg_rom()[offset])
+            .ok_or("Bad prg address read on cartridge")
+    }
+
+    fn prg_rom_offset(&self, cpu_bus_address: usize) -> Option<usize> {
+        let 0x8000..=0xFFFF = cpu_bus_address else {
+            return None;
+        };
+        let bank = self.prg_banks()[(cpu_bus_address - 0x8000) / PRG_BANK_SIZE];
+        Some(bank * PRG_BANK_SIZE + cpu_bus_address % PRG_BANK_SIZE)
+    }
+
+    fn prg_write(&mut self, cpu_bus_address: usize, val: u8) -> Result<(), &'static str> {
+        if let 0x6000..=0x7FFF = cpu_bus_address {
+            if self.prg_ram_enabled && !self.prg_ram_write_protected {
+                let prg_ram = self.cartridge_data.get_prg_ram_mut();
+                let len = prg_ram.len();
+                prg_ram[(cpu_bus_address - 0x6000) % len] = val;
+            }
+            return Ok(());
+        }
+
+        // Each 8KB range holds a pair of registers, selected by A0
+        let odd = cpu_bus_address & 0x01 != 0;
+        match (cpu_bus_address & 0xE000, odd) {
+            (0x8000, false) => {
+                self.bank_select = val & 0x07;
+                self.prg_mode = val & 0x40 != 0;
+                self.chr_inversion = val & 0x80 != 0;
+            }
+            (0x8000, true) => self.bank_registers[self.bank_select as usize] = val,
+            (0xA000, false) => self.mirroring = val & 0x01,
+            (0xA000, true) => {
+                self.prg_ram_write_protected = val & 0x40 != 0;
+                self.prg_ram_enabled = val & 0x80 != 0;
+            }
+            (0xC000, false) => self.irq.latch = val,
+            (0xC000, true) => self.irq.reload(),
+            (0xE000, false) => {
+                self.irq.enabled = false;
+                self.irq.pending = false;
+            }
+            (0xE000, true) => self.irq.enabled = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn chr_read(&self, ppu_bus_address: usize) -> Result<u8, &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                Ok(self.cartridge_data.get_chr_rom()[self.chr_offset(ppu_bus_address)])
+            }
+            _ => Err("Bad chr address read on cartridge"),
+        }
+    }
+
+    fn chr_read_pattern(&self, base_addr: usize, pattern_idx: u8) -> Option<&[u8]> {
+        let address = base_addr + pattern_idx as usize * 16;
+        if address > 0x1FFF {
+            return None;
+        }
+        // Patterns are 16 byte aligned, so each one lies within a single bank
+        let start = self.chr_offset(address);
+        self.cartridge_data.get_chr_rom().get(start..start + 16)
+    }
+
+    fn chr_write(&mut self, ppu_bus_address: usize, value: u8) -> Result<(), &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                let offset = self.chr_offset(ppu_bus_address);
+                if let Some(ram) = self.cartridge_data.get_chr_ram() {
+                    ram[offset] = value;
+                }
+                Ok(())
+            }
+            _ => Err("Bad CHR address write on cartridge"),
+        }
+    }
+
+    fn current_mirroring_mode(&self) -> MirrorMode {
+        match self.mirroring {
+            0 => MirrorMode::VERT,
+            _ => MirrorMode::HORZ,
+        }
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq.pending
+    }
+
+    fn ppu_a12_rise(&mut self) {
+        self.irq.clock();
+    }
+
+    fn banks(&self) -> Banks {
+        Banks {
+            prg: self.prg_banks().to_vec(),
+            chr: self.chr_banks().to_vec(),
+        }
+    }
+
+    fn describe_state(&self) -> String {
+        format!(
+            "PRG {:02X?}, CHR {:02X?}, mirroring {:?}, PRG RAM {}, IRQ latch {:02X}, counter {:02X}{}",
+            self.prg_banks(),
+            self.chr_banks(),
+            self.current_mirroring_mode(),
+            if self.prg_ram_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            self.irq.latch,
+            self.irq.counter,
+            if self.irq.enabled { " (enabled)" } else { "" }
+        )
+    }
+
+    fn save_data(&self) -> Option<&[u8]> {
+        self.cartridge_data.battery_ram()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        self.cartridge_data.load_prg_ram(data);
+    }
+}
+
+/// Counts down once per scanline, as seen through rises of PPU A12, and reloads from the latch when it passes zero
+#[derive(Default)]
+struct Mmc3Irq {
+    latch: u8,
+    counter: u8,
+    /// Set by writes to $C001, so that the counter is reloaded the next time it is clocked
+    reload: bool,
+    enabled: bool,
+    pending: bool,
+    /// Whether this is the MMC3A revision, which only interrupts when the counter reaches zero
+    old: bool,
+}
+
+impl Mmc3Irq {
+    fn reload(&mut self) {
+        self.counter = 0;
+        self.reload = true;
+    }
+
+    fn clock(&mut self) {
+        let was_reloaded = self.reload;
+        let before = self.counter;
+        if self.counter == 0 || self.reload {
+            self.counter = self.latch;
+            self.reload = false;
+        } else {
+            self.counter -= 1;
+        }
+        let interrupt = if self.old {
+            self.counter == 0 && (before != 0 || was_reloaded)
+        } else {
+            self.counter == 0
+        };
+        if interrupt && self.enabled {
+            self.pending = true;
+        }
+    }
+}
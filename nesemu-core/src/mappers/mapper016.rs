@@ -0,0 +1,312 @@
+//! Mapper016 - Bandai's FCG family of boards, used by the Dragon Ball and SD Gundam games among others. They
+//! switch PRG ROM in 16KB banks and CHR in 1KB banks, select the nametable mirroring, and have a 16 bit IRQ
+//! counter that counts down CPU cycles. Most of them save games to a serial EEPROM rather than battery backed RAM.
+//!
+//! The boards differ in where their registers are and what they save to:
+//!
+//! - FCG-1 and FCG-2 (mapper 16, submapper 4) have their registers at $6000-$7FFF, and write the IRQ counter
+//!   directly.
+//! - LZ93D50 (mapper 16, submapper 5) moves the registers to $8000-$FFFF, writes a latch that the counter is
+//!   reloaded from when the IRQ is enabled, and has a 24C02 EEPROM.
+//! - LZ93D50 with an X24C01 EEPROM (mapper 159).
+//! - LZ93D50 with 8KB of battery backed RAM instead of an EEPROM (mapper 153). It uses the CHR bank registers to
+//!   select which 256KB half of its PRG ROM to use, and has unbanked CHR RAM.
+//!
+//! Old iNES headers can't tell FCG and LZ93D50 boards apart as mapper 16, so without a submapper the registers are
+//! accepted at both ranges and IRQ counter writes go to both the counter and the latch.
+
+use super::{
+    cartridge_data::CartridgeData,
+    eeprom::{Eeprom, EepromKind},
+    Banks, Mapper, MirrorMode,
+};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x400;
+
+enum Storage {
+    None,
+    Eeprom(Eeprom),
+    /// Battery backed PRG RAM, which is only readable while enabled
+    Ram {
+        enabled: bool,
+    },
+}
+
+pub struct Mapper016 {
+    cartridge_data: CartridgeData,
+    /// Whether the registers are at $6000-$7FFF, like on FCG boards
+    low_registers: bool,
+    /// Whether the registers are at $8000-$FFFF, like on LZ93D50 boards
+    high_registers: bool,
+    storage: Storage,
+    prg_bank: u8,
+    /// The 256KB half of PRG ROM in use, on boards that have more than 256KB
+    prg_outer_bank: u8,
+    chr_banks: [u8; 8],
+    mirroring: u8,
+    irq: BandaiIrq,
+}
+
+impl Mapper016 {
+    pub fn new(cartridge_data: CartridgeData) -> Self {
+        let (low_registers, high_registers, storage) =
+            match (cartridge_data.mapper_id, cartridge_data.submapper) {
+                (16, 4) => (true, false, Storage::None),
+                (16, 5) => (
+                    false,
+                    true,
+                    Storage::Eeprom(Eeprom::new(EepromKind::C24C02)),
+                ),
+                (153, _) => (false, true, Storage::Ram { enabled: false }),
+                (159, _) => (
+                    false,
+                    true,
+                    Storage::Eeprom(Eeprom::new(EepromKind::C24C01)),
+                ),
+                _ => (true, true, Storage::Eeprom(Eeprom::new(EepromKind::C24C02))),
+            };
+        Self {
+            cartridge_data,
+            low_registers,
+            high_registers,
+            storage,
+            prg_bank: 0,
+            prg_outer_bank: 0,
+            chr_banks: [0; 8],
+            mirroring: 0,
+            irq: BandaiIrq {
+                // FCG boards write the counter directly, and LZ93D50 boards go through the latch
+                direct: low_registers,
+                latched: high_registers,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn write_register(&mut self, register: usize, val: u8) {
+        match register {
+            0x0..=0x7 => {
+                self.chr_banks[register] = val;
+                if let Storage::Ram { .. } = self.storage {
+                    self.prg_outer_bank = val & 0x01;
+                }
+            }
+            0x8 => self.prg_bank = val & 0x0F,
+            0x9 => self.mirroring = val & 0x03,
+            0xA => self.irq.write_control(val),
+            0xB => self.irq.write_low(val),
+            0xC => self.irq.write_high(val),
+            0xD => match &mut self.storage {
+                Storage::Eeprom(eeprom) => eeprom.write(val & 0x20 != 0, val & 0x40 != 0),
+                Storage::Ram { enabled } => *enabled = val & 0x20 != 0,
+                Storage::None => {}
+            },
+            _ => {}
+        }
+    }
+
+    /// Translates a CHR address into an offset into the cartridge's CHR data, through the 1KB bank it falls in.
+    /// Boards with PRG RAM have unbanked CHR RAM instead
+    fn chr_offset(&self, ppu_bus_address: usize) -> usize {
+        if let Storage::Ram { .. } = self.storage {
+            return ppu_bus_address;
+        }
+        let bank_count = self.cartridge_data.get_chr_rom().len() / CHR_BANK_SIZE;
+        let bank = self.chr_banks[ppu_bus_address / CHR_BANK_SIZE] as usize % bank_count.max(1);
+        bank * CHR_BANK_SIZE + ppu_bus_address % CHR_BANK_SIZE
+    }
+}
+
+impl Mapper for Mapper016 {
+    fn prg_read(&self, cpu_bus_address: usize) -> Result<u8, &'static str> {
+        // Nothing drives the data bus for most of $6000-$7FFF, so it keeps the high byte of the address
+        let open_bus = (cpu_bus_address >> 8) as u8;
+        match cpu_bus_address {
+            0x6000..=0x7FFF => Ok(match &self.storage {
+                Storage::Eeprom(eeprom) => (open_bus & !0x10) | (u8::from(eeprom.output()) << 4),
+                Storage::Ram { enabled: true } => {
+                    let prg_ram = self.cartridge_data.get_prg_ram();
+                    prg_ram[(cpu_bus_address - 0x6000) % prg_ram.len()]
+                }
+                _ => open_bus,
+            }),
+            _ => self
+                .prg_rom_offset(cpu_bus_address)
+                .map(|offset| self.cartridge_data.get_prg_rom()[offset])
+                .ok_or("Bad prg address read on cartridge"),
+        }
+    }
+
+    fn prg_rom_offset(&self, cpu_bus_address: usize) -> Option<usize> {
+        let bank = match cpu_bus_address {
+            0x8000..=0xBFFF => self.prg_bank as usize,
+            // The last bank is fixed, so that the vectors are always there
+            0xC000..=0xFFFF => 0x0F,
+            _ => return None,
+        };
+        let bank_count = self.cartridge_data.get_prg_rom().len() / PRG_BANK_SIZE;
+        let bank = ((self.prg_outer_bank as usize) << 4 | bank) % bank_count;
+        Some(bank * PRG_BANK_SIZE + cpu_bus_address % PRG_BANK_SIZE)
+    }
+
+    fn prg_write(&mut self, cpu_bus_address: usize, val: u8) -> Result<(), &'static str> {
+        match cpu_bus_address {
+            0x6000..=0x7FFF => {
+                if let Storage::Ram { enabled: true } = self.storage {
+                    let prg_ram = self.cartridge_data.get_prg_ram_mut();
+                    let len = prg_ram.len();
+                    prg_ram[(cpu_bus_address - 0x6000) % len] = val;
+                } else if self.low_registers {
+                    self.write_register(cpu_bus_address & 0x0F, val);
+                }
+            }
+            0x8000..=0xFFFF if self.high_registers => {
+                self.write_register(cpu_bus_address & 0x0F, val)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn chr_read(&self, ppu_bus_address: usize) -> Result<u8, &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                Ok(self.cartridge_data.get_chr_rom()[self.chr_offset(ppu_bus_address)])
+            }
+            _ => Err("Bad chr address read on cartridge"),
+        }
+    }
+
+    fn chr_read_pattern(&self, base_addr: usize, pattern_idx: u8) -> Option<&[u8]> {
+        let address = base_addr + pattern_idx as usize * 16;
+        if address > 0x1FFF {
+            return None;
+        }
+        // Patterns are 16 byte aligned, so each one lies within a single bank
+        let start = self.chr_offset(address);
+        self.cartridge_data.get_chr_rom().get(start..start + 16)
+    }
+
+    fn chr_write(&mut self, ppu_bus_address: usize, value: u8) -> Result<(), &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                let offset = self.chr_offset(ppu_bus_address);
+                if let Some(ram) = self.cartridge_data.get_chr_ram() {
+                    ram[offset] = value;
+                }
+                Ok(())
+            }
+            _ => Err("Bad CHR address write on cartridge"),
+        }
+    }
+
+    fn current_mirroring_mode(&self) -> MirrorMode {
+        match self.mirroring {
+            0 => MirrorMode::VERT,
+            1 => MirrorMode::HORZ,
+            2 => MirrorMode::LOWER,
+            _ => MirrorMode::UPPER,
+        }
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq.pending
+    }
+
+    fn clock_cpu(&mut self, cycles: u16) {
+        for _ in 0..cycles {
+            self.irq.clock();
+        }
+    }
+
+    fn banks(&self) -> Banks {
+        Banks {
+            prg: vec![(self.prg_outer_bank as usize) << 4 | self.prg_bank as usize],
+            // Boards with PRG RAM use the CHR registers for the outer PRG bank instead
+            chr: match self.storage {
+                Storage::Ram { .. } => Vec::new(),
+                _ => self.chr_banks.iter().map(|&bank| bank as usize).collect(),
+            },
+        }
+    }
+
+    fn describe_state(&self) -> String {
+        format!(
+            "PRG {:X}:{:X}, CHR {:02X?}, mirroring {:?}, IRQ counter {:04X}{}",
+            self.prg_outer_bank,
+            self.prg_bank,
+            self.chr_banks,
+            self.current_mirroring_mode(),
+            self.irq.counter,
+            if self.irq.enabled { " (enabled)" } else { "" }
+        )
+    }
+
+    fn save_data(&self) -> Option<&[u8]> {
+        match &self.storage {
+            Storage::Eeprom(eeprom) => Some(eeprom.memory()),
+            Storage::Ram { .. } => Some(self.cartridge_data.get_prg_ram()),
+            Storage::None => None,
+        }
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        match &mut self.storage {
+            Storage::Eeprom(eeprom) => eeprom.load(data),
+            Storage::Ram { .. } => self.cartridge_data.load_prg_ram(data),
+            Storage::None => {}
+        }
+    }
+}
+
+/// Counts down once per CPU cycle while enabled, requesting an interrupt as it passes zero
+#[derive(Default)]
+struct BandaiIrq {
+    counter: u16,
+    latch: u16,
+    enabled: bool,
+    pending: bool,
+    /// Whether writes to the counter registers go straight to the counter
+    direct: bool,
+    /// Whether writes to the counter registers go to the latch, which is copied to the counter on enabling
+    latched: bool,
+}
+
+impl BandaiIrq {
+    fn write_control(&mut self, value: u8) {
+        self.enabled = value & 0x01 != 0;
+        if self.latched {
+            self.counter = self.latch;
+        }
+        self.pending = false;
+    }
+
+    fn write_low(&mut self, value: u8) {
+        self.write(|word| (word & 0xFF00) | value as u16);
+    }
+
+    fn write_high(&mut self, value: u8) {
+        self.write(|word| (word & 0x00FF) | (value as u16) << 8);
+    }
+
+    fn write(&mut self, update: impl Fn(u16) -> u16) {
+        if self.direct {
+            self.counter = update(self.counter);
+        }
+        if self.latched {
+            self.latch = update(self.latch);
+        }
+    }
+
+    fn clock(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        // Checking for zero before counting down, rather than after, matches the timing games expect
+        if self.counter == 0 {
+            self.pending = true;
+        }
+        self.counter = self.counter.wrapping_sub(1);
+    }
+}
@@ -0,0 +1,279 @@
+//! Mapper085 - Konami VRC7, used by Lagrange Point and Tiny Toon Adventures 2. It switches PRG ROM in 8KB banks
+//! and CHR in 1KB banks, selects the nametable mirroring, counts scanlines or CPU cycles for IRQs, and carries
+//! a YM2413-derived FM sound chip.
+//!
+//! The two board revisions select between the pairs of registers at each address with different address lines:
+//! A4 on VRC7a (Lagrange Point) and A3 on VRC7b (Tiny Toon Adventures 2). Games only write to one or the other,
+//! so either is accepted.
+
+use super::{cartridge_data::CartridgeData, vrc7_audio::Vrc7Audio, Banks, Mapper, MirrorMode};
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x400;
+
+pub struct Mapper085 {
+    cartridge_data: CartridgeData,
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    mirroring: u8,
+    prg_ram_enabled: bool,
+    irq: VrcIrq,
+    audio_address: u8,
+    audio: Vrc7Audio,
+    /// Whether the sound chip is held in reset, which silences it
+    audio_reset: bool,
+}
+
+impl Mapper085 {
+    pub fn new(cartridge_data: CartridgeData) -> Self {
+        Self {
+            cartridge_data,
+            prg_banks: [0; 3],
+            chr_banks: [0; 8],
+            mirroring: 0,
+            prg_ram_enabled: false,
+            irq: VrcIrq::new(),
+            audio_address: 0,
+            audio: Vrc7Audio::new(),
+            audio_reset: false,
+        }
+    }
+
+    /// Translates a CHR address into an offset into the cartridge's CHR data, through the 1KB bank it falls in
+    fn chr_offset(&self, ppu_bus_address: usize) -> usize {
+        let bank_count = self.cartridge_data.get_chr_rom().len() / CHR_BANK_SIZE;
+        let bank = self.chr_banks[ppu_bus_address / CHR_BANK_SIZE] as usize % bank_count.max(1);
+        bank * CHR_BANK_SIZE + ppu_bus_address % CHR_BANK_SIZE
+    }
+
+    fn write_audio_register(&mut self, value: u8) {
+        if !self.audio_reset {
+            self.audio.write(self.audio_address, value);
+        }
+    }
+}
+
+impl Mapper for Mapper085 {
+    fn prg_read(&self, cpu_bus_address: usize) -> Result<u8, &'static str> {
+        if let 0x6000..=0x7FFF = cpu_bus_address {
+            if !self.prg_ram_enabled {
+                // Nothing drives the data bus, so it keeps the high byte of the address
+                return Ok((cpu_bus_address >> 8) as u8);
+            }
+            let prg_ram = self.cartridge_data.get_prg_ram();
+            return Ok(prg_ram[(cpu_bus_address - 0x6000) % prg_ram.len()]);
+        }
+        self.prg_rom_offset(cpu_bus_address)
+            .map(|offset| self.cartridge_data.get_prg_rom()[offset])
+            .ok_or("Bad prg address read on cartridge")
+    }
+
+    fn prg_rom_offset(&self, cpu_bus_address: usize) -> Option<usize> {
+        let bank_count = self.cartridge_data.get_prg_rom().len() / PRG_BANK_SIZE;
+        let bank = match cpu_bus_address {
+            0x8000..=0xDFFF => self.prg_banks[(cpu_bus_address - 0x8000) / PRG_BANK_SIZE] as usize,
+            // The last bank is fixed, so that the vectors are always there
+            0xE000..=0xFFFF => bank_count - 1,
+            _ => return None,
+        };
+        let bank = bank % bank_count;
+        Some(bank * PRG_BANK_SIZE + cpu_bus_address % PRG_BANK_SIZE)
+    }
+
+    fn prg_write(&mut self, cpu_bus_address: usize, val: u8) -> Result<(), &'static str> {
+        if let 0x6000..=0x7FFF = cpu_bus_address {
+            if self.prg_ram_enabled {
+                let prg_ram = self.cartridge_data.get_prg_ram_mut();
+                let len = prg_ram.len();
+                prg_ram[(cpu_bus_address - 0x6000) % len] = val;
+            }
+            return Ok(());
+        }
+
+        // Either A4 or A3 selects the second register at each address
+        let second = cpu_bus_address & 0x18 != 0;
+        match (cpu_bus_address & 0xF000, second) {
+            (0x8000, false) => self.prg_banks[0] = val & 0x3F,
+            (0x8000, true) => self.prg_banks[1] = val & 0x3F,
+            // The sound chip is only on VRC7a, where both of its ports need A4
+            (0x9000, _) => match cpu_bus_address & 0x38 {
+                0x00 => self.prg_banks[2] = val & 0x3F,
+                0x10 => self.audio_address = val,
+                0x30 => self.write_audio_register(val),
+                _ => {}
+            },
+            (0xA000..=0xD000, _) => {
+                let idx = ((cpu_bus_address & 0xF000) - 0xA000) / 0x800 + usize::from(second);
+                self.chr_banks[idx] = val;
+            }
+            (0xE000, false) => {
+                self.mirroring = val & 0x03;
+                self.audio_reset = val & 0x40 != 0;
+                if self.audio_reset {
+                    self.audio = Vrc7Audio::new();
+                }
+                self.prg_ram_enabled = val & 0x80 != 0;
+            }
+            (0xE000, true) => self.irq.latch = val,
+            (0xF000, false) => self.irq.write_control(val),
+            (0xF000, true) => self.irq.acknowledge(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn chr_read(&self, ppu_bus_address: usize) -> Result<u8, &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                Ok(self.cartridge_data.get_chr_rom()[self.chr_offset(ppu_bus_address)])
+            }
+            _ => Err("Bad chr address read on cartridge"),
+        }
+    }
+
+    fn chr_read_pattern(&self, base_addr: usize, pattern_idx: u8) -> Option<&[u8]> {
+        let address = base_addr + pattern_idx as usize * 16;
+        if address > 0x1FFF {
+            return None;
+        }
+        // Patterns are 16 byte aligned, so each one lies within a single bank
+        let start = self.chr_offset(address);
+        self.cartridge_data.get_chr_rom().get(start..start + 16)
+    }
+
+    fn chr_write(&mut self, ppu_bus_address: usize, value: u8) -> Result<(), &'static str> {
+        match ppu_bus_address {
+            0x0000..=0x1FFF => {
+                let offset = self.chr_offset(ppu_bus_address);
+                if let Some(ram) = self.cartridge_data.get_chr_ram() {
+                    ram[offset] = value;
+                }
+                Ok(())
+            }
+            _ => Err("Bad CHR address write on cartridge"),
+        }
+    }
+
+    fn current_mirroring_mode(&self) -> MirrorMode {
+        match self.mirroring {
+            0 => MirrorMode::VERT,
+            1 => MirrorMode::HORZ,
+            2 => MirrorMode::LOWER,
+            _ => MirrorMode::UPPER,
+        }
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq.pending
+    }
+
+    fn clock_cpu(&mut self, cycles: u16) {
+        for _ in 0..cycles {
+            self.irq.clock();
+        }
+        if !self.audio_reset {
+            self.audio.clock(cycles);
+        }
+    }
+
+    fn audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    fn banks(&self) -> Banks {
+        Banks {
+            prg: self.prg_banks.iter().map(|&bank| bank as usize).collect(),
+            chr: self.chr_banks.iter().map(|&bank| bank as usize).collect(),
+        }
+    }
+
+    fn describe_state(&self) -> String {
+        format!(
+            "PRG {:02X?}, CHR {:02X?}, mirroring {:?}, PRG RAM {}, IRQ latch {:02X}{}",
+            self.prg_banks,
+            self.chr_banks,
+            self.current_mirroring_mode(),
+            if self.prg_ram_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            self.irq.latch,
+            if self.irq.enabled { " (enabled)" } else { "" }
+        )
+    }
+
+    fn save_data(&self) -> Option<&[u8]> {
+        self.cartridge_data.battery_ram()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        self.cartridge_data.load_prg_ram(data);
+    }
+}
+
+/// The IRQ counter shared by Konami's VRC boards. It counts up from a reloadable value, either once per CPU cycle
+/// or once per scanline, and requests an interrupt when it overflows
+struct VrcIrq {
+    latch: u8,
+    counter: u8,
+    /// Counts down CPU cycles to the next scanline, three PPU dots at a time
+    prescaler: i16,
+    enabled: bool,
+    /// The value `enabled` takes when the interrupt is acknowledged
+    enable_after_ack: bool,
+    cycle_mode: bool,
+    pending: bool,
+}
+
+impl VrcIrq {
+    /// Scanlines are 341 PPU dots long, and three dots pass per CPU cycle
+    const SCANLINE_DOTS: i16 = 341;
+
+    fn new() -> Self {
+        Self {
+            latch: 0,
+            counter: 0,
+            prescaler: VrcIrq::SCANLINE_DOTS,
+            enabled: false,
+            enable_after_ack: false,
+            cycle_mode: false,
+            pending: false,
+        }
+    }
+
+    fn write_control(&mut self, value: u8) {
+        self.enable_after_ack = value & 0x01 != 0;
+        self.enabled = value & 0x02 != 0;
+        self.cycle_mode = value & 0x04 != 0;
+        if self.enabled {
+            self.counter = self.latch;
+            self.prescaler = VrcIrq::SCANLINE_DOTS;
+        }
+        self.pending = false;
+    }
+
+    fn acknowledge(&mut self) {
+        self.pending = false;
+        self.enabled = self.enable_after_ack;
+    }
+
+    fn clock(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        if !self.cycle_mode {
+            self.prescaler -= 3;
+            if self.prescaler > 0 {
+                return;
+            }
+            self.prescaler += VrcIrq::SCANLINE_DOTS;
+        }
+        if self.counter == 0xFF {
+            self.counter = self.latch;
+            self.pending = true;
+        } else {
+            self.counter += 1;
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Early cartridges were very primitive. Throughout the console's lifetime, different cartridge hardware
+//! designs were developed to overcome certain limitations, such as the 16-bit address space and the lack of
+//! scrolling in multiple screen directions. Mappers represent the equivalence classes of different cartridge
+//! hardware designs. They sit in front of the cartridge data itself, intercepting and potentially modifying
+//! read and write requests to the data. Write requests to otherwise unwriteable ROM addresses can be
+//! interpreted as commands for the mapper to configure itself in a certain way.
+
+use std::io::{Error, ErrorKind};
+
+use self::{
+    cartridge_data::CartridgeData, mapper000::Mapper000, mapper004::Mapper004,
+    mapper016::Mapper016, mapper085::Mapper085,
+};
+use super::{
+    archive, overrides, patch,
+    rom_db::{self, Region, RomInfo},
+};
+
+mod cartridge_data;
+mod eeprom;
+mod mapper000;
+mod mapper004;
+mod mapper016;
+mod mapper085;
+mod vrc7_audio;
+
+#[derive(Debug)]
+pub enum MirrorMode {
+    HORZ,
+    VERT,
+    /// Every nametable is the first 1KB of PPU RAM
+    LOWER,
+    /// Every nametable is the second 1KB of PPU RAM
+    UPPER,
+}
+
+/// The banks a mapper has selected, with one entry for each switchable window of PRG and CHR memory, in address
+/// order
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Banks {
+    pub prg: Vec<usize>,
+    pub chr: Vec<usize>,
+}
+
+/// Mappers must be `Send` so that frontends can run the emulation on a thread of its own
+pub trait Mapper: Send {
+    /// Read a single byte of data from the cartridge's PRG data
+    ///
+    /// The CPU bus maps PRG data to addresses 0x4020 - 0xFFFF, so calling this function with bus addresses
+    /// outside this range is guarunteed to fail. In addition, not all Mappers actually map PRG data to this
+    /// entire range of addresses, so addresses within this range are not guarunteed to succeed either.
+    fn prg_read(&self, cpu_bus_address: usize) -> Result<u8, &'static str>;
+    /// Write a single byte of data to the cartridge's PRG data
+    ///
+    /// PRG data consists of either ROM or RAM. Calling this function on a PRG RAM address works as expected,
+    /// but calling it on a PRG ROM address will either result in a no-op or it may activate a mapper register,
+    /// depending on the mapper used.
+    fn prg_write(&mut self, cpu_bus_address: usize, val: u8) -> Result<(), &'static str>;
+
+    /// Reads a single byte of data from the cartridge's CHR data
+    ///
+    /// The PPU bus maps CHR data from 0x0000 - 0x1FFF, so calling this function with bus addresses outside
+    /// this range is guarunteed to fail.
+    fn chr_read(&self, ppu_bus_address: usize) -> Result<u8, &'static str>;
+    /// Reads a single pattern entry (consisting of 16 bytes) from the cartridge's CHR data
+    ///
+    /// This function returns None if the pattern idx is out of range. Note that no checks are performed to
+    /// confirm the returned bytes are a valid chr pattern. An invalid pattern could be returned if CHR RAM
+    /// is being read and the program wrote non-pattern data to it, then later retrieved it as if it were a
+    /// pattern.
+    fn chr_read_pattern(&self, base_addr: usize, pattern_idx: u8) -> Option<&[u8]>;
+    /// Writes a single byte of data to the cartridge's CHR data
+    ///
+    /// This is a no-op unless the cartridge supports CHR RAM. The PPU bus maps CHR data from 0x0000 - 0x1FFF,
+    /// so calling this function with bus addresses outside this range is guarunteed to fail.
+    fn chr_write(&mut self, ppu_bus_address: usize, value: u8) -> Result<(), &'static str>;
+
+    /// Gets the current nametable mirroring mode for this cartridge.
+    ///
+    /// Some mappers support programmatically switching the nametable mirroring mode  at runtime. If a mapper
+    /// does not support this behavior, then this function will return whatever hardcoded mirroring mode was
+    /// stored in the iNES header.
+    fn current_mirroring_mode(&self) -> MirrorMode;
+
+    /// Whether the cartridge is holding the CPU's IRQ line low, requesting an interrupt
+    fn irq_pending(&self) -> bool {
+        false
+    }
+    /// Advances the mapper by the given number of CPU cycles, for mappers with timers counting them
+    fn clock_cpu(&mut self, _cycles: u16) {}
+    /// Called when A12 of the PPU address bus rises after having been low for long enough, as filtered by
+    /// [`A12Filter`]. Boards like MMC3 count scanlines this way: with the background patterns at $0000 and the
+    /// sprite patterns at $1000, A12 rises once per scanline, as the sprite patterns are fetched
+    fn ppu_a12_rise(&mut self) {}
+    /// Gets the current level of the cartridge's expansion audio, between -1 and 1, for boards with a sound chip
+    /// of their own
+    fn audio_output(&self) -> f32 {
+        0.0
+    }
+
+    /// Translates an address in $8000-$FFFF into an offset into PRG ROM, through whichever bank is selected there.
+    /// Mappers that can't tell, or map something other than PRG ROM there, give `None`
+    fn prg_rom_offset(&self, _cpu_bus_address: usize) -> Option<usize> {
+        None
+    }
+
+    /// Gets the banks currently selected, for the debugger to notice bank switches. Mappers without banks have
+    /// nothing to report
+    fn banks(&self) -> Banks {
+        Banks::default()
+    }
+
+    /// Describes which banks are selected and how the nametables are mirrored, for logging while debugging
+    /// bank switching. Mappers with banks should override this to list them
+    fn describe_state(&self) -> String {
+        format!("mirroring {:?}", self.current_mirroring_mode())
+    }
+
+    /// Gets the data the cartridge keeps while the console is off, in battery backed RAM or an EEPROM, if it has
+    /// any. This is what gets written to the save file
+    fn save_data(&self) -> Option<&[u8]> {
+        None
+    }
+    /// Restores data previously returned by [`Mapper::save_data`], such as from a save file
+    fn load_save_data(&mut self, _data: &[u8]) {}
+}
+
+/// Watches A12 of the PPU address bus for rising edges, ignoring those that follow too short a time low
+///
+/// Between sprite pattern fetches, the PPU fetches from the nametables, briefly pulling A12 low. Boards that count
+/// A12 rises only see the first rise of each scanline because they ignore rises until A12 has been low for a few
+/// CPU cycles
+pub(crate) struct A12Filter {
+    high: bool,
+    /// The PPU dot A12 last went low on
+    low_since: u64,
+}
+
+impl A12Filter {
+    /// A12 has to be low for three CPU cycles, which is nine PPU dots
+    const MIN_LOW_DOTS: u64 = 9;
+
+    pub fn new() -> Self {
+        Self {
+            high: false,
+            low_since: 0,
+        }
+    }
+
+    /// Records the address on the PPU address bus as of the given PPU dot, counted since power on. Returns whether
+    /// it is a rising edge of A12 that gets past the filter
+    pub fn update(&mut self, address: u16, dot: u64) -> bool {
+        let high = address & 0x1000 != 0;
+        let rose =
+            high && !self.high && dot.saturating_sub(self.low_since) >= A12Filter::MIN_LOW_DOTS;
+        if self.high && !high {
+            self.low_since = dot;
+        }
+        self.high = high;
+        rose
+    }
+}
+
+/// Creates a new mapper from a given ROM file, applying the given IPS or BPS patch to it first. The ROM may be
+/// inside a zip or 7z archive, as described in [`archive`]. Also returns what is known about the ROM
+///
+/// Fails if the rom's specified mapper is not supported, or if there is a problem reading the rom file or
+/// applying the patch.
+pub fn new_mapper(
+    rom_path: &str,
+    patch_path: Option<&str>,
+) -> Result<(Box<dyn Mapper>, RomInfo), Error> {
+    let mut rom = archive::read_rom(rom_path)?;
+    if let Some(patch_path) = patch_path {
+        rom = patch::apply(&rom, &std::fs::read(patch_path)?)?;
+    }
+    new_mapper_from_bytes(&rom)
+}
+
+/// Creates a new mapper from a ROM image that is already in memory, along with what is known about the ROM
+pub fn new_mapper_from_bytes(rom: &[u8]) -> Result<(Box<dyn Mapper>, RomInfo), Error> {
+    let mut cartridge_data = CartridgeData::from_bytes(rom)?;
+    let hash = cartridge_data.hash();
+    let overrides = overrides::lookup(hash.crc32);
+    if let Some(overrides) = &overrides {
+        log::info!(
+            "Applying compatibility overrides for ROM {:08x}",
+            hash.crc32
+        );
+        cartridge_data.apply_overrides(overrides);
+    }
+    let info = RomInfo {
+        hash,
+        entry: rom_db::lookup(&hash),
+        mapper: cartridge_data.mapper_id,
+        submapper: cartridge_data.submapper,
+        prg_rom_size: cartridge_data.get_prg_rom().len(),
+        chr_rom_size: cartridge_data.chr_rom_size(),
+        region: overrides
+            .and_then(|overrides| overrides.region)
+            .unwrap_or(cartridge_data.region()),
+        controller: overrides
+            .and_then(|overrides| overrides.controller)
+            .unwrap_or_default(),
+        overridden: overrides.is_some(),
+    };
+    if info.region != Region::Ntsc {
+        log::warn!(
+            "This ROM is for {:?} consoles, but only NTSC timing is emulated",
+            info.region
+        );
+    }
+    let mapper: Box<dyn Mapper> = match cartridge_data.mapper_id {
+        0 => Box::new(Mapper000::new(cartridge_data)),
+        4 => Box::new(Mapper004::new(cartridge_data)),
+        16 | 153 | 159 => Box::new(Mapper016::new(cartridge_data)),
+        85 => Box::new(Mapper085::new(cartridge_data)),
+        _ => return Err(Error::from(ErrorKind::Unsupported)),
+    };
+    Ok((mapper, info))
+}
@@ -0,0 +1,412 @@
+//! The FM sound chip on VRC7 boards, a cut down YM2413 (OPLL) with six channels, no rhythm mode and its own set
+//! of built in instruments. Each channel is a pair of operators, a modulator whose sine wave bends the phase of
+//! the carrier, which is what is heard.
+//!
+//! The chip is modelled with floating point maths at its own sample rate rather than with the log-sin and
+//! exponent tables of the real thing, so it sounds right but isn't bit exact.
+
+use std::f64::consts::TAU;
+
+const CHANNEL_COUNT: usize = 6;
+/// The chip makes a sample every 72 cycles of its 3.58MHz clock, which is every 36 CPU cycles
+const CPU_CYCLES_PER_SAMPLE: u16 = 36;
+const SAMPLE_RATE: f64 = 3_579_545.0 / 72.0;
+
+/// The built in instruments, 1-15, in the same layout as the custom instrument at registers $00-$07
+const PATCHES: [[u8; 8]; 15] = [
+    [0x03, 0x21, 0x05, 0x06, 0xE8, 0x81, 0x42, 0x27],
+    [0x13, 0x41, 0x14, 0x0D, 0xD8, 0xF6, 0x23, 0x12],
+    [0x11, 0x11, 0x08, 0x08, 0xFA, 0xB2, 0x20, 0x12],
+    [0x31, 0x61, 0x0C, 0x07, 0xA8, 0x64, 0x61, 0x27],
+    [0x32, 0x21, 0x1E, 0x06, 0xE1, 0x76, 0x01, 0x28],
+    [0x02, 0x01, 0x06, 0x00, 0xA3, 0xE2, 0xF4, 0xF4],
+    [0x21, 0x61, 0x1D, 0x07, 0x82, 0x81, 0x11, 0x07],
+    [0x23, 0x21, 0x22, 0x17, 0xA2, 0x72, 0x01, 0x17],
+    [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01],
+    [0xB5, 0x01, 0x0F, 0x0F, 0xA8, 0xA5, 0x51, 0x02],
+    [0x17, 0xC1, 0x24, 0x07, 0xF8, 0xF8, 0x22, 0x12],
+    [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16],
+    [0x01, 0x02, 0xD3, 0x05, 0xC9, 0x95, 0x03, 0x02],
+    [0x61, 0x63, 0x0C, 0x00, 0x94, 0xC0, 0x33, 0xF6],
+    [0x21, 0x72, 0x0D, 0x00, 0xC1, 0xD5, 0x56, 0x06],
+];
+
+/// Frequency multipliers selected by the MULT field of an operator
+const MULTIPLIERS: [f64; 16] = [
+    0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 10.0, 12.0, 12.0, 15.0, 15.0,
+];
+/// Attenuation in dB for key scale level 3, by the top four bits of the F-number, before adjusting for the block
+const KEY_SCALE_DB: [f64; 16] = [
+    0.0, 18.0, 24.0, 27.75, 30.0, 32.25, 33.75, 35.25, 36.0, 37.5, 38.25, 39.0, 39.75, 40.5, 41.25,
+    42.0,
+];
+/// Beyond this much attenuation, in dB, an operator is silent
+const MAX_ATTENUATION: f64 = 48.0;
+/// Release rates used after key off: with the channel's sustain bit set, and for percussive instruments
+const SUSTAIN_RELEASE_RATE: u8 = 5;
+const PERCUSSIVE_RELEASE_RATE: u8 = 7;
+/// Tremolo runs at 3.7Hz with a depth of 4.8dB, and vibrato at 6.4Hz with a depth of about 14 cents
+const TREMOLO_HZ: f64 = 3.7;
+const TREMOLO_DB: f64 = 4.8;
+const VIBRATO_HZ: f64 = 6.4;
+const VIBRATO_DEPTH: f64 = 0.008;
+
+pub(super) struct Vrc7Audio {
+    /// The custom instrument, instrument 0
+    custom_patch: [u8; 8],
+    channels: [Channel; CHANNEL_COUNT],
+    /// CPU cycles left until the next sample is made
+    countdown: u16,
+    tremolo_phase: f64,
+    vibrato_phase: f64,
+    /// The latest sample, between -1 and 1
+    output: f32,
+}
+
+impl Vrc7Audio {
+    pub fn new() -> Self {
+        Self {
+            custom_patch: [0; 8],
+            channels: Default::default(),
+            countdown: CPU_CYCLES_PER_SAMPLE,
+            tremolo_phase: 0.0,
+            vibrato_phase: 0.0,
+            output: 0.0,
+        }
+    }
+
+    pub fn output(&self) -> f32 {
+        self.output
+    }
+
+    pub fn write(&mut self, register: u8, value: u8) {
+        let channel = (register & 0x0F) as usize;
+        match register {
+            0x00..=0x07 => self.custom_patch[register as usize] = value,
+            0x10..=0x15 => {
+                let channel = &mut self.channels[channel];
+                channel.f_number = (channel.f_number & 0x100) | value as u16;
+            }
+            0x20..=0x25 => {
+                let channel = &mut self.channels[channel];
+                channel.f_number = (channel.f_number & 0xFF) | ((value as u16 & 0x01) << 8);
+                channel.block = (value >> 1) & 0x07;
+                channel.sustain = value & 0x20 != 0;
+                channel.set_key(value & 0x10 != 0);
+            }
+            0x30..=0x35 => {
+                let channel = &mut self.channels[channel];
+                channel.instrument = value >> 4;
+                channel.volume = value & 0x0F;
+            }
+            _ => {}
+        }
+    }
+
+    /// Advances the chip by the given number of CPU cycles, making a new sample every 36 of them
+    pub fn clock(&mut self, cycles: u16) {
+        let mut cycles = cycles;
+        while cycles >= self.countdown {
+            cycles -= self.countdown;
+            self.countdown = CPU_CYCLES_PER_SAMPLE;
+            self.output = self.sample();
+        }
+        self.countdown -= cycles;
+    }
+
+    fn sample(&mut self) -> f32 {
+        self.tremolo_phase = (self.tremolo_phase + TREMOLO_HZ / SAMPLE_RATE).fract();
+        self.vibrato_phase = (self.vibrato_phase + VIBRATO_HZ / SAMPLE_RATE).fract();
+        let lfo = Lfo {
+            tremolo_db: TREMOLO_DB * (1.0 - (TAU * self.tremolo_phase).cos()) / 2.0,
+            vibrato: 1.0 + VIBRATO_DEPTH * (TAU * self.vibrato_phase).sin(),
+        };
+        let mut mix = 0.0;
+        for channel in &mut self.channels {
+            let patch = match channel.instrument {
+                0 => Patch::new(&self.custom_patch),
+                instrument => Patch::new(&PATCHES[instrument as usize - 1]),
+            };
+            mix += channel.sample(&patch, &lfo);
+        }
+        (mix / CHANNEL_COUNT as f64) as f32
+    }
+}
+
+/// One operator's half of an instrument
+#[derive(Clone, Copy)]
+struct OperatorPatch {
+    tremolo: bool,
+    vibrato: bool,
+    /// Sustained instruments hold at the sustain level while the key is on, and percussive ones keep decaying
+    sustained: bool,
+    key_scale_rate: bool,
+    multiplier: f64,
+    key_scale_level: u8,
+    /// Replaces the negative half of the sine wave with silence
+    rectified: bool,
+    attack_rate: u8,
+    decay_rate: u8,
+    sustain_level: u8,
+    release_rate: u8,
+}
+
+struct Patch {
+    modulator: OperatorPatch,
+    carrier: OperatorPatch,
+    /// Attenuation of the modulator, in 0.75dB steps
+    total_level: u8,
+    feedback: u8,
+}
+
+impl Patch {
+    fn new(bytes: &[u8; 8]) -> Self {
+        let operator = |i: usize, key_scale_level: u8, rectified: bool| OperatorPatch {
+            tremolo: bytes[i] & 0x80 != 0,
+            vibrato: bytes[i] & 0x40 != 0,
+            sustained: bytes[i] & 0x20 != 0,
+            key_scale_rate: bytes[i] & 0x10 != 0,
+            multiplier: MULTIPLIERS[(bytes[i] & 0x0F) as usize],
+            key_scale_level,
+            rectified,
+            attack_rate: bytes[4 + i] >> 4,
+            decay_rate: bytes[4 + i] & 0x0F,
+            sustain_level: bytes[6 + i] >> 4,
+            release_rate: bytes[6 + i] & 0x0F,
+        };
+        Self {
+            modulator: operator(0, bytes[2] >> 6, bytes[3] & 0x08 != 0),
+            carrier: operator(1, bytes[3] >> 6, bytes[3] & 0x10 != 0),
+            total_level: bytes[2] & 0x3F,
+            feedback: bytes[3] & 0x07,
+        }
+    }
+}
+
+/// The low frequency oscillators' effects on the current sample
+struct Lfo {
+    tremolo_db: f64,
+    /// Multiplies the frequency of operators with vibrato
+    vibrato: f64,
+}
+
+#[derive(Default)]
+struct Channel {
+    f_number: u16,
+    block: u8,
+    key_on: bool,
+    sustain: bool,
+    instrument: u8,
+    /// Attenuation of the carrier, in 3dB steps
+    volume: u8,
+    modulator: Operator,
+    carrier: Operator,
+    /// The modulator's last two outputs, which it feeds back into its own phase
+    feedback: [f64; 2],
+}
+
+impl Channel {
+    fn set_key(&mut self, key_on: bool) {
+        if key_on && !self.key_on {
+            self.modulator.key_on();
+            self.carrier.key_on();
+            self.feedback = [0.0; 2];
+        } else if !key_on && self.key_on {
+            self.modulator.stage = Stage::Release;
+            self.carrier.stage = Stage::Release;
+        }
+        self.key_on = key_on;
+    }
+
+    /// The rate adjustment for higher notes when key scale rate is on, from the block and top bit of the F-number
+    fn key_scale(&self) -> u8 {
+        self.block << 1 | (self.f_number >> 8) as u8
+    }
+
+    /// Attenuation in dB for higher notes, at key scale level 3
+    fn key_scale_db(&self) -> f64 {
+        let db = KEY_SCALE_DB[(self.f_number >> 5) as usize] - 6.0 * (7 - self.block) as f64;
+        db.max(0.0)
+    }
+
+    fn sample(&mut self, patch: &Patch, lfo: &Lfo) -> f64 {
+        let frequency = self.f_number as f64 * (1 << self.block) as f64 / (1 << 19) as f64;
+        let key_scale = self.key_scale();
+        let key_scale_db = self.key_scale_db();
+
+        let feedback = if patch.feedback == 0 {
+            0.0
+        } else {
+            (self.feedback[0] + self.feedback[1]) * 2f64.powi(patch.feedback as i32 - 7)
+        };
+        let modulator_db = patch.total_level as f64 * 0.75;
+        let modulation = self.modulator.sample(
+            &patch.modulator,
+            self.sustain,
+            frequency,
+            key_scale,
+            key_scale_db,
+            modulator_db,
+            feedback,
+            lfo,
+        );
+        self.feedback = [self.feedback[1], modulation];
+
+        let carrier_db = self.volume as f64 * 3.0;
+        self.carrier.sample(
+            &patch.carrier,
+            self.sustain,
+            frequency,
+            key_scale,
+            key_scale_db,
+            carrier_db,
+            modulation * 2.0,
+            lfo,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum Stage {
+    Attack,
+    Decay,
+    Sustain,
+    #[default]
+    Release,
+}
+
+struct Operator {
+    /// Position in the waveform, in cycles
+    phase: f64,
+    /// Attenuation from the envelope, in dB
+    envelope: f64,
+    stage: Stage,
+}
+
+impl Default for Operator {
+    fn default() -> Self {
+        Self {
+            phase: 0.0,
+            envelope: MAX_ATTENUATION,
+            stage: Stage::Release,
+        }
+    }
+}
+
+impl Operator {
+    fn key_on(&mut self) {
+        self.phase = 0.0;
+        self.stage = Stage::Attack;
+    }
+
+    /// Advances the operator by a sample and gives its output, between -1 and 1. `phase_offset` is the modulation
+    /// or feedback bending its phase, in cycles
+    #[allow(clippy::too_many_arguments)]
+    fn sample(
+        &mut self,
+        patch: &OperatorPatch,
+        channel_sustain: bool,
+        frequency: f64,
+        key_scale: u8,
+        key_scale_db: f64,
+        attenuation_db: f64,
+        phase_offset: f64,
+        lfo: &Lfo,
+    ) -> f64 {
+        self.clock_envelope(patch, channel_sustain, key_scale);
+
+        let vibrato = if patch.vibrato { lfo.vibrato } else { 1.0 };
+        self.phase = (self.phase + frequency * patch.multiplier * vibrato).fract();
+
+        let key_scale_db = match patch.key_scale_level {
+            0 => 0.0,
+            level => key_scale_db / (1 << (3 - level)) as f64,
+        };
+        let tremolo_db = if patch.tremolo { lfo.tremolo_db } else { 0.0 };
+        let db = self.envelope + key_scale_db + attenuation_db + tremolo_db;
+        if self.envelope >= MAX_ATTENUATION || db >= 96.0 {
+            return 0.0;
+        }
+
+        let wave = (TAU * (self.phase + phase_offset)).sin();
+        if patch.rectified && wave < 0.0 {
+            return 0.0;
+        }
+        wave * 10f64.powf(-db / 20.0)
+    }
+
+    fn clock_envelope(&mut self, patch: &OperatorPatch, channel_sustain: bool, key_scale: u8) {
+        let key_scale = if patch.key_scale_rate {
+            key_scale
+        } else {
+            key_scale >> 2
+        };
+        match self.stage {
+            Stage::Attack => {
+                let Some(time) = attack_time(patch.attack_rate, key_scale) else {
+                    return;
+                };
+                // The attack curve is exponential, so it takes about ln(48 / 0.1) time constants to finish
+                let step = 6.2 / (time * SAMPLE_RATE);
+                self.envelope -= (self.envelope + 0.1) * step.min(1.0);
+                if self.envelope <= 0.0 {
+                    self.envelope = 0.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.decay(patch.decay_rate, key_scale);
+                if self.envelope >= patch.sustain_level as f64 * 3.0 {
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => {
+                if !patch.sustained {
+                    self.decay(patch.release_rate, key_scale);
+                }
+            }
+            Stage::Release => {
+                let rate = if channel_sustain {
+                    SUSTAIN_RELEASE_RATE
+                } else if patch.sustained {
+                    patch.release_rate
+                } else {
+                    PERCUSSIVE_RELEASE_RATE
+                };
+                self.decay(rate, key_scale);
+            }
+        }
+    }
+
+    fn decay(&mut self, rate: u8, key_scale: u8) {
+        if let Some(time) = decay_time(rate, key_scale) {
+            let step = MAX_ATTENUATION / (time * SAMPLE_RATE);
+            self.envelope = (self.envelope + step).min(MAX_ATTENUATION);
+        }
+    }
+}
+
+/// Scales a time taken at rate 1 down to the given rate, where each step up in rate halves the time and the key
+/// scale fills in the steps between. Rate 0 never changes the envelope
+fn scale_time(rate_1_time: f64, rate: u8, key_scale: u8) -> Option<f64> {
+    if rate == 0 {
+        return None;
+    }
+    let effective = (rate * 4 + key_scale).min(63);
+    let octaves = (effective >> 2) as i32 - 1;
+    let fraction = 4.0 / (4 + (effective & 0x03)) as f64;
+    Some(rate_1_time * fraction / 2f64.powi(octaves))
+}
+
+/// Seconds to attack from silence to full volume. Rate 15 is instant
+fn attack_time(rate: u8, key_scale: u8) -> Option<f64> {
+    if rate == 15 {
+        return Some(0.0);
+    }
+    scale_time(2.8, rate, key_scale)
+}
+
+/// Seconds to decay from full volume to silence
+fn decay_time(rate: u8, key_scale: u8) -> Option<f64> {
+    scale_time(10.0, rate, key_scale)
+}
@@ -0,0 +1,107 @@
+//! Some ROMs only run correctly with settings their header doesn't give, either because the dump is bad or
+//! because the board does something the header has no way to describe. Rather than special casing them in code,
+//! these are kept in a table of overrides, keyed by the CRC32 of the ROM's PRG and CHR data as in [`rom_db`].
+//!
+//! The table lives in `data/overrides.txt`, which describes its format at the top.
+//!
+//! [`rom_db`]: super::rom_db
+
+use std::{str::FromStr, sync::OnceLock};
+
+use super::{controller::ControllerType, rom_db::Region};
+
+const TABLE: &str = include_str!("../data/overrides.txt");
+
+/// The mirroring to force, for boards with fixed mirroring
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+}
+
+/// Settings to use in place of those given by a ROM's header. Anything left as `None` keeps the header's value
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Overrides {
+    pub mapper: Option<u16>,
+    pub submapper: Option<u8>,
+    pub mirroring: Option<Mirroring>,
+    pub region: Option<Region>,
+    pub controller: Option<ControllerType>,
+}
+
+impl FromStr for Overrides {
+    type Err = String;
+
+    /// Parses a list of `key=value` settings, separated by whitespace
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut overrides = Overrides::default();
+        for setting in text.split_whitespace() {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, found '{}'", setting))?;
+            let invalid = || format!("Invalid value '{}' for {}", value, key);
+            match key {
+                "mapper" => overrides.mapper = Some(value.parse().map_err(|_| invalid())?),
+                "submapper" => overrides.submapper = Some(value.parse().map_err(|_| invalid())?),
+                "mirroring" => {
+                    overrides.mirroring = Some(match value {
+                        "horizontal" => Mirroring::Horizontal,
+                        "vertical" => Mirroring::Vertical,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "region" => {
+                    overrides.region = Some(match value {
+                        "ntsc" => Region::Ntsc,
+                        "pal" => Region::Pal,
+                        "dendy" => Region::Dendy,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "controller" => {
+                    overrides.controller = Some(match value {
+                        "standard" => ControllerType::Standard,
+                        "zapper" => ControllerType::Zapper,
+                        "power_pad" => ControllerType::PowerPad,
+                        "arkanoid" => ControllerType::Arkanoid,
+                        _ => return Err(invalid()),
+                    })
+                }
+                _ => return Err(format!("Unknown setting '{}'", key)),
+            }
+        }
+        Ok(overrides)
+    }
+}
+
+/// Finds the overrides for the ROM with the given CRC32, if it needs any
+pub fn lookup(crc32: u32) -> Option<Overrides> {
+    table()
+        .iter()
+        .find(|(entry_crc32, _)| *entry_crc32 == crc32)
+        .map(|(_, overrides)| *overrides)
+}
+
+fn table() -> &'static [(u32, Overrides)] {
+    static ENTRIES: OnceLock<Vec<(u32, Overrides)>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        TABLE
+            .lines()
+            .map(|line| line.split_once('#').map_or(line, |(line, _)| line).trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| match parse_entry(line) {
+                Ok(entry) => Some(entry),
+                Err(error) => {
+                    log::warn!("Skipping invalid override '{}': {}", line, error);
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+fn parse_entry(line: &str) -> Result<(u32, Overrides), String> {
+    let (crc32, settings) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let crc32 = u32::from_str_radix(crc32, 16).map_err(|_| format!("Invalid CRC32 '{}'", crc32))?;
+    Ok((crc32, settings.parse()?))
+}
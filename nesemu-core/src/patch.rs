@@ -0,0 +1,224 @@
+//! Translations and ROM hacks are distributed as patches against the original ROM, rather than as patched ROMs.
+//! Two formats are supported, and are told apart by the magic at the start of the patch:
+//!
+//! - IPS, a list of records that each overwrite or fill a range of the ROM. There are no checksums, so applying
+//!   it to the wrong ROM silently produces garbage.
+//! - BPS, which builds a new ROM by copying from the original, from itself or from the patch. It carries
+//!   checksums of the original and the result, so applying it to the wrong ROM fails.
+//!
+//! Patches are applied to the ROM image in memory when it is loaded, leaving the file on disk untouched.
+
+use std::{
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use super::util::crc32;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const BPS_MAGIC: &[u8] = b"BPS1";
+/// The BPS footer holds the checksums of the original ROM, the patched ROM and the patch itself
+const BPS_FOOTER_SIZE: usize = 12;
+
+/// Finds a patch with the same name as the ROM, such as `game.ips` for `game.nes`, so that patches placed next
+/// to the ROM are applied without being asked for
+pub fn find_patch(rom_path: &str) -> Option<String> {
+    ["ips", "bps"]
+        .iter()
+        .map(|extension| Path::new(rom_path).with_extension(extension))
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Applies an IPS or BPS patch to a ROM image, returning the patched image
+///
+/// Fails if the patch is in neither format, is malformed, or, for BPS patches, was made for a different ROM
+pub fn apply(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    if let Some(records) = patch.strip_prefix(IPS_MAGIC) {
+        apply_ips(rom, records)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else {
+        Err(invalid("Not an IPS or BPS patch"))
+    }
+}
+
+fn apply_ips(rom: &[u8], records: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut output = rom.to_vec();
+    let mut reader = PatchReader::new(records);
+    loop {
+        let offset = reader.bytes(3)?;
+        if offset == IPS_EOF {
+            break;
+        }
+        let offset = u32::from_be_bytes([0, offset[0], offset[1], offset[2]]) as usize;
+        let size = reader.u16_be()? as usize;
+        // A record with no size is run length encoded, filling the range with a single value
+        let (size, data) = if size == 0 {
+            let size = reader.u16_be()? as usize;
+            (size, vec![reader.byte()?; size])
+        } else {
+            (size, reader.bytes(size)?.to_vec())
+        };
+        if output.len() < offset + size {
+            output.resize(offset + size, 0);
+        }
+        output[offset..offset + size].copy_from_slice(&data);
+    }
+
+    // Some patches follow the end marker with the size to truncate the output to
+    if let Ok(size) = reader.bytes(3) {
+        output.truncate(u32::from_be_bytes([0, size[0], size[1], size[2]]) as usize);
+    }
+    Ok(output)
+}
+
+fn apply_bps(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    let body_end = patch
+        .len()
+        .checked_sub(BPS_FOOTER_SIZE)
+        .filter(|end| *end >= BPS_MAGIC.len())
+        .ok_or_else(|| invalid("BPS patch is truncated"))?;
+    let footer = &patch[body_end..];
+    let checksum =
+        |idx: usize| u32::from_le_bytes(footer[idx * 4..idx * 4 + 4].try_into().unwrap());
+    if crc32(&patch[..patch.len() - 4]) != checksum(2) {
+        return Err(invalid("BPS patch is corrupted"));
+    }
+    if crc32(rom) != checksum(0) {
+        return Err(invalid("BPS patch was made for a different ROM"));
+    }
+
+    let mut reader = PatchReader::new(&patch[BPS_MAGIC.len()..body_end]);
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    let metadata_size = reader.number()?;
+    reader.bytes(metadata_size)?;
+    if source_size != rom.len() {
+        return Err(invalid("BPS patch was made for a different ROM"));
+    }
+
+    let mut output = Vec::new();
+    // Copies from the source and from the output each keep their own position, which the copies move relative to
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+    while !reader.is_empty() {
+        let action = reader.number()?;
+        let length = (action >> 2) + 1;
+        if output.len() + length > target_size {
+            return Err(invalid("BPS patch writes past the end of the patched ROM"));
+        }
+        match action & 3 {
+            // Source read, copying from the same position in the original ROM
+            0 => {
+                let start = output.len();
+                let data = rom
+                    .get(start..start + length)
+                    .ok_or_else(|| invalid("BPS patch reads past the end of the ROM"))?;
+                output.extend_from_slice(data);
+            }
+            // Target read, copying from the patch itself
+            1 => output.extend_from_slice(reader.bytes(length)?),
+            // Source copy, copying from anywhere in the original ROM
+            2 => {
+                source_offset = reader.relative_offset(source_offset)?;
+                let data = source_offset
+                    .checked_add(length)
+                    .and_then(|end| rom.get(source_offset..end))
+                    .ok_or_else(|| invalid("BPS patch reads past the end of the ROM"))?;
+                output.extend_from_slice(data);
+                source_offset += length;
+            }
+            // Target copy, copying from earlier in the output. The ranges may overlap, which repeats a pattern,
+            // so this has to go one byte at a time
+            _ => {
+                target_offset = reader.relative_offset(target_offset)?;
+                for _ in 0..length {
+                    let byte = *output.get(target_offset).ok_or_else(|| {
+                        invalid("BPS patch copies from past the end of the output")
+                    })?;
+                    output.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+
+    if output.len() != target_size || crc32(&output) != checksum(1) {
+        return Err(invalid("BPS patch produced the wrong ROM"));
+    }
+    Ok(output)
+}
+
+/// Reads the fields of a patch in order, failing rather than panicking if the patch ends early
+struct PatchReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if count > self.data.len() {
+            return Err(invalid("Patch is truncated"));
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16_be(&mut self) -> Result<u16, Error> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Reads one of BPS's variable length numbers, which store 7 bits per byte, ending with the byte that has
+    /// its top bit set
+    fn number(&mut self) -> Result<usize, Error> {
+        let mut number = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.byte()?;
+            number = (byte as usize & 0x7F)
+                .checked_mul(shift)
+                .and_then(|value| number.checked_add(value))
+                .ok_or_else(|| invalid("BPS patch has an oversized number"))?;
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+            shift = shift
+                .checked_mul(0x80)
+                .ok_or_else(|| invalid("BPS patch has an oversized number"))?;
+            number = number
+                .checked_add(shift)
+                .ok_or_else(|| invalid("BPS patch has an oversized number"))?;
+        }
+    }
+
+    /// Reads a signed offset for a BPS copy, and applies it to the given position
+    fn relative_offset(&mut self, position: usize) -> Result<usize, Error> {
+        let offset = self.number()?;
+        let distance = offset >> 1;
+        let position = if offset & 1 != 0 {
+            position.checked_sub(distance)
+        } else {
+            position.checked_add(distance)
+        };
+        position.ok_or_else(|| invalid("BPS patch copies from outside the ROM"))
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
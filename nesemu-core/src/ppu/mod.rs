@@ -0,0 +1,524 @@
+//! The PPU is responsible for drawing the final frame to the screen. It runs in parallel alongside
+//! the CPU, and the two components communicate via a set of PPU registers that are memory mapped into
+//! the CPU's address space. Synchronization of the two components is performed by the CPU only
+//! communicating with the PPU during the VBLANK period (after all visible scanlines have been drawn).
+//!
+//! The real PPU hardware renders one dot to the screen at a time, but this emulation takes a shortcut
+//! and renders entire scanlines one at a time. This means attempts to change PPU state in the middle of a
+//! scanline will not work correctly, but this behavior appears to be very rare in actual programs
+
+use super::{bus::Bus, screen::FrameBuffer};
+use bitfield::{Bit, BitMut, BitRange, BitRangeMut};
+use ppu_registers::{PPUCTRL, PPUMASK, PPUSTATUS};
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable},
+    register_bitfields,
+    registers::InMemoryRegister,
+};
+
+mod palette;
+pub mod palette_memory; // TODO: Change to private vis after refactoring bus
+pub mod ppu_registers;
+pub mod tile_cache;
+
+// TODO:
+// Sprite overflow
+// 8x16 bit sprite mode
+// Respect PPUMASK disabling sprites or bg
+
+register_bitfields! [
+    u8,
+    SpriteAttribs [
+        PALETTE           OFFSET(0) NUMBITS(2) [],
+        UNIMPLEMENTED     OFFSET(2) NUMBITS(3) [],
+        PRIORITY          OFFSET(5) NUMBITS(1) [],
+        FLIP_HORZ         OFFSET(6) NUMBITS(1) [],
+        FLIP_VERT         OFFSET(7) NUMBITS(1) [],
+    ]
+];
+
+/// The PPU stores a small amount of internal RAM called Object Attribute Memory. This is where all sprites
+/// and their properties are to be stored by the CPU in anticipation for their rendering to the screen.
+struct OAMSprite {
+    y_pixel_coord: u8,
+    tile_idx: u8,
+    attribs: InMemoryRegister<u8, SpriteAttribs::Register>,
+    x_pixel_coord: u8,
+    sprite_0: bool, // Sprite 0 is a special sprite that can be used to signal the CPU when the PPU has begun
+    // rendering it, for example so the CPU knows how much of the screen has been rendered
+    current_x: u8, // When drawing, we need to keep track of how many pixels along a scanline we have drawn
+                   // for this sprite
+}
+
+impl OAMSprite {
+    pub fn from(data: &[u8], sprite_0: bool) -> Self {
+        Self {
+            y_pixel_coord: data[0],
+            tile_idx: data[1],
+            attribs: InMemoryRegister::new(data[2]),
+            x_pixel_coord: data[3],
+            current_x: data[3],
+            sprite_0,
+        }
+    }
+}
+
+pub struct PPU {
+    nametable_addr: u16,
+    x_scroll: u8,
+    y_scroll: u8,
+    scanlines: usize,
+    /// Holds the sprites on the next scanline, of which only the first `sprite_count` are in use
+    secondary_oam: [OAMSprite; PPU::MAX_SPRITES_PER_SCANLINE],
+    sprite_count: usize,
+    dots: usize,
+    /// Dots run since power on, for timing changes to the PPU address bus
+    dot_clock: u64,
+    generated_interrupt: bool,
+    frame_origin: (usize, usize),
+    last_frame_origin: (usize, usize),
+    skip_rendering: bool,
+    /// How many times to repeat the last scanline of VBLANK each frame, to overclock the CPU
+    extra_scanlines: usize,
+    /// How many extra scanlines have been run so far this frame
+    extra_scanlines_run: usize,
+}
+
+impl PPU {
+    const VISIBLE_DOTS_PER_SCANLINE: usize = 256;
+    const MAX_SPRITES_PER_SCANLINE: usize = 8;
+    const DOTS_PER_SCANLINE: usize = 341;
+    const NUM_SCANLINES: usize = 262;
+    pub fn new() -> Self {
+        Self {
+            nametable_addr: 0x2000,
+            x_scroll: 0,
+            y_scroll: 0,
+            scanlines: 0,
+            secondary_oam: std::array::from_fn(|_| OAMSprite::from(&[0xFF; 4], false)),
+            sprite_count: 0,
+            dots: 21, // Simulates power-up delay
+            dot_clock: 0,
+            generated_interrupt: false,
+            frame_origin: (0, 0),
+            last_frame_origin: (0, 0),
+            skip_rendering: false,
+            extra_scanlines: 0,
+            extra_scanlines_run: 0,
+        }
+    }
+
+    /// Steps the PPU simulation by one cycle. Returns when the fb has been fully updated for this frame
+    /// and is ready to present to the screen.
+    ///
+    /// Note that the PPU only updates the framebuffer when a full scanline's worth of cycles has been
+    /// completed.
+    pub fn step<T: FrameBuffer>(
+        &mut self,
+        fb: &mut T,
+        bus: &mut Bus,
+    ) -> Result<bool, &'static str> {
+        self.step_cycles(fb, bus, 1)
+    }
+
+    /// Steps the PPU simulation by the given number of cycles. Returns whether the fb was fully updated for a
+    /// frame along the way.
+    ///
+    /// Fails if a tile can't be read from the cartridge, in which case the scanline is left partially drawn.
+    ///
+    /// Most dots don't change any state besides the dot counter, so rather than stepping one dot at a time,
+    /// the counter skips straight ahead to the next dot where something happens.
+    pub fn step_cycles<T: FrameBuffer>(
+        &mut self,
+        fb: &mut T,
+        bus: &mut Bus,
+        cycles: usize,
+    ) -> Result<bool, &'static str> {
+        let mut remaining = cycles;
+        let mut finished_frame = false;
+        while remaining > 0 {
+            // At the start of each scanline, we have to check if a split x scroll occured...
+            if self.dots == 0 {
+                self.x_scroll = bus.ppu_get_registers().fine_x;
+                // We only have to modify the coarse x scroll in the nametable addr
+                self.nametable_addr.set_bit_range(4, 0, self.x_scroll / 8);
+                // According to loopy docs, we reset bit 10 of the nametable address on every scanline
+                self.nametable_addr
+                    .set_bit(10, bus.ppu_get_registers().ppuctrl.get().bit(0));
+            }
+
+            // Though each dot represents a single pixel, we don't render until the scanline is finished, so the
+            // only other dot of interest is the one where vblank starts or ends
+            let next_event = if self.dots == 0 && (self.scanlines == 241 || self.scanlines == 261) {
+                1
+            } else {
+                PPU::DOTS_PER_SCANLINE
+            };
+            let skipped = remaining.min(next_event - self.dots);
+            self.dots += skipped;
+            self.dot_clock += skipped as u64;
+            remaining -= skipped;
+
+            if self.dots == PPU::DOTS_PER_SCANLINE {
+                // We just completed a scanline, render it
+                // Don't bother drawing to the overdraw scanlines, they will never be seen anyway
+                if self.scanlines <= 239 {
+                    // Sprite zero hits and the Zapper's light are only detected while drawing, so scanlines
+                    // where either could happen are drawn even while skipping rendering
+                    let sprites = &self.secondary_oam[..self.sprite_count];
+                    if bus.zapper.sees_scanline(self.scanlines) {
+                        let mut view = bus.zapper.view(fb);
+                        self.draw_scanline(&mut view, bus)?;
+                        bus.zapper.sense(view, self.dot_clock);
+                    } else if !self.skip_rendering || sprites.iter().any(|sprite| sprite.sprite_0) {
+                        self.draw_scanline(fb, bus)?;
+                    } else {
+                        self.increment_coarse_y();
+                    }
+                    self.sprite_evaluation(self.scanlines + 1, bus);
+                }
+                if self.scanlines <= 239 || self.scanlines == 261 {
+                    self.replay_fetches(bus);
+                }
+                if self.scanlines == 260 && self.extra_scanlines_run < self.extra_scanlines {
+                    // Run the last scanline of VBLANK again. Nothing happens on it, so the CPU just gets more
+                    // time before rendering starts
+                    self.extra_scanlines_run += 1;
+                } else {
+                    self.scanlines += 1;
+                }
+                self.dots = 0;
+
+                if self.scanlines >= PPU::NUM_SCANLINES {
+                    // We just finished a frame
+                    self.last_frame_origin = self.frame_origin;
+                    self.prepare_next_frame(bus);
+                    finished_frame = true;
+                }
+                continue;
+            }
+
+            // Handle vblank
+            if self.scanlines == 241 && self.dots == 1 {
+                bus.ppu_get_registers_mut()
+                    .ppustatus
+                    .modify(PPUSTATUS::VBLANK::SET);
+                self.generated_interrupt = bus
+                    .ppu_get_registers_mut()
+                    .ppuctrl
+                    .is_set(PPUCTRL::NMI_ENABLE);
+            } else if self.scanlines == 261 && self.dots == 1 {
+                // Pre-render scanline...
+                bus.ppu_get_registers_mut()
+                    .ppustatus
+                    .modify(PPUSTATUS::VBLANK::CLEAR);
+                bus.ppu_get_registers_mut()
+                    .ppustatus
+                    .modify(PPUSTATUS::SPRITE0_HIT::CLEAR);
+                bus.ppu_get_registers_mut().writes_ignored = false;
+            }
+        }
+        bus.set_ppu_dot(self.dot_clock);
+        Ok(finished_frame)
+    }
+
+    /// Sets whether to skip drawing into the fb, for frames that will never be shown. The PPU state still
+    /// advances exactly as if the frame were drawn, but the fb is left with unspecified contents
+    pub fn set_skip_rendering(&mut self, skip: bool) {
+        self.skip_rendering = skip;
+    }
+
+    /// Sets how many extra scanlines to add to the end of VBLANK each frame, overclocking the CPU so that games
+    /// which can't finish their work within a frame slow down less
+    ///
+    /// The visible scanlines, and when VBLANK starts and the NMI fires, are unaffected, but VBLANK lasts longer,
+    /// and mappers that count CPU cycles see more of them each frame. Zero runs at the console's real speed
+    pub fn set_extra_scanlines(&mut self, scanlines: usize) {
+        self.extra_scanlines = scanlines;
+    }
+
+    /// Checks whether the PPU has generated a NMI. Calls to this function will clear the pending MMI from the PPU.
+    pub fn generated_interrupt(&mut self) -> bool {
+        let res = self.generated_interrupt;
+        if self.generated_interrupt {
+            self.generated_interrupt = false;
+        }
+
+        res
+    }
+
+    /// Gets the pixel coordinate within the 512x480 space spanned by the four nametables that was situated at
+    /// the top-left corner of the screen for the last completed frame
+    ///
+    /// Only the scroll position at the start of the frame is reported, so mid-frame changes to the x scroll
+    /// (eg for a status bar) are not reflected here.
+    pub fn scroll_origin(&self) -> (usize, usize) {
+        self.last_frame_origin
+    }
+
+    /// Determines which sprites are occupying the NEXT scanline and will therefore need to be drawn during
+    /// the next scanline
+    ///
+    /// Sprites are drawn one scanline at a time, to make blending/transparency with the background colors
+    /// simpler to implement (and more accurate to how the real hardware works).
+    /// There are potential performance optimizations here, to not do O(n) search of the OAM every single
+    /// scanline
+    fn sprite_evaluation(&mut self, next_scanline: usize, bus: &mut Bus) {
+        self.sprite_count = 0;
+
+        for (i, sprite_data) in bus.oam_ram.chunks(4).enumerate() {
+            let y_coord = sprite_data[0] as usize;
+            // TODO: IMPORTANT: Sprites are sometimes 16 pixels long!
+            if (y_coord..y_coord + 8).contains(&next_scanline) {
+                // Like the real hardware, any sprites past the first 8 on a scanline aren't drawn
+                if self.sprite_count == PPU::MAX_SPRITES_PER_SCANLINE {
+                    break;
+                }
+                self.secondary_oam[self.sprite_count] = OAMSprite::from(sprite_data, i == 0);
+                self.sprite_count += 1;
+            }
+        }
+    }
+
+    /// Puts the addresses the PPU fetched from over the scanline just completed on the PPU address bus, so that
+    /// the cartridge sees A12 change as it would on the real hardware
+    ///
+    /// Since scanlines are drawn all at once, this happens at the end of the scanline rather than as each fetch
+    /// is made. Only the pattern table of each fetch is reproduced, since A12 is all that cartridges watch
+    fn replay_fetches(&self, bus: &mut Bus) {
+        let registers = bus.ppu_get_registers();
+        if !registers.ppumask.is_set(PPUMASK::SHOW_BACKGROUND)
+            && !registers.ppumask.is_set(PPUMASK::SHOW_SPRITES)
+        {
+            return;
+        }
+        let ctrl = &registers.ppuctrl;
+        let bg_table = ctrl.read(PPUCTRL::BPTNTABLE_ADDR) as u16 * 0x1000;
+        let sprite_table = ctrl.read(PPUCTRL::SPTNTABLE_ADDR) as u16 * 0x1000;
+        let tall_sprites = ctrl.is_set(PPUCTRL::SPRITE_SIZE);
+        let start = self.dot_clock.saturating_sub(PPU::DOTS_PER_SCANLINE as u64);
+
+        // The 32 tiles of this scanline, then the sprites of the next one, then the first two tiles of the next
+        for tile in 0..32 {
+            PPU::fetch_tile(bus, start + 1 + tile * 8, bg_table);
+        }
+        for slot in 0..PPU::MAX_SPRITES_PER_SCANLINE {
+            // Unused slots fetch tile $FF. 8x16 sprites choose their pattern table with the tile's lowest bit
+            let tile = self.secondary_oam[..self.sprite_count]
+                .get(slot)
+                .map_or(0xFF, |sprite| sprite.tile_idx);
+            let table = if tall_sprites {
+                (tile & 0x01) as u16 * 0x1000
+            } else {
+                sprite_table
+            };
+            PPU::fetch_tile(bus, start + 257 + slot as u64 * 8, table);
+        }
+        for tile in 0..2 {
+            PPU::fetch_tile(bus, start + 321 + tile * 8, bg_table);
+        }
+    }
+
+    /// Fetches take two dots each: the nametable byte, the attribute byte, then the two planes of the pattern.
+    /// Sprites make two nametable fetches in place of the first two
+    fn fetch_tile(bus: &mut Bus, dot: u64, pattern_table: u16) {
+        bus.ppu_address_bus(0x2000, dot);
+        bus.ppu_address_bus(0x23C0, dot + 2);
+        bus.ppu_address_bus(pattern_table, dot + 4);
+        bus.ppu_address_bus(pattern_table | 0x08, dot + 6);
+    }
+
+    /// Reconfigures the PPU state in preparation for beginning to render a new frame
+    fn prepare_next_frame(&mut self, bus: &mut Bus) {
+        self.scanlines = 0;
+        self.extra_scanlines_run = 0;
+        // The sprites found while drawing the last visible scanline were for the scanline below it, not for the
+        // top of the next frame, which never has any sprites
+        self.sprite_count = 0;
+        // Update x_scroll and y_scroll
+        self.y_scroll = bus.ppu_get_registers().fine_y;
+        self.x_scroll = bus.ppu_get_registers().fine_x;
+
+        // Reconstruct the starting address of the nametable based on PPUSCROLL
+        let nametable_start_idx =
+            (((self.y_scroll as usize) / 8) * 32) + (self.x_scroll as usize / 8);
+        let nametable_base_addr = bus.ppu_get_nametable_base_addr();
+        self.nametable_addr = (nametable_base_addr + nametable_start_idx) as u16;
+
+        // Keep track of where this frame starts in nametable pixel space, for debugging purposes
+        let nametable_x = (nametable_base_addr & 0x400 != 0) as usize * 256;
+        let nametable_y = (nametable_base_addr & 0x800 != 0) as usize * 240;
+        self.frame_origin = (
+            nametable_x + self.x_scroll as usize,
+            nametable_y + self.y_scroll as usize,
+        );
+
+        // According to https://emudev.de/nes-emulator/fixing-smb/
+        // we are supposed to clear the PPUCTRL nametable address every frame.
+        // This conflicts with the loopy docs and I feel like it can't be truly correct,
+        // btu it does solve the SMB bugs I've been having
+        bus.ppu_get_registers_mut()
+            .ppuctrl
+            .modify(PPUCTRL::NTABLE_ADDR::Addr2000);
+    }
+
+    /// Draws a single scanline into the framebuffer
+    ///
+    /// Fails if a tile can't be read from the cartridge
+    fn draw_scanline<T: FrameBuffer>(
+        &mut self,
+        fb: &mut T,
+        bus: &mut Bus,
+    ) -> Result<(), &'static str> {
+        let pixel_space_y = self.scanlines;
+        let (_, coarse_y) = self.get_coarse_coords();
+
+        // x and y scroll represent the nametable pixel coordinate that is to be situated at the top-left
+        // corner of the screen.
+        // However, we also need "wrapped" versions of these coordinates which represent offsets into an
+        // individual 8x8 pixel nametable entry
+        let mut fine_x_wrapped = self.x_scroll % 8;
+        let fine_y_wrapped = self.y_scroll % 8;
+
+        // The nametable entry, attribute and pattern data are the same across all 8 pixels of a tile, so they
+        // are only fetched when drawing reaches a new tile
+        let mut palette_num_bg = 0;
+        let mut tile_row = [0u8; 8];
+
+        for pixel_space_x in 0..PPU::VISIBLE_DOTS_PER_SCANLINE {
+            let (coarse_x, _) = self.get_coarse_coords();
+            if pixel_space_x == 0 || fine_x_wrapped == 0 {
+                // Compute pattern table idx and palette idx
+                // This monstrosity taken from https://www.nesdev.org/wiki/PPU_scrolling#Wrapping_around
+                let attrib_table_addr = 0x23C0
+                    | (self.nametable_addr & 0x0C00)
+                    | ((self.nametable_addr >> 4) & 0x38)
+                    | ((self.nametable_addr >> 2) & 0x07);
+                let attrib_table_val = bus.ppu_read_nametable(attrib_table_addr as usize)?;
+                let pt_idx = bus.ppu_read_nametable(self.nametable_addr as usize)?;
+
+                // Get tile data bg color
+                palette_num_bg = PPU::compute_bg_palette_num(attrib_table_val, coarse_x, coarse_y);
+                // Get the row of the chr tile that this scanline passes through
+                tile_row =
+                    bus.ppu_get_pattern_row(pt_idx, true, fine_y_wrapped + pixel_space_y as u8)?;
+            }
+            let palette_idx_bg = tile_row[fine_x_wrapped as usize];
+            let bg_color = bus
+                .palette_memory
+                .get_color_by_idx(palette_num_bg, palette_idx_bg)?;
+
+            // Write the bg pixel into the fb. This may be overwritten by a sprite
+            fb.plot_pixel(pixel_space_x, pixel_space_y, bg_color);
+            let bg_pixel_transparent = bus
+                .palette_memory
+                .is_entry_transparent(palette_num_bg, palette_idx_bg);
+
+            // Handle sprites, drawing the earliest sprite last so that it ends up on top
+            let sprite_iter = self.secondary_oam[..self.sprite_count]
+                .iter_mut()
+                .rev()
+                .filter(|sprite| sprite.current_x == pixel_space_x as u8);
+            for sprite in sprite_iter {
+                if sprite.current_x >= sprite.x_pixel_coord.saturating_add(8) {
+                    continue; // No more drawing needed for this sprite on this scanline
+                }
+                // Prepare to render a single pixel of a sprite
+                let mut x = pixel_space_x as u8 - sprite.x_pixel_coord;
+                let mut y = (pixel_space_y as u8 - sprite.y_pixel_coord) % 8;
+                if sprite.attribs.is_set(SpriteAttribs::FLIP_HORZ) {
+                    x = 7 - x;
+                }
+                if sprite.attribs.is_set(SpriteAttribs::FLIP_VERT) {
+                    y = 7 - y;
+                }
+                let sprite_palette_idx =
+                    bus.ppu_get_pattern_row(sprite.tile_idx, false, y)?[x as usize];
+                // if the sprite pixel isn't transparent...
+                if sprite_palette_idx != 0 {
+                    let sprite_palette_num: u8 = sprite.attribs.read(SpriteAttribs::PALETTE) + 4;
+                    let sprite_color = bus
+                        .palette_memory
+                        .get_color_by_idx(sprite_palette_num, sprite_palette_idx)?;
+
+                    // Is this a sprite zero hit?
+                    if sprite.sprite_0 && !bg_pixel_transparent {
+                        bus.ppu_get_registers_mut()
+                            .ppustatus
+                            .modify(PPUSTATUS::SPRITE0_HIT::SET);
+                    }
+
+                    // Is the sprite pixel behind a transparent background pixel?
+                    if sprite.attribs.is_set(SpriteAttribs::PRIORITY) && !bg_pixel_transparent {
+                        // If this sprite pixel is meant to be drawn in the background,
+                        // we must re-write the background pixel color into here
+                        // We have to REWRITE the background color because the pixel color may have
+                        // been adjusted by a previous sprite overlapping this sprite
+                        fb.plot_pixel(pixel_space_x, pixel_space_y, bg_color);
+                    } else {
+                        fb.plot_pixel(pixel_space_x, pixel_space_y, sprite_color);
+                    }
+                }
+                sprite.current_x += 1;
+            }
+
+            // Handle offset x wrapping into the next nametable entry
+            fine_x_wrapped += 1;
+            if fine_x_wrapped > 7 {
+                fine_x_wrapped = 0;
+
+                // Increment Coarse X
+                if coarse_x == 31 {
+                    self.nametable_addr.set_bit_range(4, 0, 0); // Wrap Coarse X to zero
+                                                                // Flip bit to switch horz nametable
+                    self.nametable_addr
+                        .set_bit(10, !self.nametable_addr.bit(10));
+                } else {
+                    self.nametable_addr.set_bit_range(4, 0, coarse_x + 1);
+                }
+            }
+        }
+
+        self.increment_coarse_y();
+        Ok(())
+    }
+
+    /// Moves the nametable address down to the next row of tiles, if the scanline just drawn was the last
+    /// one of the current row
+    fn increment_coarse_y(&mut self) {
+        let (_, coarse_y) = self.get_coarse_coords();
+        // If our y coordinate is about to enter a new nametable entry...
+        if (self.y_scroll as usize + self.scanlines) % 8 == 7 {
+            // Increment Coarse Y
+            if coarse_y == 29 {
+                self.nametable_addr.set_bit_range(9, 5, 0); // Wrap coarse y to zero
+                                                            // Flip bit to switch vert nametable
+                self.nametable_addr
+                    .set_bit(11, !self.nametable_addr.bit(11));
+            } else if coarse_y == 31 {
+                self.nametable_addr.set_bit_range(9, 5, 0);
+            } else {
+                self.nametable_addr.set_bit_range(9, 5, coarse_y + 1);
+            }
+        }
+    }
+
+    fn get_coarse_coords(&mut self) -> (u8, u8) {
+        // Our coarse coordinates index into individual cells in the nametable
+        let coarse_y = self.nametable_addr.bit_range(9, 5);
+        let coarse_x = self.nametable_addr.bit_range(4, 0);
+
+        (coarse_x, coarse_y)
+    }
+
+    fn compute_bg_palette_num(attrib_value: u8, coarse_x: u8, coarse_y: u8) -> u8 {
+        // The second bit of our tile coordinates contains the information
+        // we need to determine our quadrant
+        match (coarse_y.bit(1), coarse_x.bit(1)) {
+            (false, false) => attrib_value.bit_range(1, 0),
+            (false, true) => attrib_value.bit_range(3, 2),
+            (true, false) => attrib_value.bit_range(5, 4),
+            (true, true) => attrib_value.bit_range(7, 6),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::screen::Color;
+
+use super::palette::lookup_palette_color;
+
+pub struct PaletteMemory {
+    memory: [u8; 32],
+}
+
+impl PaletteMemory {
+    pub fn new() -> Self {
+        Self { memory: [0u8; 32] }
+    }
+
+    /// Writes an entry. Palette memory is mirrored every 32 bytes through $3F00-$3FFF
+    pub fn set_entry(&mut self, addr: usize, val: u8) {
+        let idx = addr % 0x20;
+        // The transparent entries of the sprite palettes are mirrors of the background palettes' ones
+        let idx = if idx >= 0x10 && idx.is_multiple_of(4) {
+            idx - 0x10
+        } else {
+            idx
+        };
+        self.memory[idx] = val;
+    }
+
+    /// Reads an entry. Palette memory is mirrored every 32 bytes through $3F00-$3FFF
+    pub fn get_entry(&self, addr: usize) -> u8 {
+        // Mirror transparent colors into the universal background color
+        if addr.is_multiple_of(4) {
+            self.memory[0]
+        } else {
+            self.memory[addr % 0x20]
+        }
+    }
+
+    pub fn is_entry_transparent(&self, palette_num: u8, idx: u8) -> bool {
+        let palette_idx = (palette_num * 4) + idx;
+        palette_idx.is_multiple_of(4)
+    }
+
+    pub fn get_color_by_idx(&self, palette_num: u8, idx: u8) -> Result<Color, &'static str> {
+        let addr = 0x3F00 + (palette_num as usize * 4) + idx as usize;
+        // Only the low 6 bits of an entry select a color
+        let color_idx = self.get_entry(addr) & 0x3F;
+        lookup_palette_color(color_idx)
+    }
+}
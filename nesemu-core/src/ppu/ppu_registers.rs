@@ -1,4 +1,4 @@
-use tock_registers::{register_bitfields, registers::InMemoryRegister};
+use tock_registers::{interfaces::Writeable, register_bitfields, registers::InMemoryRegister};
 
 register_bitfields!(
     u8,
@@ -51,6 +51,22 @@ pub struct PPURegisters {
     pub write_latch: bool,
     pub fine_x: u8,
     pub fine_y: u8,
+    /// Set by a reset, after which the PPU ignores writes to PPUCTRL, PPUMASK, PPUSCROLL and PPUADDR until
+    /// the end of the next VBLANK
+    pub writes_ignored: bool,
+}
+
+impl PPURegisters {
+    /// Clears the registers that are affected by the reset line. PPUSTATUS and PPUADDR are left untouched
+    pub fn reset(&mut self) {
+        self.ppuctrl.set(0);
+        self.ppumask.set(0);
+        self.ppudata = 0;
+        self.fine_x = 0;
+        self.fine_y = 0;
+        self.write_latch = false;
+        self.writes_ignored = true;
+    }
 }
 
 impl Default for PPURegisters {
@@ -64,6 +80,7 @@ impl Default for PPURegisters {
             fine_x: 0,
             fine_y: 0,
             write_latch: false,
+            writes_ignored: false,
         }
     }
 }
@@ -0,0 +1,67 @@
+//! Pattern table tiles store each row of 8 pixels as two bitplanes, spread 8 bytes apart. Picking the
+//! palette index of a pixel out of the bitplanes is done for every pixel drawn, so tiles are decoded once
+//! into one palette index per pixel and kept until the CHR data behind them changes.
+
+use bitfield::Bit;
+
+/// The number of tiles across both pattern tables
+const TILE_COUNT: usize = 512;
+const TILE_SIZE: usize = 16;
+
+/// A single decoded row of a tile, holding the palette index of each pixel from left to right
+pub type TileRow = [u8; 8];
+
+pub struct TileCache {
+    rows: Vec<[TileRow; 8]>,
+    valid: [bool; TILE_COUNT],
+}
+
+impl TileCache {
+    pub fn new() -> Self {
+        Self {
+            rows: vec![[[0u8; 8]; 8]; TILE_COUNT],
+            valid: [false; TILE_COUNT],
+        }
+    }
+
+    /// Gets a row of the tile at the given CHR address, decoding the tile from `tile_data` if it isn't
+    /// cached already
+    ///
+    /// Fails if `tile_data` fails to read the tile
+    pub fn get_row(
+        &mut self,
+        chr_addr: usize,
+        row: u8,
+        tile_data: impl FnOnce() -> Result<[u8; 16], &'static str>,
+    ) -> Result<TileRow, &'static str> {
+        let tile = chr_addr / TILE_SIZE;
+        if !self.valid[tile] {
+            self.rows[tile] = TileCache::decode(&tile_data()?);
+            self.valid[tile] = true;
+        }
+        Ok(self.rows[tile][row as usize % 8])
+    }
+
+    /// Forgets the tile containing the given CHR address, such as when CHR RAM is written to
+    pub fn invalidate(&mut self, chr_addr: usize) {
+        self.valid[(chr_addr / TILE_SIZE) % TILE_COUNT] = false;
+    }
+
+    /// Forgets every tile, such as when the mapper may have switched CHR banks
+    pub fn invalidate_all(&mut self) {
+        self.valid = [false; TILE_COUNT];
+    }
+
+    fn decode(tile_data: &[u8; 16]) -> [TileRow; 8] {
+        let mut rows = [[0u8; 8]; 8];
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                // The leftmost pixel of a row is stored in the highest bit
+                let low_bit = u8::from(tile_data[y].bit(7 - x));
+                let high_bit = u8::from(tile_data[y + 8].bit(7 - x));
+                *pixel = low_bit + (high_bit << 1);
+            }
+        }
+        rows
+    }
+}
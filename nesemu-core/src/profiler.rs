@@ -0,0 +1,65 @@
+//! Measures how long each part of the console takes to emulate, to help find where slowdowns come from.
+//!
+//! Reading the clock for every instruction has a cost of its own, so nothing is measured until the profiler is
+//! enabled.
+
+use std::time::{Duration, Instant};
+
+/// The time spent emulating each part of the console
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Timings {
+    pub cpu: Duration,
+    pub ppu: Duration,
+    pub dma: Duration,
+}
+
+pub struct Profiler {
+    enabled: bool,
+    timings: Timings,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            timings: Timings::default(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.timings = Timings::default();
+    }
+
+    /// Gets the time spent since the last call, such as over the last frame, and starts counting again
+    pub fn take_timings(&mut self) -> Timings {
+        std::mem::take(&mut self.timings)
+    }
+
+    /// Reads the clock to time the next step of emulation, if profiling is enabled
+    pub(crate) fn start(&self) -> Option<Instant> {
+        self.enabled.then(Instant::now)
+    }
+
+    /// Adds the time spent on a CPU instruction or DMA transfer, then starts timing the PPU catching up with it
+    pub(crate) fn cpu_done(&mut self, start: Option<Instant>, dma: bool) -> Option<Instant> {
+        let start = start?;
+        let now = Instant::now();
+        if dma {
+            self.timings.dma += now - start;
+        } else {
+            self.timings.cpu += now - start;
+        }
+        Some(now)
+    }
+
+    pub(crate) fn ppu_done(&mut self, start: Option<Instant>) {
+        if let Some(start) = start {
+            self.timings.ppu += start.elapsed();
+        }
+    }
+}
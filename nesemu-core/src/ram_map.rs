@@ -0,0 +1,140 @@
+//! A RAM map names the addresses a game keeps its variables at, such as the player's position or the number of
+//! lives, along with how to read them and what they mean. Names from the RAM map can be used in watch
+//! expressions, and are shown next to the addresses they annotate in the trace log.
+//!
+//! Only the map itself lives here. Importing and exporting it is left to the frontend, so that RAM maps can be
+//! shared between users working on the same game in whatever format suits them.
+
+use super::bus::Bus;
+
+/// How the bytes at an address are read
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ValueType {
+    #[default]
+    U8,
+    I8,
+    /// Little endian, like the 6502's own pointers
+    U16,
+    I16,
+}
+
+impl ValueType {
+    pub const ALL: [ValueType; 4] = [ValueType::U8, ValueType::I8, ValueType::U16, ValueType::I16];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::U8 => "u8",
+            ValueType::I8 => "i8",
+            ValueType::U16 => "u16",
+            ValueType::I16 => "i16",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        ValueType::ALL
+            .into_iter()
+            .find(|value_type| value_type.name().eq_ignore_ascii_case(name))
+            .ok_or("Unknown type, expected one of u8, i8, u16 and i16")
+    }
+
+    /// The number of bytes the value takes up
+    pub fn size(self) -> usize {
+        match self {
+            ValueType::U8 | ValueType::I8 => 1,
+            ValueType::U16 | ValueType::I16 => 2,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RamEntry {
+    pub address: u16,
+    pub name: String,
+    pub value_type: ValueType,
+    pub description: String,
+}
+
+impl RamEntry {
+    /// Checks whether the entry's value takes up the given address
+    pub fn contains(&self, address: usize) -> bool {
+        (self.address as usize..self.address as usize + self.value_type.size()).contains(&address)
+    }
+
+    /// Reads the entry's value without side effects, so that annotated registers aren't disturbed
+    ///
+    /// Fails if the address can't be read
+    pub fn read(&self, bus: &mut Bus) -> Result<i64, &'static str> {
+        let address = self.address as usize;
+        let low = bus.cpu_read_byte_no_modify(address)?;
+        Ok(match self.value_type {
+            ValueType::U8 => low as i64,
+            ValueType::I8 => low as i8 as i64,
+            ValueType::U16 | ValueType::I16 => {
+                let high = bus.cpu_read_byte_no_modify((address + 1) & 0xFFFF)?;
+                let value = u16::from_le_bytes([low, high]);
+                if self.value_type == ValueType::I16 {
+                    value as i16 as i64
+                } else {
+                    value as i64
+                }
+            }
+        })
+    }
+}
+
+/// The RAM map of the loaded game, in the order the entries were added
+#[derive(Clone, Default, Debug)]
+pub struct RamMap {
+    entries: Vec<RamEntry>,
+}
+
+impl RamMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[RamEntry] {
+        &self.entries
+    }
+
+    /// Adds an entry to the map
+    ///
+    /// Fails if the name couldn't be used in a watch expression, or is already taken by another entry
+    pub fn add(&mut self, entry: RamEntry) -> Result<(), &'static str> {
+        let mut chars = entry.name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(
+                "Names must be letters, digits and underscores, and not start with a digit",
+            );
+        }
+        if self.find(&entry.name).is_some() {
+            return Err("An entry with that name already exists");
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.entries.remove(index);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Finds the entry with the given name, ignoring case
+    pub fn find(&self, name: &str) -> Option<&RamEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds the first entry whose value takes up the given address
+    pub fn entry_at(&self, address: usize) -> Option<&RamEntry> {
+        self.entries.iter().find(|entry| entry.contains(address))
+    }
+}
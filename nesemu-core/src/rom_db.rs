@@ -0,0 +1,146 @@
+//! Identifies ROMs by the hash of their PRG and CHR data, looking them up in a database bundled with the
+//! emulator to find their canonical title and whether the dump is known to be good.
+//!
+//! The iNES header is left out of the hash, since it varies between otherwise identical dumps, and the same goes
+//! for the trainer. This matches the hashes used by No-Intro and NesCartDB, so entries can be copied from them.
+//!
+//! The database lives in `data/rom_db.txt`, which describes its format at the top.
+
+use std::{fmt::Write, sync::OnceLock};
+
+use super::{controller::ControllerType, util::crc32};
+
+const DATABASE: &str = include_str!("../data/rom_db.txt");
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RomHash {
+    pub crc32: u32,
+    pub sha1: [u8; 20],
+}
+
+impl RomHash {
+    /// Hashes the PRG ROM followed by the CHR ROM. Cartridges with CHR RAM have no CHR data to hash
+    pub fn new(prg_rom: &[u8], chr_rom: &[u8]) -> Self {
+        let mut data = prg_rom.to_vec();
+        data.extend_from_slice(chr_rom);
+        Self {
+            crc32: crc32(&data),
+            sha1: sha1_smol::Sha1::from(&data).digest().bytes(),
+        }
+    }
+
+    pub fn sha1_hex(&self) -> String {
+        self.sha1
+            .iter()
+            .fold(String::with_capacity(40), |mut hex, byte| {
+                let _ = write!(hex, "{:02x}", byte);
+                hex
+            })
+    }
+}
+
+/// The console a game was made for, which sets the timing of the CPU and PPU. Only NTSC timing is emulated so far
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Region {
+    #[default]
+    Ntsc,
+    Pal,
+    /// A famiclone common in Russia, with PAL's frame rate but NTSC's CPU timing
+    Dendy,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DumpStatus {
+    /// The ROM matches a dump known to be good
+    Verified,
+    /// The ROM matches a dump known to be bad, such as one that is missing data or was modified
+    Bad,
+    /// The ROM isn't in the database, so it might be a bad dump, a hack or homebrew
+    Unknown,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DatabaseEntry {
+    pub hash: RomHash,
+    pub good: bool,
+    pub title: String,
+}
+
+/// What is known about the ROM that was loaded, from its header and from the database
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RomInfo {
+    pub hash: RomHash,
+    /// The matching database entry, if there is one
+    pub entry: Option<&'static DatabaseEntry>,
+    pub mapper: u16,
+    pub submapper: u8,
+    pub prg_rom_size: usize,
+    /// Zero for cartridges with CHR RAM
+    pub chr_rom_size: usize,
+    pub region: Region,
+    pub controller: ControllerType,
+    /// Whether [`overrides`](super::overrides) replaced any of the header's settings
+    pub overridden: bool,
+}
+
+impl RomInfo {
+    /// The canonical title of the ROM, if it is in the database
+    pub fn title(&self) -> Option<&'static str> {
+        self.entry.map(|entry| entry.title.as_str())
+    }
+
+    pub fn dump_status(&self) -> DumpStatus {
+        match self.entry {
+            Some(entry) if entry.good => DumpStatus::Verified,
+            Some(_) => DumpStatus::Bad,
+            None => DumpStatus::Unknown,
+        }
+    }
+}
+
+/// Finds the database entry for a ROM. Both hashes have to match, so a CRC32 collision can't misidentify a ROM
+pub fn lookup(hash: &RomHash) -> Option<&'static DatabaseEntry> {
+    database().iter().find(|entry| entry.hash == *hash)
+}
+
+fn database() -> &'static [DatabaseEntry] {
+    static ENTRIES: OnceLock<Vec<DatabaseEntry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        DATABASE
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let entry = parse_entry(line);
+                if entry.is_none() {
+                    log::warn!("Skipping invalid ROM database entry '{}'", line);
+                }
+                entry
+            })
+            .collect()
+    })
+}
+
+fn parse_entry(line: &str) -> Option<DatabaseEntry> {
+    let mut fields = line.splitn(4, char::is_whitespace);
+    let crc32 = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let sha1_hex = fields.next()?;
+    if sha1_hex.len() != 40 {
+        return None;
+    }
+    let mut sha1 = [0u8; 20];
+    for (idx, byte) in sha1.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(sha1_hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+    }
+    let good = match fields.next()? {
+        "good" => true,
+        "bad" => false,
+        _ => return None,
+    };
+    let title = fields.next()?.trim().to_string();
+    Some(DatabaseEntry {
+        hash: RomHash { crc32, sha1 },
+        good,
+        title,
+    })
+}
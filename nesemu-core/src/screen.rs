@@ -0,0 +1,72 @@
+//! Frontends receive the rendered image through the [`FrameBuffer`] trait, which the PPU plots pixels into one
+//! at a time. Colors are plain RGB triplets, so that the core doesn't depend on any particular graphics library.
+
+use std::hash::Hasher;
+
+use xxhash_rust::xxh3::Xxh3Default;
+
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::from_rgb(0, 0, 0);
+
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+pub trait FrameBuffer {
+    fn plot_pixel(&mut self, x: usize, y: usize, color: Color);
+}
+
+/// A simple in-memory framebuffer, for frontends which don't need to display the image as it is drawn
+#[derive(Clone)]
+pub struct Frame {
+    pub pixels: Vec<Color>,
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Self {
+            pixels: vec![Color::BLACK; SCREEN_WIDTH * SCREEN_HEIGHT],
+        }
+    }
+
+    /// Hashes the image, with a hash that is the same on every build, so that frames can be compared against
+    /// ones rendered on other machines
+    pub fn hash(&self) -> u64 {
+        let mut hasher = Xxh3Default::new();
+        let mut row = [0u8; SCREEN_WIDTH * 3];
+        for pixels in self.pixels.chunks(SCREEN_WIDTH) {
+            for (bytes, pixel) in row.chunks_mut(3).zip(pixels) {
+                bytes.copy_from_slice(&[pixel.r, pixel.g, pixel.b]);
+            }
+            hasher.write(&row);
+        }
+        hasher.finish()
+    }
+}
+
+impl FrameBuffer for Frame {
+    fn plot_pixel(&mut self, x: usize, y: usize, color: Color) {
+        // Ignore pixels in the overscan area
+        if let Some(pixel) = self.pixels.get_mut(y * SCREEN_WIDTH + x) {
+            *pixel = color;
+        }
+    }
+}
+
+/// A framebuffer which discards every pixel, for when only the emulation itself matters, such as benchmarking
+pub struct NullFrameBuffer;
+
+impl FrameBuffer for NullFrameBuffer {
+    fn plot_pixel(&mut self, _: usize, _: usize, _: Color) {}
+}
@@ -0,0 +1,112 @@
+//! Snapshots of the console's memory, for finding which addresses a game keeps a piece of state at by comparing
+//! memory before and after that state changes, such as before and after losing a life.
+//!
+//! Snapshots cover CPU RAM, nametable RAM and OAM. They can be written to a file and read back, so that they can
+//! be compared across sessions or shared.
+
+use std::{
+    fmt::Display,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use super::bus::Bus;
+
+const CPU_RAM_SIZE: usize = 0x800;
+const PPU_RAM_SIZE: usize = 0x800;
+const OAM_SIZE: usize = 0x100;
+
+/// A part of the console's memory that is captured in snapshots
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryRegion {
+    CpuRam,
+    /// Nametable RAM, which the PPU sees at $2000-$2FFF through the cartridge's mirroring
+    PpuRam,
+    Oam,
+}
+
+impl Display for MemoryRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryRegion::CpuRam => write!(f, "CPU RAM"),
+            MemoryRegion::PpuRam => write!(f, "PPU RAM"),
+            MemoryRegion::Oam => write!(f, "OAM"),
+        }
+    }
+}
+
+/// A byte that differs between two snapshots, with its offset into the region it is in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Difference {
+    pub region: MemoryRegion,
+    pub offset: usize,
+    pub old: u8,
+    pub new: u8,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MemorySnapshot {
+    pub cpu_ram: Vec<u8>,
+    pub ppu_ram: Vec<u8>,
+    pub oam: Vec<u8>,
+}
+
+impl MemorySnapshot {
+    /// Captures the current contents of the console's memory
+    pub fn capture(bus: &Bus) -> Self {
+        Self {
+            cpu_ram: bus.cpu_ram().to_vec(),
+            ppu_ram: bus.ppu_ram().to_vec(),
+            oam: bus.oam_ram.to_vec(),
+        }
+    }
+
+    /// Gets every byte that differs from another snapshot, taking this snapshot's values as the old ones
+    pub fn diff(&self, newer: &MemorySnapshot) -> Vec<Difference> {
+        let regions = [
+            (MemoryRegion::CpuRam, &self.cpu_ram, &newer.cpu_ram),
+            (MemoryRegion::PpuRam, &self.ppu_ram, &newer.ppu_ram),
+            (MemoryRegion::Oam, &self.oam, &newer.oam),
+        ];
+        regions
+            .into_iter()
+            .flat_map(|(region, old, new)| {
+                old.iter()
+                    .zip(new.iter())
+                    .enumerate()
+                    .filter(|(_, (old, new))| old != new)
+                    .map(move |(offset, (&old, &new))| Difference {
+                        region,
+                        offset,
+                        old,
+                        new,
+                    })
+            })
+            .collect()
+    }
+
+    /// Writes the snapshot to a file, as the contents of each region one after another
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, [&self.cpu_ram[..], &self.ppu_ram, &self.oam].concat())
+    }
+
+    /// Reads a snapshot written by [`MemorySnapshot::save`]
+    ///
+    /// Fails if the file can't be read or isn't the size of a snapshot
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read(path)?;
+        if data.len() != CPU_RAM_SIZE + PPU_RAM_SIZE + OAM_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "File is not a memory snapshot",
+            ));
+        }
+        let (cpu_ram, rest) = data.split_at(CPU_RAM_SIZE);
+        let (ppu_ram, oam) = rest.split_at(PPU_RAM_SIZE);
+        Ok(Self {
+            cpu_ram: cpu_ram.to_vec(),
+            ppu_ram: ppu_ram.to_vec(),
+            oam: oam.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+
+/// A file that silently discards everything written to it if it couldn't be opened
+///
+/// Writes are buffered, and only reach the file once enough has been written or the file is dropped
+pub struct OptionalFile(Option<BufWriter<File>>);
+impl OptionalFile {
+    pub fn new(name: &str) -> Self {
+        Self(
+            OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(name)
+                .ok()
+                .map(BufWriter::new),
+        )
+    }
+}
+impl Write for OptionalFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.0.as_mut() {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.0.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The CRC-32 used to check BPS patches and to identify ROMs
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
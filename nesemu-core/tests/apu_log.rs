@@ -0,0 +1,46 @@
+//! Checks that the APU log records writes to the APU's registers with when they were made, using a generated ROM
+//! that writes to them and to the controller port between them
+
+mod common;
+
+use std::path::PathBuf;
+
+use nesemu_core::{apu_log::ApuWrite, screen::Frame, NES};
+
+#[test]
+fn records_apu_writes() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x0F,       // C000: LDA #$0F
+        0x8D, 0x15, 0x40, // C002: STA $4015
+        0x8D, 0x16, 0x40, // C005: STA $4016   ; Controller strobe, not the APU
+        0xA9, 0x3F,       // C008: LDA #$3F
+        0x8D, 0x00, 0x40, // C00A: STA $4000
+        0x4C, 0x0D, 0xC0, // C00D: JMP $C00D
+    ];
+    let path = common::nrom(&program, [0x0000, 0xC000, 0x0000]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.apu_log.set_enabled(true);
+    nes.run_frame(&mut Frame::new()).unwrap();
+    nes.run_frame(&mut Frame::new()).unwrap();
+    assert_eq!(nes.frame_count(), 2);
+
+    let write = |cycle, address, value| ApuWrite {
+        frame: 0,
+        cycle,
+        address,
+        value,
+    };
+    assert_eq!(
+        nes.apu_log.writes(),
+        [write(2, 0x4015, 0x0F), write(12, 0x4000, 0x3F)]
+    );
+
+    let log_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("apu_log.txt");
+    nes.apu_log.export(&log_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&log_path).unwrap(),
+        "; frame cycle register value\n0 2 $4015 $0F\n0 12 $4000 $3F\n"
+    );
+}
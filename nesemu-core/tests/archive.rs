@@ -0,0 +1,59 @@
+//! Loads ROMs out of a zip archive built at the start of each test
+
+use std::{fs::File, io::Write, path::PathBuf};
+
+use nesemu_core::{archive, NES};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+const NESTEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes");
+
+/// Writes a zip archive holding the given entries, with each entry's contents taken from the given file
+fn write_zip(name: &str, entries: &[(&str, &str)]) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let mut zip = ZipWriter::new(File::create(&path).unwrap());
+    for (entry, source) in entries {
+        zip.start_file(*entry, SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&std::fs::read(source).unwrap()).unwrap();
+    }
+    zip.finish().unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn first_rom() {
+    let readme = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/roms/sprite_hit/readme.txt"
+    );
+    let path = write_zip(
+        "first_rom.zip",
+        &[("readme.txt", readme), ("nestest.nes", NESTEST)],
+    );
+    assert!(archive::is_archive(&path));
+    assert_eq!(archive::list_roms(&path).unwrap(), ["nestest.nes"]);
+    assert_eq!(
+        archive::read_rom(&path).unwrap(),
+        std::fs::read(NESTEST).unwrap()
+    );
+    NES::new(path).unwrap();
+}
+
+#[test]
+fn chosen_rom() {
+    let path = write_zip(
+        "chosen_rom.zip",
+        &[("one.nes", NESTEST), ("two.nes", NESTEST)],
+    );
+    assert_eq!(archive::list_roms(&path).unwrap(), ["one.nes", "two.nes"]);
+    let rom_path = archive::entry_path(&path, "two.nes");
+    assert!(archive::is_archive(&rom_path));
+    NES::new(rom_path).unwrap();
+    assert!(NES::new(archive::entry_path(&path, "three.nes")).is_err());
+}
+
+#[test]
+fn no_roms() {
+    let path = write_zip("no_roms.zip", &[]);
+    assert!(archive::read_rom(&path).is_err());
+}
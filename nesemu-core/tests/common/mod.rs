@@ -0,0 +1,125 @@
+//! Support for running blargg's test ROMs headless, and for generating small ROMs for tests of their own.
+//!
+//! The newer ROMs report through PRG RAM: once the signature DE B0 61 is written to $6001-$6003, $6000 holds
+//! the status, which is $80 while the test is running and the result code once it has finished. A result of
+//! zero means the test passed, and anything else is explained by the text written from $6004 onwards.
+//!
+//! Older ROMs only show their result on screen, but also leave the result code at $F8 once they finish. There,
+//! a result of one means the test passed, and the meaning of any other code is listed in the ROM's readme.
+//!
+//! Generated ROMs are NROM-128 cartridges, with 16KB of PRG ROM mirrored at $8000 and $C000 and 8KB of blank CHR
+//! ROM.
+
+// Each test binary includes this module, but only uses the support for the ROMs it runs
+#![allow(dead_code)]
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+};
+
+use nesemu_core::{screen::Frame, RunResult, NES};
+
+/// Every ROM finishes within several seconds of emulated time, so anything much longer has hung
+const MAX_FRAMES: usize = 60 * 60;
+const STATUS_RUNNING: u8 = 0x80;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+/// How long the older ROMs are given to finish, since they don't report when they are still running
+const LEGACY_FRAMES: usize = 5 * 60;
+const LEGACY_PASSED: u8 = 1;
+
+/// Runs the ROM at the given path until it reports a result, panicking with its text output unless it passed
+pub fn run_blargg_test(rom_path: &str) {
+    let mut nes = NES::new(rom_path.to_string()).unwrap();
+    let mut frame = Frame::new();
+    for _ in 0..MAX_FRAMES {
+        run_frame(&mut nes, &mut frame);
+        let bus = nes.bus_mut();
+        let mut read = |address| bus.cpu_read_byte_no_modify(address).unwrap_or(0);
+        if [read(0x6001), read(0x6002), read(0x6003)] != SIGNATURE {
+            continue;
+        }
+        let status = read(0x6000);
+        if status == STATUS_RUNNING {
+            continue;
+        }
+        let text: Vec<u8> = (0x6004..0x8000)
+            .map(&mut read)
+            .take_while(|&byte| byte != 0)
+            .collect();
+        assert_eq!(
+            status,
+            0,
+            "{} failed with status {}:\n{}",
+            rom_path,
+            status,
+            String::from_utf8_lossy(&text)
+        );
+        return;
+    }
+    panic!("{} did not finish within {} frames", rom_path, MAX_FRAMES);
+}
+
+/// Runs one of the older ROMs at the given path, panicking with its result code unless it passed
+pub fn run_legacy_blargg_test(rom_path: &str) {
+    let mut nes = NES::new(rom_path.to_string()).unwrap();
+    let mut frame = Frame::new();
+    for _ in 0..LEGACY_FRAMES {
+        run_frame(&mut nes, &mut frame);
+    }
+    let result = nes.bus_mut().cpu_read_byte_no_modify(0xF8).unwrap();
+    assert_eq!(
+        result, LEGACY_PASSED,
+        "{} failed with result code {}",
+        rom_path, result
+    );
+}
+
+fn run_frame(nes: &mut NES, frame: &mut Frame) {
+    match nes.run_frame(frame) {
+        Ok(RunResult::FrameComplete) => {}
+        Ok(RunResult::Break(reason)) => panic!("Unexpected break: {:?}", reason),
+        Err(error) => panic!(
+            "CPU failed with error '{}' at {:#06X}",
+            error,
+            nes.cpu().program_counter()
+        ),
+    }
+}
+
+/// Builds the image of an NROM-128 ROM with the program at the start of PRG ROM, so at $C000 onwards, and the
+/// given NMI, reset and IRQ vectors. Data the program uses, such as interrupt handlers, goes after it in the
+/// program
+pub fn nrom_image(program: &[u8], vectors: [u16; 3]) -> Vec<u8> {
+    let mut rom = vec![b'N', b'E', b'S', 0x1A, 1, 1, 0, 0];
+    rom.resize(16, 0);
+    let mut prg = vec![0u8; 0x4000];
+    prg[..program.len()].copy_from_slice(program);
+    for (bytes, vector) in prg[0x3FFA..].chunks_mut(2).zip(vectors) {
+        bytes.copy_from_slice(&vector.to_le_bytes());
+    }
+    rom.extend_from_slice(&prg);
+    rom.resize(rom.len() + 0x2000, 0);
+    rom
+}
+
+/// Writes the ROM built by [`nrom_image`] to the target's temporary directory, returning its path
+pub fn nrom(program: &[u8], vectors: [u16; 3]) -> PathBuf {
+    let rom = nrom_image(program, vectors);
+    // Named after the contents, so tests running at the same time with different ROMs don't overwrite each other.
+    // The file is written under a name of its own and then moved into place, so a test never reads one that is
+    // half written by another test building the same ROM
+    let mut hasher = DefaultHasher::new();
+    rom.hash(&mut hasher);
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join(format!("nrom-{:016x}.nes", hasher.finish()));
+    let temp_path = dir.join(format!(
+        "nrom-{:016x}.{}.{:?}.tmp",
+        hasher.finish(),
+        std::process::id(),
+        std::thread::current().id()
+    ));
+    std::fs::write(&temp_path, rom).unwrap();
+    std::fs::rename(&temp_path, &path).unwrap();
+    path
+}
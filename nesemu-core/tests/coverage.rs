@@ -0,0 +1,37 @@
+//! Checks that coverage records exactly the bytes of executed instructions, using a generated ROM with a branch
+//! over code that never runs
+
+mod common;
+
+use nesemu_core::{screen::Frame, NES};
+
+#[test]
+fn records_executed_code() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x00,       // C000: LDA #$00
+        0xF0, 0x03,       // C002: BEQ $C007
+        0x4C, 0x04, 0xC0, // C004: JMP $C004   ; Never runs
+        0x4C, 0x07, 0xC0, // C007: JMP $C007
+    ];
+    let path = common::nrom(&program, [0x0000, 0xC000, 0x0000]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.coverage.set_enabled(true);
+    nes.run_frame(&mut Frame::new()).unwrap();
+
+    let coverage = &nes.coverage;
+    assert_eq!(coverage.executed_ranges(), [0..4, 7..10]);
+    assert_eq!(coverage.executed_bytes(), 7);
+    assert!(coverage
+        .to_report()
+        .starts_with("Executed 7 of 16384 bytes of PRG ROM (0.0%)\n000000-000003 (4 bytes)\n"));
+
+    // PRG flags followed by CHR flags, with code run from $C000-$DFFF marked as in the third 8KB window
+    let cdl = coverage.to_cdl();
+    assert_eq!(cdl.len(), 0x4000 + 0x2000);
+    assert_eq!(
+        cdl[..10],
+        [0x09, 0x09, 0x09, 0x09, 0, 0, 0, 0x09, 0x09, 0x09]
+    );
+}
@@ -0,0 +1,88 @@
+//! Checks that ld65 debug info maps a generated ROM back to its source, and that stepping follows the lines
+
+mod common;
+
+use nesemu_core::{debug_info::DebugInfo, screen::NullFrameBuffer, NES};
+
+/// Debug info as written by `ld65 --dbgfile` for the program below, with a line of C covering its first
+/// instruction, and records the debugger has no use for
+const DEBUG_INFO: &str = r#"version	major=2,minor=0
+info	csym=0,file=2,lib=0,line=8,mod=1,scope=1,seg=1,span=5,sym=3,type=1
+file	id=0,name="src/main.s",size=120,mtime=0x65000000,mod=0
+file	id=1,name="src/main.c",size=40,mtime=0x65000000,mod=0
+line	id=0,file=0,line=5,span=0
+line	id=1,file=0,line=6,span=1
+line	id=2,file=0,line=7,span=2
+line	id=3,file=0,line=9
+line	id=4,file=0,line=10,span=3
+line	id=5,file=0,line=11,span=4
+line	id=6,file=1,line=3,type=1,count=1,span=0
+line	id=7,file=0,line=2,type=2,count=1,span=4
+mod	id=0,name="main.o",file=0
+seg	id=0,name="CODE",start=0x00C000,size=0x000A,addrsize=absolute,type=ro,oname="test.nes",ooffs=16
+span	id=0,seg=0,start=0,size=2
+span	id=1,seg=0,start=2,size=3
+span	id=2,seg=0,start=5,size=3
+span	id=3,seg=0,start=8,size=1
+span	id=4,seg=0,start=9,size=1
+scope	id=0,name="",mod=0,size=10,span=0+1+2+3+4
+sym	id=0,name="forever",addrsize=absolute,scope=0,def=2,val=0xC005,seg=0,type=lab
+sym	id=1,name="increment",addrsize=absolute,scope=0,def=4,ref=1,val=0xC008,seg=0,type=lab
+sym	id=2,name="COUNT",addrsize=zeropage,scope=0,def=3,val=0x10,type=equ
+"#;
+
+#[test]
+fn maps_code_to_source_and_steps_lines() {
+    #[rustfmt::skip]
+    let program = [
+        0xA2, 0x00,       // C000: LDX #$00    main.s:5
+        0x20, 0x08, 0xC0, // C002: JSR $C008   main.s:6
+        0x4C, 0x05, 0xC0, // C005: JMP $C005   main.s:7
+        0xE8,             // C008: INX         main.s:10
+        0x60,             // C009: RTS         main.s:11
+    ];
+    let path = common::nrom(&program, [0xC005, 0xC000, 0xC005]);
+
+    let debug_info = DebugInfo::parse(DEBUG_INFO).unwrap();
+    assert_eq!(debug_info.files(), ["src/main.s", "src/main.c"]);
+    assert_eq!(debug_info.symbol("increment").unwrap().address, 0xC008);
+    assert_eq!(debug_info.symbol_at(0xC005).unwrap().name, "forever");
+    // Constants aren't addresses
+    assert!(debug_info.symbol("COUNT").is_none());
+    assert!(debug_info.symbol_at(0x10).is_none());
+    // Operands belong to the line of their instruction, and the ROM offset has the iNES header taken off
+    let line = debug_info.line_at(0xC004, Some(0x0004)).unwrap();
+    assert_eq!((line.file, line.line, line.prg_rom_offset), (0, 6, Some(2)));
+    // The same address in another bank isn't the same code
+    assert!(debug_info.line_at(0xC004, Some(0x4004)).is_none());
+    assert!(debug_info.line_at(0xC00A, None).is_none());
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.debug_info = debug_info;
+    let location = |nes: &NES| {
+        let program_counter = nes.cpu().program_counter();
+        let line = nes.source_line(program_counter).unwrap();
+        (program_counter, line.file, line.line)
+    };
+    // C is preferred over the assembly of the same code, and assembly over a macro expanded into it
+    assert_eq!(location(&nes), (0xC000, 1, 3));
+    let mut steps = Vec::new();
+    for _ in 0..4 {
+        nes.step_source_line(&mut NullFrameBuffer).unwrap();
+        steps.push(location(&nes));
+    }
+    assert_eq!(
+        steps,
+        [
+            (0xC002, 0, 6),
+            (0xC008, 0, 10),
+            (0xC009, 0, 11),
+            (0xC005, 0, 7)
+        ]
+    );
+    // A line that loops forever gives up rather than hanging
+    nes.step_source_line(&mut NullFrameBuffer).unwrap();
+    assert_eq!(location(&nes), (0xC005, 0, 7));
+
+    assert!(DebugInfo::parse("span\tid=0,seg=0,start=0").is_err());
+}
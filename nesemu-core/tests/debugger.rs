@@ -0,0 +1,58 @@
+//! Checks that the debugger breaks on interrupts, using a generated ROM that runs a BRK and then enables NMIs
+
+mod common;
+
+use nesemu_core::{
+    debugger::{BreakReason, Interrupt},
+    screen::Frame,
+    RunResult, NES,
+};
+
+#[test]
+fn breaks_on_interrupts() {
+    #[rustfmt::skip]
+    let mut program = vec![
+        0x78,             // C000: SEI
+        0x00, 0x42,       // C001: BRK #$42
+        0xA9, 0x80,       // C003: LDA #$80
+        0x8D, 0x00, 0x20, // C005: STA $2000   ; Enable NMIs
+        0x4C, 0x08, 0xC0, // C008: JMP $C008
+    ];
+    // An IRQ handler at $C100 and an NMI handler at $C200, each just an RTI
+    program.resize(0x100, 0);
+    program.push(0x40);
+    program.resize(0x200, 0);
+    program.push(0x40);
+    let path = common::nrom(&program, [0xC200, 0xC000, 0xC100]);
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.debugger.break_on_nmi = true;
+    nes.debugger.break_on_brk = true;
+    let mut frame = Frame::new();
+
+    assert_eq!(
+        nes.run_frame(&mut frame),
+        Ok(RunResult::Break(BreakReason::Interrupt {
+            kind: Interrupt::Brk,
+            from: 0xC001,
+            handler: 0xC100,
+        }))
+    );
+    assert_eq!(nes.cpu().program_counter(), 0xC100);
+    // BRK skips the byte after it, and pushes the status with the B flag set
+    let stack = &nes.bus().cpu_ram()[0x1FB..0x1FE];
+    assert_eq!(stack[1..], [0x03, 0xC0]);
+    assert_ne!(stack[0] & 0x10, 0);
+
+    assert_eq!(
+        nes.run_frame(&mut frame),
+        Ok(RunResult::Break(BreakReason::Interrupt {
+            kind: Interrupt::Nmi,
+            from: 0xC008,
+            handler: 0xC200,
+        }))
+    );
+
+    // Without breaking on NMIs, the rest of the frame runs through
+    nes.debugger.break_on_nmi = false;
+    assert_eq!(nes.run_frame(&mut frame), Ok(RunResult::FrameComplete));
+}
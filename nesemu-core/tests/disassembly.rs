@@ -0,0 +1,52 @@
+//! Checks that disassembly decodes each addressing mode and reads the vectors, using a generated ROM
+
+mod common;
+
+use nesemu_core::{
+    disassembly::{disassemble, disassemble_range, read_vector, VECTORS},
+    NES,
+};
+
+#[test]
+fn disassembles_code_and_vectors() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x0F,       // C000: LDA #$0F
+        0x9D, 0x00, 0x03, // C002: STA $0300,X
+        0xB1, 0x10,       // C005: LDA ($10),Y
+        0x0A,             // C007: ASL A
+        0xD0, 0xF6,       // C008: BNE $C000
+        0x6C, 0xFC, 0xFF, // C00A: JMP ($FFFC)
+        0x02,             // C00D: Not an opcode
+        0x40,             // C00E: RTI
+    ];
+    let path = common::nrom(&program, [0xC00E, 0xC000, 0xC00E]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    let bus = nes.bus_mut();
+    let lines: Vec<_> = disassemble_range(bus, 0xC000, 8)
+        .into_iter()
+        .map(|instruction| (instruction.address, instruction.text))
+        .collect();
+    let expected = [
+        (0xC000, "LDA #$0F"),
+        (0xC002, "STA $0300,X"),
+        (0xC005, "LDA ($10),Y"),
+        (0xC007, "ASL A"),
+        (0xC008, "BNE $C000"),
+        (0xC00A, "JMP ($FFFC)"),
+        (0xC00D, ".db $02"),
+        (0xC00E, "RTI"),
+    ];
+    assert_eq!(
+        lines,
+        expected.map(|(address, text)| (address, text.to_string()))
+    );
+    assert_eq!(disassemble(bus, 0xC002).unwrap().bytes, [0x9D, 0x00, 0x03]);
+
+    let handlers: Vec<_> = VECTORS
+        .iter()
+        .map(|&(_, vector)| read_vector(bus, vector).unwrap())
+        .collect();
+    assert_eq!(handlers, [0xC00E, 0xC000, 0xC00E]);
+}
@@ -0,0 +1,53 @@
+//! Checks the parsing and evaluation of watch expressions against a console with known register and memory values
+
+use nesemu_core::{expression::Expression, NES};
+
+const ROM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes");
+
+fn console() -> NES {
+    let mut nes = NES::new(ROM_PATH.to_string()).unwrap();
+    let registers = nes.cpu_mut().registers_mut();
+    registers.accumulator = 0x12;
+    registers.x_reg = 3;
+    registers.program_counter = 0xC000;
+    let bus = nes.bus_mut();
+    bus.cpu_write_byte(0x0300, 0x34).unwrap();
+    bus.cpu_write_byte(0x0301, 0x12).unwrap();
+    bus.cpu_write_byte(0x0303, 0x80).unwrap();
+    nes
+}
+
+fn evaluate(nes: &mut NES, text: &str) -> Result<i64, &'static str> {
+    Expression::parse(text)?.evaluate(nes)
+}
+
+#[test]
+fn evaluates() {
+    let mut nes = console();
+    let cases = [
+        ("[$0300]+[$0301]*256", 0x1234),
+        ("[$300 + x]", 0x80),
+        ("a", 0x12),
+        ("PC", 0xC000),
+        ("1 + 2 * 3", 7),
+        ("(1 + 2) * 3", 9),
+        ("10 - 4 - 3", 3),
+        ("-5 + 8", 3),
+        ("~0 & $FF", 0xFF),
+        ("1 << 4 | 1", 17),
+        ("[$0301] == $12", 1),
+        ("!([$0300] > 100)", 1),
+        ("7 % 4 ^ 1", 2),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(evaluate(&mut nes, text), Ok(expected), "{}", text);
+    }
+}
+
+#[test]
+fn rejects_bad_expressions() {
+    let mut nes = console();
+    for text in ["", "1 +", "[$0300", "(1", "foo", "1 2", "$", "1 / 0"] {
+        assert!(evaluate(&mut nes, text).is_err(), "{}", text);
+    }
+}
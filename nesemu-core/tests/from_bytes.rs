@@ -0,0 +1,44 @@
+//! Checks that a ROM image held in memory runs without touching the filesystem, using a generated ROM that counts
+//! up in RAM
+
+mod common;
+
+use nesemu_core::{screen::Frame, RunResult, NES};
+
+fn rom() -> Vec<u8> {
+    #[rustfmt::skip]
+    let program = [
+        0xE6, 0x10,       // C000: INC $10
+        0x4C, 0x00, 0xC0, // C002: JMP $C000
+    ];
+    common::nrom_image(&program, [0xC000, 0xC000, 0xC000])
+}
+
+#[test]
+fn loads_rom_from_bytes() {
+    let mut nes = NES::from_bytes(rom()).unwrap();
+    assert_eq!(nes.rom_path(), "");
+    assert_eq!(nes.patch_path(), None);
+
+    let mut frame = Frame::new();
+    assert_eq!(nes.run_frame(&mut frame), Ok(RunResult::FrameComplete));
+    assert_ne!(nes.bus().cpu_ram()[0x10], 0);
+    // There is no file to save to, so saving quietly does nothing
+    nes.save().unwrap();
+
+    // Power cycling reloads the cartridge from the image that was kept
+    nes.power_cycle().unwrap();
+    assert_eq!(nes.bus().cpu_ram()[0x10], 0);
+    assert_eq!(nes.cpu().program_counter(), 0xC000);
+    assert_eq!(nes.run_frame(&mut frame), Ok(RunResult::FrameComplete));
+}
+
+#[test]
+fn rejects_malformed_bytes() {
+    assert!(NES::from_bytes(Vec::new()).is_err());
+    assert!(NES::from_bytes(b"not a rom".to_vec()).is_err());
+    // A header claiming more PRG ROM than is present
+    let mut truncated = rom();
+    truncated.truncate(0x1000);
+    assert!(NES::from_bytes(truncated).is_err());
+}
@@ -0,0 +1,53 @@
+//! Parses tables of corrected iNES headers, and checks that a corrected header changes the mapper a ROM is loaded
+//! with
+
+mod common;
+
+use nesemu_core::{header_db, NES};
+
+/// Header for a mapper 16 cartridge with one bank each of PRG and CHR ROM and vertical mirroring
+const CORRECTED: [u8; 16] = [
+    b'N', b'E', b'S', 0x1A, 1, 1, 0x01, 0x10, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+#[test]
+fn bundled_table_is_valid() {
+    let table = include_str!("../data/header_corrections.txt");
+    assert_eq!(header_db::parse(table), header_db::bundled());
+}
+
+#[test]
+fn parses_table() {
+    let table = "
+        # Comments and blank lines are skipped
+
+        0123abcd 4e45531a010101100000000000000000   # Trailing comment
+        0123abce 4e45531a0101                       # Too short
+        0123abcf 00000000000000000000000000000000   # Not an iNES header
+        xyz 4e45531a010101100000000000000000        # Not a CRC32
+    ";
+    let entries = header_db::parse(table);
+    assert_eq!(entries, [(0x0123abcd, CORRECTED)]);
+    assert_eq!(header_db::lookup(&entries, 0x0123abcd), Some(CORRECTED));
+    assert_eq!(header_db::lookup(&entries, 0x0123abce), None);
+}
+
+#[test]
+fn corrected_header_changes_mapper() {
+    // A dump of a mapper 16 game whose header gives mapper 0. The program is just a loop
+    let rom = common::nrom_image(&[0x4C, 0x00, 0xC0], [0xC000, 0xC000, 0xC000]);
+    let body = rom[16..].to_vec();
+
+    // With no trainer, the CRC32 the table is keyed by is the same as the one the ROM database uses
+    let nes = NES::from_bytes(rom).unwrap();
+    assert_eq!(nes.rom_info().mapper, 0);
+    let crc32 = nes.rom_info().hash.crc32;
+
+    let table = format!("{:08x} 4e45531a010101100000000000000000", crc32);
+    let entries = header_db::parse(&table);
+    let header = header_db::lookup(&entries, crc32).unwrap();
+    let mut corrected = header.to_vec();
+    corrected.extend_from_slice(&body);
+    let nes = NES::from_bytes(corrected).unwrap();
+    assert_eq!(nes.rom_info().mapper, 16);
+}
@@ -0,0 +1,90 @@
+//! Checks that hooks registered on the console are called as emulation runs, and can pause it, using a generated
+//! ROM that enables NMIs and then counts up in RAM
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use nesemu_core::{debugger::BreakReason, hooks::Control, screen::Frame, RunResult, NES};
+
+#[test]
+fn calls_hooks_and_breaks() {
+    #[rustfmt::skip]
+    let mut program = vec![
+        0xA9, 0x80,       // C000: LDA #$80
+        0x8D, 0x00, 0x20, // C002: STA $2000   ; Enable NMIs
+        0xE6, 0x10,       // C005: INC $10
+        0x4C, 0x05, 0xC0, // C007: JMP $C005
+    ];
+    // RTI at $C200, for the NMI and IRQ handlers
+    program.resize(0x200, 0);
+    program.push(0x40);
+    let path = common::nrom(&program, [0xC200, 0xC000, 0xC200]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let nmis = Arc::new(Mutex::new(0));
+    let writes = Arc::new(Mutex::new(Vec::new()));
+    let instructions = Arc::new(Mutex::new(0));
+    {
+        let frames = frames.clone();
+        nes.hooks.on_frame_complete(move |nes| {
+            frames.lock().unwrap().push(nes.frame_count());
+            Control::Continue
+        });
+        let nmis = nmis.clone();
+        nes.hooks.on_nmi(move |nes| {
+            assert_eq!(nes.cpu().program_counter(), 0xC200);
+            *nmis.lock().unwrap() += 1;
+            Control::Continue
+        });
+        let writes = writes.clone();
+        nes.hooks.on_memory_write(move |_, address, value| {
+            // Leave out the NMI pushing to the stack
+            if !(0x100..0x200).contains(&address) {
+                writes.lock().unwrap().push((address, value));
+            }
+            Control::Continue
+        });
+        let instructions = instructions.clone();
+        nes.hooks.on_instruction(move |_, address| {
+            let mut instructions = instructions.lock().unwrap();
+            *instructions += 1;
+            // Pause the first time round the loop
+            if address == 0xC007 && *instructions == 4 {
+                Control::Break
+            } else {
+                Control::Continue
+            }
+        });
+    }
+
+    let mut frame = Frame::new();
+    assert_eq!(
+        nes.run_frame(&mut frame),
+        Ok(RunResult::Break(BreakReason::Hook))
+    );
+    assert_eq!(nes.cpu().program_counter(), 0xC005);
+    assert_eq!(*instructions.lock().unwrap(), 4);
+    assert_eq!(*writes.lock().unwrap(), [(0x2000, 0x80), (0x10, 1)]);
+
+    for _ in 0..3 {
+        assert_eq!(nes.run_frame(&mut frame), Ok(RunResult::FrameComplete));
+    }
+    assert_eq!(*frames.lock().unwrap(), [1, 2, 3]);
+    assert_eq!(*nmis.lock().unwrap(), 3);
+    // Every increment of $10 was seen, and nothing else was written outside the stack
+    let writes = writes.lock().unwrap();
+    let count = nes.bus().cpu_ram()[0x10];
+    assert_eq!(
+        writes.len(),
+        1 + writes.iter().filter(|w| w.0 == 0x10).count()
+    );
+    assert_eq!(writes.last(), Some(&(0x10, count)));
+
+    // Hooks no longer run once cleared
+    nes.hooks.clear();
+    assert!(nes.hooks.is_empty());
+    nes.run_frame(&mut frame).unwrap();
+    assert_eq!(*frames.lock().unwrap(), [1, 2, 3]);
+}
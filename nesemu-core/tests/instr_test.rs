@@ -0,0 +1,100 @@
+//! Runs blargg's instr_test-v5 ROMs, which check the result of every official instruction in every addressing
+//! mode, along with its effect on the flags.
+
+mod common;
+
+use common::run_blargg_test;
+
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/instr_test");
+
+fn run_test(name: &str) {
+    run_blargg_test(&format!("{}/{}.nes", ROM_DIR, name));
+}
+
+#[test]
+fn basics() {
+    run_test("instr_basics");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn implied() {
+    run_test("instr_imp");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn immediate() {
+    run_test("instr_imm");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn zero_page() {
+    run_test("instr_zp");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn zero_page_indexed() {
+    run_test("instr_zp_xy");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn absolute() {
+    run_test("instr_abs");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn absolute_indexed() {
+    run_test("instr_abs_xy");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn indirect_x() {
+    run_test("instr_ind_x");
+}
+
+#[test]
+#[ignore = "needs the unofficial opcodes, which aren't emulated"]
+fn indirect_y() {
+    run_test("instr_ind_y");
+}
+
+#[test]
+fn branches() {
+    run_test("instr_branches");
+}
+
+#[test]
+fn stack() {
+    run_test("instr_stack");
+}
+
+#[test]
+fn jmp_jsr() {
+    run_test("instr_jmp_jsr");
+}
+
+#[test]
+fn rts() {
+    run_test("instr_rts");
+}
+
+#[test]
+fn rti() {
+    run_test("instr_rti");
+}
+
+#[test]
+fn brk() {
+    run_test("instr_brk");
+}
+
+#[test]
+fn special() {
+    run_test("instr_special");
+}
@@ -0,0 +1,407 @@
+//! Runs small generated programs on each mapper, checking their bank switching and IRQs
+//!
+//! Each ROM is made of 8KB PRG banks whose first byte is the bank's number, apart from the last bank, which holds
+//! the program at $E000, an IRQ handler at $E100 and an NMI handler at $E200.
+
+use std::path::PathBuf;
+
+use nesemu_core::{
+    audio::Audio,
+    debugger::{BankMemory, BreakReason},
+    screen::Frame,
+    RunResult, NES,
+};
+
+const PRG_BANK_SIZE: usize = 0x2000;
+
+/// Builds an iNES ROM with the given mapper and number of 8KB PRG banks, and no CHR ROM
+fn build_rom(mapper: u8, prg_banks: usize, program: &[u8], irq_handler: &[u8]) -> Vec<u8> {
+    let mut rom = vec![
+        b'N',
+        b'E',
+        b'S',
+        0x1A,
+        (prg_banks / 2) as u8,
+        0,
+        (mapper & 0x0F) << 4,
+        mapper & 0xF0,
+    ];
+    rom.resize(16, 0);
+
+    let mut prg = vec![0u8; prg_banks * PRG_BANK_SIZE];
+    for bank in 0..prg_banks {
+        prg[bank * PRG_BANK_SIZE] = bank as u8;
+    }
+    let last_bank = (prg_banks - 1) * PRG_BANK_SIZE;
+    prg[last_bank..last_bank + program.len()].copy_from_slice(program);
+    prg[last_bank + 0x100..last_bank + 0x100 + irq_handler.len()].copy_from_slice(irq_handler);
+    // RTI
+    prg[last_bank + 0x200] = 0x40;
+    // NMI, reset and IRQ vectors
+    let vectors = prg.len() - 6;
+    prg[vectors..].copy_from_slice(&[0x00, 0xE2, 0x00, 0xE0, 0x00, 0xE1]);
+
+    rom.extend_from_slice(&prg);
+    rom
+}
+
+fn run(name: &str, rom: &[u8], frames: usize) -> NES {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, rom).unwrap();
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    let mut frame = Frame::new();
+    for _ in 0..frames {
+        nes.run_frame(&mut frame).unwrap();
+    }
+    nes
+}
+
+#[test]
+fn mmc3() {
+    #[rustfmt::skip]
+    let program = [
+        0x78,             // SEI
+        0xA9, 0x06,       // LDA #$06
+        0x8D, 0x00, 0x80, // STA $8000
+        0xA9, 0x02,       // LDA #$02
+        0x8D, 0x01, 0x80, // STA $8001   ; Bank 2 at $8000
+        0xAD, 0x00, 0x80, // LDA $8000
+        0x85, 0x10,       // STA $10
+        0xA9, 0x46,       // LDA #$46
+        0x8D, 0x00, 0x80, // STA $8000   ; Swap $8000 and $C000
+        0xAD, 0x00, 0x80, // LDA $8000
+        0x85, 0x11,       // STA $11
+        0xAD, 0x00, 0xC0, // LDA $C000
+        0x85, 0x12,       // STA $12
+        0xA9, 0x07,       // LDA #$07
+        0x8D, 0x00, 0xC0, // STA $C000   ; IRQ every 8 scanlines
+        0x8D, 0x01, 0xC0, // STA $C001
+        0x8D, 0x01, 0xE0, // STA $E001   ; Enable the IRQ
+        0xA9, 0x08,       // LDA #$08
+        0x8D, 0x00, 0x20, // STA $2000   ; Sprite patterns at $1000, so A12 rises once per scanline
+        0xA9, 0x18,       // LDA #$18
+        0x8D, 0x01, 0x20, // STA $2001   ; Show the background and sprites
+        0x58,             // CLI
+        0x4C, 0x35, 0xE0, // JMP $E035
+    ];
+    #[rustfmt::skip]
+    let irq_handler = [
+        0xE6, 0x13,       // INC $13
+        0x8D, 0x00, 0xE0, // STA $E000   ; Acknowledge
+        0x8D, 0x01, 0xE0, // STA $E001
+        0x40,             // RTI
+    ];
+    let mut nes = run("mmc3.nes", &build_rom(4, 8, &program, &irq_handler), 1);
+
+    let ram = nes.bus().cpu_ram();
+    assert_eq!(ram[0x10], 2);
+    // The second last bank is fixed at whichever of $8000 and $C000 isn't switchable
+    assert_eq!(ram[0x11], 6);
+    assert_eq!(ram[0x12], 2);
+
+    // A12 rises on the 240 visible scanlines and the pre-render one, and every eighth of those interrupts
+    let before = ram[0x13];
+    nes.run_frame(&mut Frame::new()).unwrap();
+    let irqs = nes.bus().cpu_ram()[0x13].wrapping_sub(before);
+    assert!((29..=31).contains(&irqs), "IRQ fired {} times", irqs);
+}
+
+#[test]
+fn vrc7() {
+    #[rustfmt::skip]
+    let program = [
+        0x78,             // SEI
+        0xA9, 0x02,       // LDA #$02
+        0x8D, 0x00, 0x80, // STA $8000   ; Bank 2 at $8000
+        0xAD, 0x00, 0x80, // LDA $8000
+        0x85, 0x10,       // STA $10
+        0xA9, 0x05,       // LDA #$05
+        0x8D, 0x10, 0x80, // STA $8010   ; Bank 5 at $A000
+        0xAD, 0x00, 0xA0, // LDA $A000
+        0x85, 0x11,       // STA $11
+        0xA9, 0x00,       // LDA #$00
+        0x8D, 0x10, 0xE0, // STA $E010   ; IRQ every 256 CPU cycles
+        0xA9, 0x07,       // LDA #$07
+        0x8D, 0x00, 0xF0, // STA $F000   ; Enable the IRQ in cycle mode
+        0x58,             // CLI
+        0x4C, 0x20, 0xE0, // JMP $E020
+    ];
+    #[rustfmt::skip]
+    let irq_handler = [
+        0xE6, 0x12,       // INC $12
+        0x8D, 0x10, 0xF0, // STA $F010   ; Acknowledge
+        0x40,             // RTI
+    ];
+    let nes = run("vrc7.nes", &build_rom(85, 8, &program, &irq_handler), 1);
+
+    let ram = nes.bus().cpu_ram();
+    assert_eq!(ram[0x10], 2);
+    assert_eq!(ram[0x11], 5);
+    // A frame is about 29780 CPU cycles, so the IRQ fires about 116 times
+    assert!(
+        (110..=120).contains(&ram[0x12]),
+        "IRQ fired {} times",
+        ram[0x12]
+    );
+}
+
+#[test]
+fn vrc7_audio() {
+    // A custom instrument whose modulator is turned all the way down, so the carrier plays an almost pure sine
+    // wave, then a note of F-number $120 in block 4, which is 437Hz
+    let writes = [
+        (0x00, 0x21),
+        (0x01, 0x21),
+        (0x02, 0x3F),
+        (0x03, 0x00),
+        (0x04, 0xF0),
+        (0x05, 0xF0),
+        (0x06, 0x00),
+        (0x07, 0x00),
+        (0x30, 0x00),
+        (0x10, 0x20),
+        (0x20, 0x19),
+    ];
+    let mut program = Vec::new();
+    for (register, value) in writes {
+        // LDA #register, STA $9010, LDA #value, STA $9030
+        program.extend_from_slice(&[0xA9, register, 0x8D, 0x10, 0x90]);
+        program.extend_from_slice(&[0xA9, value, 0x8D, 0x30, 0x90]);
+    }
+    let end = 0xE000 + program.len() as u16;
+    program.extend_from_slice(&[0x4C, end as u8, (end >> 8) as u8]);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("vrc7_audio.nes");
+    std::fs::write(&path, build_rom(85, 8, &program, &[])).unwrap();
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.audio.set_enabled(true);
+    let mut frame = Frame::new();
+    for _ in 0..60 {
+        nes.run_frame(&mut frame).unwrap();
+    }
+
+    let samples = nes.audio.take_samples();
+    assert!((43_000..=45_000).contains(&samples.len()));
+    let peak = samples
+        .iter()
+        .fold(0f32, |peak, sample| peak.max(sample.abs()));
+    assert!(peak > 0.1, "Peak level was {}", peak);
+    let rising = samples
+        .windows(2)
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count();
+    let hz = rising as f64 * Audio::SAMPLE_RATE as f64 / samples.len() as f64;
+    assert!((430.0..=444.0).contains(&hz), "Played {}Hz", hz);
+
+    // Bit 6 of $E000 holds the sound chip in reset, silencing it
+    nes.bus_mut().cpu_write_byte(0xE000, 0x40).unwrap();
+    nes.run_frame(&mut frame).unwrap();
+    let samples = nes.audio.take_samples();
+    assert!(samples.iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn bandai_eeprom() {
+    #[rustfmt::skip]
+    let program = [
+        0x78,             // E000: SEI
+        0x20, 0x3D, 0xE0, // E001: JSR start
+        0xA9, 0x05,       // E004: LDA #$05    ; Write to address 5
+        0x20, 0x58, 0xE0, // E006: JSR send
+        0xA9, 0xA5,       // E009: LDA #$A5
+        0x20, 0x58, 0xE0, // E00B: JSR send
+        0x20, 0x48, 0xE0, // E00E: JSR stop
+        0x20, 0x3D, 0xE0, // E011: JSR start
+        0xA9, 0x85,       // E014: LDA #$85    ; Read from address 5
+        0x20, 0x58, 0xE0, // E016: JSR send
+        0x20, 0x7F, 0xE0, // E019: JSR receive
+        0xA5, 0x01,       // E01C: LDA $01
+        0x85, 0x10,       // E01E: STA $10
+        0xA9, 0x02,       // E020: LDA #$02
+        0x8D, 0x08, 0x80, // E022: STA $8008   ; 16KB bank 2 at $8000
+        0xAD, 0x00, 0x80, // E025: LDA $8000
+        0x85, 0x11,       // E028: STA $11
+        0xA9, 0xFF,       // E02A: LDA #$FF
+        0x8D, 0x0B, 0x80, // E02C: STA $800B
+        0xA9, 0x00,       // E02F: LDA #$00
+        0x8D, 0x0C, 0x80, // E031: STA $800C   ; IRQ after 256 CPU cycles
+        0xA9, 0x01,       // E034: LDA #$01
+        0x8D, 0x0A, 0x80, // E036: STA $800A   ; Enable the IRQ
+        0x58,             // E039: CLI
+        0x4C, 0x3A, 0xE0, // E03A: JMP $E03A
+
+        // start: SDA falls while SCL is high
+        0xA9, 0x60,       // E03D: LDA #$60
+        0x8D, 0x0D, 0x80, // E03F: STA $800D
+        0xA9, 0x20,       // E042: LDA #$20
+        0x8D, 0x0D, 0x80, // E044: STA $800D
+        0x60,             // E047: RTS
+
+        // stop: SDA rises while SCL is high
+        0xA9, 0x00,       // E048: LDA #$00
+        0x8D, 0x0D, 0x80, // E04A: STA $800D
+        0xA9, 0x20,       // E04D: LDA #$20
+        0x8D, 0x0D, 0x80, // E04F: STA $800D
+        0xA9, 0x60,       // E052: LDA #$60
+        0x8D, 0x0D, 0x80, // E054: STA $800D
+        0x60,             // E057: RTS
+
+        // send: clocks out A least significant bit first, then clocks the acknowledgement
+        0x85, 0x00,       // E058: STA $00
+        0xA2, 0x08,       // E05A: LDX #$08
+        0xA9, 0x00,       // E05C: LDA #$00
+        0x46, 0x00,       // E05E: LSR $00
+        0x90, 0x02,       // E060: BCC $E064
+        0xA9, 0x40,       // E062: LDA #$40
+        0x8D, 0x0D, 0x80, // E064: STA $800D   ; SCL low, SDA is the bit
+        0x09, 0x20,       // E067: ORA #$20
+        0x8D, 0x0D, 0x80, // E069: STA $800D   ; SCL high
+        0x29, 0x40,       // E06C: AND #$40
+        0x8D, 0x0D, 0x80, // E06E: STA $800D   ; SCL low
+        0xCA,             // E071: DEX
+        0xD0, 0xE8,       // E072: BNE $E05C
+        0xA9, 0x60,       // E074: LDA #$60
+        0x8D, 0x0D, 0x80, // E076: STA $800D
+        0xA9, 0x40,       // E079: LDA #$40
+        0x8D, 0x0D, 0x80, // E07B: STA $800D
+        0x60,             // E07E: RTS
+
+        // receive: clocks a byte into $01 least significant bit first, without acknowledging it
+        0xA2, 0x08,       // E07F: LDX #$08
+        0xA9, 0x40,       // E081: LDA #$40
+        0x8D, 0x0D, 0x80, // E083: STA $800D   ; SCL low, SDA released
+        0xAD, 0x00, 0x60, // E086: LDA $6000
+        0x29, 0x10,       // E089: AND #$10
+        0xC9, 0x10,       // E08B: CMP #$10
+        0x66, 0x01,       // E08D: ROR $01
+        0xA9, 0x60,       // E08F: LDA #$60
+        0x8D, 0x0D, 0x80, // E091: STA $800D   ; SCL high
+        0xCA,             // E094: DEX
+        0xD0, 0xEA,       // E095: BNE $E081
+        0xA9, 0x40,       // E097: LDA #$40
+        0x8D, 0x0D, 0x80, // E099: STA $800D
+        0xA9, 0x60,       // E09C: LDA #$60
+        0x8D, 0x0D, 0x80, // E09E: STA $800D
+        0x60,             // E0A1: RTS
+    ];
+    #[rustfmt::skip]
+    let irq_handler = [
+        0xE6, 0x12,       // INC $12
+        0xA9, 0x01,       // LDA #$01
+        0x8D, 0x0A, 0x80, // STA $800A   ; Acknowledge and reload the counter
+        0x40,             // RTI
+    ];
+    let save_path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bandai_eeprom.sav");
+    let _ = std::fs::remove_file(&save_path);
+    let mut nes = run(
+        "bandai_eeprom.nes",
+        &build_rom(159, 16, &program, &irq_handler),
+        1,
+    );
+
+    let ram = nes.bus().cpu_ram();
+    assert_eq!(ram[0x10], 0xA5);
+    assert_eq!(ram[0x11], 4);
+    // The IRQ fires every 256 cycles plus the time taken to handle it
+    assert!(
+        (100..=116).contains(&ram[0x12]),
+        "IRQ fired {} times",
+        ram[0x12]
+    );
+
+    nes.save().unwrap();
+    let save = std::fs::read(&save_path).unwrap();
+    assert_eq!(save.len(), 128);
+    assert_eq!(save[5], 0xA5);
+    assert!(!save_path.with_extension("sav.tmp").exists());
+
+    // Saving again without any changes leaves the file alone
+    std::fs::remove_file(&save_path).unwrap();
+    nes.save().unwrap();
+    assert!(!save_path.exists());
+}
+
+#[test]
+fn fcg_chr_switch_redraws_patterns() {
+    #[rustfmt::skip]
+    let program = [
+        0x78,             // E000: SEI
+        0x2C, 0x02, 0x20, // E001: BIT $2002   ; Wait for vblank
+        0x10, 0xFB,       // E004: BPL $E001
+        0xA9, 0x3F,       // E006: LDA #$3F
+        0x8D, 0x06, 0x20, // E008: STA $2006
+        0xA9, 0x00,       // E00B: LDA #$00
+        0x8D, 0x06, 0x20, // E00D: STA $2006   ; Background palette 0
+        0xA9, 0x0F,       // E010: LDA #$0F
+        0x8D, 0x07, 0x20, // E012: STA $2007
+        0x8D, 0x07, 0x20, // E015: STA $2007
+        0x8D, 0x07, 0x20, // E018: STA $2007
+        0xA9, 0x30,       // E01B: LDA #$30
+        0x8D, 0x07, 0x20, // E01D: STA $2007   ; Color 3 is white and the rest black
+        0xA9, 0x00,       // E020: LDA #$00
+        0x8D, 0x05, 0x20, // E022: STA $2005
+        0x8D, 0x05, 0x20, // E025: STA $2005
+        0x8D, 0x00, 0x20, // E028: STA $2000   ; Background patterns at $0000
+        0xA9, 0x0A,       // E02B: LDA #$0A
+        0x8D, 0x01, 0x20, // E02D: STA $2001   ; Show the background
+        0x4C, 0x30, 0xE0, // E030: JMP $E030
+    ];
+    // One 8KB bank of CHR ROM, where tile 0 of the first 1KB bank is blank and tile 0 of the second is solid color 3
+    let mut rom = build_rom(16, 4, &program, &[]);
+    rom[5] = 1;
+    let mut chr = vec![0u8; 0x2000];
+    chr[0x400..0x410].fill(0xFF);
+    rom.extend_from_slice(&chr);
+
+    // The nametable is still all tile 0, so the whole background is drawn from the CHR bank at $0000
+    let mut nes = run("fcg_chr_switch.nes", &rom, 3);
+    let mut frame = Frame::new();
+    nes.run_frame(&mut frame).unwrap();
+    let blank = frame.pixels[128 * 256 + 128];
+
+    // FCG boards take their registers at $6000-$7FFF, where CHR bank 0 is $6000
+    nes.bus_mut().cpu_write_byte(0x6000, 1).unwrap();
+    nes.run_frame(&mut frame).unwrap();
+    nes.run_frame(&mut frame).unwrap();
+    assert_ne!(frame.pixels[128 * 256 + 128], blank);
+}
+
+#[test]
+fn break_on_bank_switch() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x02,       // LDA #$02
+        0x8D, 0x00, 0x80, // STA $8000   ; Bank 2 at $8000
+        0x8D, 0x00, 0x80, // STA $8000   ; Bank 2 again, which isn't a switch
+        0xA9, 0x05,       // LDA #$05
+        0x8D, 0x00, 0xA0, // STA $A000   ; CHR bank 5 at $0000
+        0x4C, 0x0D, 0xE0, // JMP $E00D
+    ];
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bank_switch.nes");
+    std::fs::write(&path, build_rom(85, 8, &program, &[])).unwrap();
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.debugger.break_on_bank_switch = true;
+    let mut frame = Frame::new();
+
+    assert_eq!(
+        nes.run_frame(&mut frame),
+        Ok(RunResult::Break(BreakReason::BankSwitch {
+            memory: BankMemory::Prg,
+            window: 0,
+            old: 0,
+            new: 2,
+            address: 0x8000,
+        }))
+    );
+    assert_eq!(
+        nes.run_frame(&mut frame),
+        Ok(RunResult::Break(BreakReason::BankSwitch {
+            memory: BankMemory::Chr,
+            window: 0,
+            old: 0,
+            new: 5,
+            address: 0xA000,
+        }))
+    );
+    assert_eq!(nes.run_frame(&mut frame), Ok(RunResult::FrameComplete));
+}
@@ -0,0 +1,57 @@
+//! Runs nestest in its automated mode, which starts at $C000 and needs no PPU, and compares the trace log of every
+//! instruction with the log of a known good run. Any mismatch in the registers or cycle count points at the
+//! instruction which the CPU got wrong.
+//!
+//! The reference log is in the format of the canonical nestest log. Only the official opcodes are emulated, so
+//! the comparison stops at the first unofficial opcode, which the log marks with a `*`.
+
+use nesemu_core::{screen::Frame, NES};
+
+const ROM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes");
+const LOG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.log");
+
+#[test]
+fn nestest() {
+    let expected_log = std::fs::read_to_string(LOG_PATH).unwrap();
+    let expected: Vec<&str> = expected_log
+        .lines()
+        .take_while(|line| line.as_bytes().get(15) != Some(&b'*'))
+        .collect();
+
+    let trace_path =
+        std::env::temp_dir().join(format!("nesemu-nestest-{}.log", std::process::id()));
+    let mut nes = NES::new(ROM_PATH.to_string()).unwrap();
+    nes.cpu_mut().registers_mut().program_counter = 0xC000;
+    nes.set_trace_file(trace_path.to_str().unwrap());
+    let mut frame = Frame::new();
+    for line in &expected {
+        if let Err(error) = nes.step_instruction(&mut frame) {
+            panic!("CPU failed with error '{}' at:\n{}", error, line);
+        }
+    }
+    // The trace is only guaranteed to be written out once the NES is dropped
+    drop(nes);
+    let actual_log = std::fs::read_to_string(&trace_path).unwrap();
+    let _ = std::fs::remove_file(&trace_path);
+
+    let mut actual = actual_log.lines();
+    for (idx, expected) in expected.iter().enumerate() {
+        let actual = actual.next().unwrap_or_default();
+        assert_eq!(
+            normalize(actual),
+            normalize(expected),
+            "Trace differs from nestest.log on line {}",
+            idx + 1
+        );
+    }
+}
+
+/// Strips a log line down to what the trace records, since the trace leaves out the PPU position and its column
+/// widths may differ
+fn normalize(line: &str) -> String {
+    let line = match (line.find("PPU:"), line.find("CYC:")) {
+        (Some(ppu), Some(cyc)) => format!("{}{}", &line[..ppu], &line[cyc..]),
+        _ => line.to_string(),
+    };
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,50 @@
+//! Checks that OAM DMA copies from whichever page is written to $4014, not only those in CPU RAM, using a
+//! generated ROM
+
+mod common;
+
+use nesemu_core::{screen::Frame, NES};
+
+fn run_dma(page: u8) -> NES {
+    #[rustfmt::skip]
+    let mut program = vec![
+        0xA2, 0x00,       // C000: LDX #$00
+        0x8A,             // C002: TXA
+        0x9D, 0x00, 0x03, // C003: STA $0300,X
+        0xE8,             // C006: INX
+        0xD0, 0xF9,       // C007: BNE $C002
+        0xA9, page,       // C009: LDA #page
+        0x8D, 0x14, 0x40, // C00B: STA $4014
+        0x4C, 0x0E, 0xC0, // C00E: JMP $C00E
+    ];
+    // Sprite data in ROM at $C100, counting down
+    program.resize(0x100, 0);
+    program.extend((0..=0xFF).rev());
+    let path = common::nrom(&program, [0x0000, 0xC000, 0x0000]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.run_frame(&mut Frame::new()).unwrap();
+    nes
+}
+
+#[test]
+fn dma_from_ram() {
+    let nes = run_dma(0x03);
+    let expected: Vec<u8> = (0..=0xFF).collect();
+    assert_eq!(nes.bus().oam_ram.to_vec(), expected);
+}
+
+#[test]
+fn dma_from_mirrored_ram() {
+    // $0B00 mirrors $0300
+    let nes = run_dma(0x0B);
+    let expected: Vec<u8> = (0..=0xFF).collect();
+    assert_eq!(nes.bus().oam_ram.to_vec(), expected);
+}
+
+#[test]
+fn dma_from_rom() {
+    let nes = run_dma(0xC1);
+    let expected: Vec<u8> = (0..=0xFF).rev().collect();
+    assert_eq!(nes.bus().oam_ram.to_vec(), expected);
+}
@@ -0,0 +1,47 @@
+//! Checks that overclocking with extra scanlines gives the CPU more cycles each frame without changing how often
+//! the NMI fires, using a generated ROM that counts NMIs
+
+mod common;
+
+use nesemu_core::{screen::Frame, NES};
+
+const FRAMES: u64 = 5;
+
+/// Runs some frames with the given number of extra scanlines, returning the CPU cycles run and NMIs counted
+fn run(extra_scanlines: usize) -> (u64, u8) {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x80,       // C000: LDA #$80
+        0x8D, 0x00, 0x20, // C002: STA $2000   ; Enable NMI
+        0x4C, 0x05, 0xC0, // C005: JMP $C005
+        0xE6, 0x00,       // C008: INC $00     ; NMI handler
+        0x40,             // C00A: RTI
+    ];
+    let path = common::nrom(&program, [0xC008, 0xC000, 0xC000]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.ppu_mut().set_extra_scanlines(extra_scanlines);
+    let mut frame = Frame::new();
+    for _ in 0..FRAMES {
+        nes.run_frame(&mut frame).unwrap();
+    }
+    (nes.cycle_count(), nes.bus().cpu_ram()[0])
+}
+
+#[test]
+fn extra_scanlines_add_cpu_time() {
+    let (cycles, nmis) = run(0);
+    let (overclocked_cycles, overclocked_nmis) = run(20);
+    assert_eq!(nmis, overclocked_nmis);
+    assert_eq!(nmis as u64, FRAMES);
+
+    // Each scanline is 341 PPU dots, and the CPU runs once every 3 dots
+    let expected = FRAMES * 20 * 341 / 3;
+    let extra = overclocked_cycles - cycles;
+    assert!(
+        extra.abs_diff(expected) <= 7,
+        "Expected about {} extra cycles, got {}",
+        expected,
+        extra
+    );
+}
@@ -0,0 +1,34 @@
+//! Parses the settings of compatibility overrides
+
+use nesemu_core::{
+    controller::ControllerType,
+    overrides::{Mirroring, Overrides},
+    rom_db::Region,
+};
+
+#[test]
+fn settings() {
+    let overrides: Overrides =
+        "mapper=4 submapper=1 mirroring=vertical region=dendy controller=zapper"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        overrides,
+        Overrides {
+            mapper: Some(4),
+            submapper: Some(1),
+            mirroring: Some(Mirroring::Vertical),
+            region: Some(Region::Dendy),
+            controller: Some(ControllerType::Zapper),
+        }
+    );
+    assert_eq!("".parse::<Overrides>().unwrap(), Overrides::default());
+}
+
+#[test]
+fn invalid_settings() {
+    assert!("mirroring=diagonal".parse::<Overrides>().is_err());
+    assert!("mapper=x".parse::<Overrides>().is_err());
+    assert!("mapper".parse::<Overrides>().is_err());
+    assert!("speed=2".parse::<Overrides>().is_err());
+}
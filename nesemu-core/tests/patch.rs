@@ -0,0 +1,90 @@
+//! Applies small hand-built IPS and BPS patches, covering each kind of record and action in the two formats.
+
+use nesemu_core::patch;
+
+const ROM: &[u8] = b"ABCDEFGH";
+
+#[test]
+fn ips() {
+    let mut ips = b"PATCH".to_vec();
+    // Overwrite two bytes at offset 1
+    ips.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x02, b'x', b'y']);
+    // Fill three bytes at offset 6 with 'z', growing the ROM past its end
+    ips.extend_from_slice(&[0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x03, b'z']);
+    ips.extend_from_slice(b"EOF");
+    assert_eq!(patch::apply(ROM, &ips).unwrap(), b"AxyDEFzzz");
+
+    // Truncate the output after the end marker
+    ips.extend_from_slice(&[0x00, 0x00, 0x04]);
+    assert_eq!(patch::apply(ROM, &ips).unwrap(), b"AxyD");
+}
+
+#[test]
+fn ips_truncated() {
+    assert!(patch::apply(ROM, b"PATCH\x00\x00\x01\x00\x05xy").is_err());
+}
+
+#[test]
+fn bps() {
+    let mut bps = b"BPS1".to_vec();
+    // Source size, target size and an empty metadata block
+    bps.extend_from_slice(&[number(8), number(10), number(0)]);
+    // Source read of "AB"
+    bps.push(number(action(0, 2)));
+    // Target read of "xy"
+    bps.extend_from_slice(&[number(action(1, 2)), b'x', b'y']);
+    // Source copy of "FGH", from 5 bytes ahead of the start of the ROM
+    bps.extend_from_slice(&[number(action(2, 3)), number(5 << 1)]);
+    // Target copy of "ABx", from the start of the output
+    bps.extend_from_slice(&[number(action(3, 3)), number(0)]);
+    let target = b"ABxyFGHABx";
+    finish_bps(&mut bps, target);
+    assert_eq!(patch::apply(ROM, &bps).unwrap(), target);
+}
+
+#[test]
+fn bps_wrong_rom() {
+    let mut bps = b"BPS1".to_vec();
+    bps.extend_from_slice(&[number(8), number(8), number(0), number(action(0, 8))]);
+    finish_bps(&mut bps, ROM);
+    assert!(patch::apply(ROM, &bps).is_ok());
+    assert!(patch::apply(b"ABCDEFGX", &bps).is_err());
+}
+
+#[test]
+fn unknown_format() {
+    assert!(patch::apply(ROM, b"NOT A PATCH").is_err());
+}
+
+/// Encodes a BPS number, for numbers small enough to fit in a single byte
+fn number(value: usize) -> u8 {
+    assert!(value < 0x80);
+    value as u8 | 0x80
+}
+
+fn action(command: usize, length: usize) -> usize {
+    ((length - 1) << 2) | command
+}
+
+/// Appends the checksums of the source, target and patch
+fn finish_bps(bps: &mut Vec<u8>, target: &[u8]) {
+    bps.extend_from_slice(&crc32(ROM).to_le_bytes());
+    bps.extend_from_slice(&crc32(target).to_le_bytes());
+    let patch_crc = crc32(bps);
+    bps.extend_from_slice(&patch_crc.to_le_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
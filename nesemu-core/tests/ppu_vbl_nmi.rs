@@ -0,0 +1,51 @@
+//! Runs blargg's ppu_vbl_nmi ROMs, which check the timing of the VBLANK flag and NMI to a single PPU clock,
+//! along with the clock skipped on odd frames. The ROMs which print a table of results check it themselves,
+//! so every ROM reports a plain pass or fail.
+//!
+//! The suite's clear_time, nmi_timing, suppression and even_odd_frames ROMs aren't included here. Most of the
+//! ROMs that are included fail until the PPU is emulated in step with the CPU, so they are ignored for now.
+
+mod common;
+
+use common::run_blargg_test;
+
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/ppu_vbl_nmi");
+
+fn run_test(name: &str) {
+    run_blargg_test(&format!("{}/{}.nes", ROM_DIR, name));
+}
+
+#[test]
+fn vbl_basics() {
+    run_test("vbl_nmi_basics");
+}
+
+#[test]
+#[ignore = "the PPU catches up only after each CPU instruction, so isn't accurate to a single clock"]
+fn vbl_set_time() {
+    run_test("vbl_nmi_set_time");
+}
+
+#[test]
+#[ignore = "enabling NMI during VBLANK doesn't trigger one immediately"]
+fn nmi_control() {
+    run_test("vbl_nmi_control");
+}
+
+#[test]
+#[ignore = "the PPU catches up only after each CPU instruction, so isn't accurate to a single clock"]
+fn nmi_on_timing() {
+    run_test("vbl_nmi_on_timing");
+}
+
+#[test]
+#[ignore = "the PPU catches up only after each CPU instruction, so isn't accurate to a single clock"]
+fn nmi_off_timing() {
+    run_test("vbl_nmi_off_timing");
+}
+
+#[test]
+#[ignore = "the PPU catches up only after each CPU instruction, so isn't accurate to a single clock"]
+fn even_odd_timing() {
+    run_test("vbl_nmi_even_odd_timing");
+}
@@ -0,0 +1,93 @@
+//! Checks RAM map entries: validating names, reading typed values, using them in watch expressions and naming the
+//! addresses instructions access in the trace log
+
+use nesemu_core::{
+    expression::Expression,
+    ram_map::{RamEntry, RamMap, ValueType},
+    screen::Frame,
+    NES,
+};
+
+const ROM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes");
+
+fn entry(address: u16, name: &str, value_type: ValueType) -> RamEntry {
+    RamEntry {
+        address,
+        name: name.to_string(),
+        value_type,
+        description: String::new(),
+    }
+}
+
+#[test]
+fn validates_names() {
+    let mut map = RamMap::new();
+    assert!(map.add(entry(0x0300, "player_x", ValueType::U8)).is_ok());
+    assert!(map.add(entry(0x0301, "PLAYER_X", ValueType::U8)).is_err());
+    assert!(map.add(entry(0x0302, "1up", ValueType::U8)).is_err());
+    assert!(map.add(entry(0x0302, "lives left", ValueType::U8)).is_err());
+    assert!(map.add(entry(0x0302, "", ValueType::U8)).is_err());
+    assert_eq!(map.entries().len(), 1);
+}
+
+#[test]
+fn reads_typed_values() {
+    let mut nes = NES::new(ROM_PATH.to_string()).unwrap();
+    let bus = nes.bus_mut();
+    bus.cpu_write_byte(0x0300, 0xFE).unwrap();
+    bus.cpu_write_byte(0x0301, 0xFF).unwrap();
+    let map = &mut bus.ram_map;
+    map.add(entry(0x0300, "byte", ValueType::U8)).unwrap();
+    map.add(entry(0x0300, "signed_byte", ValueType::I8))
+        .unwrap();
+    map.add(entry(0x0300, "word", ValueType::U16)).unwrap();
+    map.add(entry(0x0300, "signed_word", ValueType::I16))
+        .unwrap();
+
+    let cases = [
+        ("byte", 0xFE),
+        ("signed_byte", -2),
+        ("word", 0xFFFE),
+        ("signed_word", -2),
+        ("Word + 2", 0x10000),
+    ];
+    for (text, expected) in cases {
+        let value = Expression::parse(text).and_then(|expression| expression.evaluate(&mut nes));
+        assert_eq!(value, Ok(expected), "{}", text);
+    }
+    let value = Expression::parse("missing").and_then(|expression| expression.evaluate(&mut nes));
+    assert!(value.is_err());
+
+    let map = &nes.bus().ram_map;
+    assert_eq!(
+        map.entry_at(0x0301).map(|entry| entry.name.as_str()),
+        Some("word")
+    );
+    assert!(map.entry_at(0x0302).is_none());
+}
+
+#[test]
+fn annotates_trace() {
+    let trace_path =
+        std::env::temp_dir().join(format!("nesemu-ram-map-{}.log", std::process::id()));
+    let mut nes = NES::new(ROM_PATH.to_string()).unwrap();
+    nes.cpu_mut().registers_mut().program_counter = 0xC000;
+    nes.bus_mut()
+        .ram_map
+        .add(entry(0x0000, "scratch", ValueType::U8))
+        .unwrap();
+    nes.set_trace_file(trace_path.to_str().unwrap());
+    let mut frame = Frame::new();
+    for _ in 0..4 {
+        nes.step_instruction(&mut frame).unwrap();
+    }
+    drop(nes);
+    let log = std::fs::read_to_string(&trace_path).unwrap();
+    let _ = std::fs::remove_file(&trace_path);
+
+    let lines: Vec<&str> = log.lines().collect();
+    // JMP $C5F5, LDX #$00, STX $00, STX $10
+    assert!(!lines[1].contains(';'));
+    assert!(lines[2].contains("STX $00 = 00") && lines[2].ends_with("  ; scratch"));
+    assert!(!lines[3].contains(';'));
+}
@@ -0,0 +1,79 @@
+//! Checks that register breakpoints catch instructions reading and writing PPU registers through their mirrors,
+//! but not the writes OAM DMA makes, using a generated ROM
+
+mod common;
+
+use nesemu_core::{
+    debugger::{Access, BreakReason},
+    screen::Frame,
+    RunResult, NES,
+};
+
+#[test]
+fn breaks_on_register_accesses() {
+    #[rustfmt::skip]
+    let program = [
+        0xA9, 0x1E,       // C000: LDA #$1E
+        0x8D, 0x01, 0x20, // C002: STA $2001
+        0xAD, 0x02, 0x20, // C005: LDA $2002
+        0x8D, 0xF9, 0x3F, // C008: STA $3FF9, a mirror of $2001
+        0xA9, 0x02,       // C00B: LDA #$02
+        0x8D, 0x14, 0x40, // C00D: STA $4014, starting OAM DMA from page $02
+        0x4C, 0x10, 0xC0, // C010: JMP $C010
+    ];
+    let path = common::nrom(&program, [0xC010, 0xC000, 0xC010]);
+
+    let mut nes = NES::new(path.to_string_lossy().into_owned()).unwrap();
+    nes.debugger.add_register_breakpoint(0x2001, Access::Write);
+    // Set through a mirror, but caught as $2002
+    nes.debugger.add_register_breakpoint(0x3FFA, Access::Read);
+    nes.debugger.add_register_breakpoint(0x2004, Access::Write);
+    nes.debugger.add_register_breakpoint(0x4014, Access::Write);
+    assert_eq!(
+        nes.debugger
+            .register_breakpoints()
+            .copied()
+            .collect::<Vec<_>>(),
+        [
+            (0x2001, Access::Write),
+            (0x2002, Access::Read),
+            (0x2004, Access::Write),
+            (0x4014, Access::Write)
+        ]
+    );
+
+    let mut frame = Frame::new();
+    let mut breaks = Vec::new();
+    while let RunResult::Break(reason) = nes.run_frame(&mut frame).unwrap() {
+        breaks.push((reason, nes.cpu().program_counter()));
+    }
+    let hit = |access, address, from| BreakReason::RegisterAccess {
+        access,
+        address,
+        from,
+    };
+    // Each break comes after the instruction that made the access has finished
+    assert_eq!(
+        breaks,
+        [
+            (hit(Access::Write, 0x2001, 0xC002), 0xC005),
+            (hit(Access::Read, 0x2002, 0xC005), 0xC008),
+            (hit(Access::Write, 0x2001, 0xC008), 0xC00B),
+            (hit(Access::Write, 0x4014, 0xC00D), 0xC010),
+        ]
+    );
+    assert_eq!(
+        breaks[0].0.to_string(),
+        "Write to $2001 by the instruction at $C002"
+    );
+
+    // Once removed, the writes no longer break
+    nes.power_cycle().unwrap();
+    nes.debugger
+        .remove_register_breakpoint(0x2001, Access::Write);
+    nes.debugger
+        .remove_register_breakpoint(0x2002, Access::Read);
+    nes.debugger
+        .remove_register_breakpoint(0x4014, Access::Write);
+    assert_eq!(nes.run_frame(&mut frame).unwrap(), RunResult::FrameComplete);
+}
@@ -0,0 +1,25 @@
+//! Identifies ROMs through the bundled database
+
+use nesemu_core::{
+    rom_db::{self, DumpStatus, RomHash},
+    NES,
+};
+
+#[test]
+fn known_rom() {
+    let nes =
+        NES::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes").into()).unwrap();
+    let info = nes.rom_info();
+    assert_eq!(info.hash.crc32, 0x158b0388);
+    assert_eq!(
+        info.hash.sha1_hex(),
+        "4131307f0f69f2a5c54b7d438328c5b2a5ed0820"
+    );
+    assert_eq!(info.title(), Some("nestest"));
+    assert_eq!(info.dump_status(), DumpStatus::Verified);
+}
+
+#[test]
+fn unknown_rom() {
+    assert_eq!(rom_db::lookup(&RomHash::new(&[0; 16384], &[0; 8192])), None);
+}
@@ -0,0 +1,202 @@
+//! Screenshot regression tests, which run ROMs for a number of frames with scripted input and compare the last
+//! frame against a reference PNG. Unlike the test ROMs, these exercise whole games the way they're played.
+//!
+//! Cases are listed in `screenshots/cases.txt`, with one case per line giving its name, the ROM to run and the
+//! number of frames to run it for. The reference image is `screenshots/<name>.png`. The input for the case is
+//! read from `screenshots/<name>.movie` if it exists. It's an input movie as recorded by the GUI, holding the
+//! state of both controllers for each frame, and any frames past its end have no buttons held.
+//!
+//! ROMs are looked for in `tests/roms` first. Games can't be redistributed, so they are looked for in the
+//! directory named by the `NESEMU_TEST_ROMS` environment variable instead, and their cases are skipped when the
+//! ROM can't be found.
+//!
+//! Running with `NESEMU_BLESS=1` writes the frames as the new reference images rather than comparing them. When
+//! a frame doesn't match, it's saved to the target directory so it can be compared with the reference.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use nesemu_core::{
+    controller::InputEvent,
+    screen::{Color, Frame, SCREEN_HEIGHT, SCREEN_WIDTH},
+    NES,
+};
+
+const CASE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/screenshots");
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms");
+const OUTPUT_DIR: &str = env!("CARGO_TARGET_TMPDIR");
+
+/// How far apart a color channel can be before the pixel counts as different, allowing for small changes to
+/// the palette
+const CHANNEL_TOLERANCE: u8 = 8;
+/// How many pixels can differ before the frame counts as different
+const PIXEL_TOLERANCE: usize = 16;
+
+struct Case {
+    name: String,
+    rom: String,
+    frames: usize,
+}
+
+#[test]
+fn screenshots() {
+    let bless = std::env::var_os("NESEMU_BLESS").is_some();
+    let mut failures = Vec::new();
+    for case in read_cases().unwrap() {
+        let Some(rom_path) = find_rom(&case.rom) else {
+            eprintln!("Skipping {}, since {} wasn't found", case.name, case.rom);
+            continue;
+        };
+        let frame = run_case(&case, &rom_path);
+        let reference_path = Path::new(CASE_DIR).join(format!("{}.png", case.name));
+        if bless {
+            write_png(&reference_path, &frame).unwrap();
+            continue;
+        }
+
+        let reference = read_png(&reference_path).unwrap();
+        let different = frame
+            .pixels
+            .iter()
+            .zip(&reference.pixels)
+            .filter(|(pixel, reference)| !similar(pixel, reference))
+            .count();
+        if different > PIXEL_TOLERANCE {
+            let actual_path = Path::new(OUTPUT_DIR).join(format!("{}.png", case.name));
+            write_png(&actual_path, &frame).unwrap();
+            failures.push(format!(
+                "{}: {} pixels differ, saved the frame to {}",
+                case.name,
+                different,
+                actual_path.display()
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "Frames differ from the reference:\n{}",
+        failures.join("\n")
+    );
+}
+
+fn run_case(case: &Case, rom_path: &Path) -> Frame {
+    let movie_path = Path::new(CASE_DIR).join(format!("{}.movie", case.name));
+    let inputs = if movie_path.exists() {
+        read_movie(&movie_path).unwrap()
+    } else {
+        Vec::new()
+    };
+
+    let mut nes = NES::new(rom_path.to_string_lossy().into_owned()).unwrap();
+    let mut frame = Frame::new();
+    for idx in 0..case.frames {
+        let [first, second] = inputs.get(idx).copied().unwrap_or_default();
+        let bus = nes.bus_mut();
+        bus.controller
+            .set_state_from_window(InputEvent { input_state: first });
+        bus.controller_two.set_state_from_window(InputEvent {
+            input_state: second,
+        });
+        if let Err(error) = nes.run_frame(&mut frame) {
+            panic!(
+                "{} failed on frame {} with error '{}'",
+                case.name, idx, error
+            );
+        }
+    }
+    frame
+}
+
+fn read_cases() -> Result<Vec<Case>, Error> {
+    parse_lines(&Path::new(CASE_DIR).join("cases.txt"), |line| {
+        let mut fields = line.split_whitespace();
+        match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(name), Some(rom), Some(frames), None) => Some(Case {
+                name: name.to_string(),
+                rom: rom.to_string(),
+                frames: frames.parse().ok()?,
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// Reads a movie in the GUI's format, which has the state of both controllers as hex bytes on each line
+fn read_movie(path: &Path) -> Result<Vec<[u8; 2]>, Error> {
+    parse_lines(path, |line| {
+        let mut bytes = line
+            .split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16).ok());
+        match (bytes.next(), bytes.next(), bytes.next()) {
+            (Some(Some(first)), Some(Some(second)), None) => Some([first, second]),
+            _ => None,
+        }
+    })
+}
+
+/// Parses every line of a file other than blank lines and comments
+fn parse_lines<T>(path: &Path, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| {
+            parse(line).ok_or_else(|| {
+                let message = format!("{}:{}: invalid line '{}'", path.display(), idx + 1, line);
+                Error::new(ErrorKind::InvalidData, message)
+            })
+        })
+        .collect()
+}
+
+fn find_rom(rom: &str) -> Option<PathBuf> {
+    let bundled = Path::new(ROM_DIR).join(rom);
+    if bundled.exists() {
+        return Some(bundled);
+    }
+    let path = Path::new(&std::env::var_os("NESEMU_TEST_ROMS")?).join(rom);
+    path.exists().then_some(path)
+}
+
+fn similar(pixel: &Color, reference: &Color) -> bool {
+    pixel.r.abs_diff(reference.r) <= CHANNEL_TOLERANCE
+        && pixel.g.abs_diff(reference.g) <= CHANNEL_TOLERANCE
+        && pixel.b.abs_diff(reference.b) <= CHANNEL_TOLERANCE
+}
+
+fn read_png(path: &Path) -> Result<Frame, Error> {
+    let mut reader = png::Decoder::new(BufReader::new(File::open(path)?)).read_info()?;
+    let mut data = vec![0; reader.output_buffer_size().unwrap_or_default()];
+    let info = reader.next_frame(&mut data)?;
+    if (info.width as usize, info.height as usize) != (SCREEN_WIDTH, SCREEN_HEIGHT)
+        || info.color_type != png::ColorType::Rgb
+        || info.bit_depth != png::BitDepth::Eight
+    {
+        let message = format!("{} isn't an 8 bit RGB image of the screen", path.display());
+        return Err(Error::new(ErrorKind::InvalidData, message));
+    }
+
+    let mut frame = Frame::new();
+    for (pixel, rgb) in frame.pixels.iter_mut().zip(data.chunks(3)) {
+        *pixel = Color::from_rgb(rgb[0], rgb[1], rgb[2]);
+    }
+    Ok(frame)
+}
+
+fn write_png(path: &Path, frame: &Frame) -> Result<(), Error> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = frame
+        .pixels
+        .iter()
+        .flat_map(|pixel| [pixel.r, pixel.g, pixel.b])
+        .collect();
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Checks that memory snapshots find the bytes that changed, and survive being saved to a file and read back
+
+use std::path::PathBuf;
+
+use nesemu_core::{
+    snapshot::{Difference, MemoryRegion, MemorySnapshot},
+    NES,
+};
+
+const ROM_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/nestest.nes");
+
+#[test]
+fn diffs_snapshots() {
+    let mut nes = NES::new(ROM_PATH.to_string()).unwrap();
+    let before = MemorySnapshot::capture(nes.bus());
+    let bus = nes.bus_mut();
+    bus.cpu_write_byte(0x0042, 3).unwrap();
+    bus.oam_ram[4] = 0x80;
+    // Write 7 to $2000 through PPUADDR and PPUDATA
+    bus.cpu_write_byte(0x2006, 0x20).unwrap();
+    bus.cpu_write_byte(0x2006, 0x00).unwrap();
+    bus.cpu_write_byte(0x2007, 7).unwrap();
+    let after = MemorySnapshot::capture(nes.bus());
+
+    let difference = |region, offset, old, new| Difference {
+        region,
+        offset,
+        old,
+        new,
+    };
+    assert_eq!(
+        before.diff(&after),
+        [
+            difference(MemoryRegion::CpuRam, 0x42, 0, 3),
+            difference(MemoryRegion::PpuRam, 0, 0, 7),
+            difference(MemoryRegion::Oam, 4, 0, 0x80),
+        ]
+    );
+    assert!(after.diff(&after).is_empty());
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("after.snap");
+    after.save(&path).unwrap();
+    assert_eq!(MemorySnapshot::load(&path).unwrap(), after);
+    std::fs::write(&path, [0; 16]).unwrap();
+    assert!(MemorySnapshot::load(&path).is_err());
+}
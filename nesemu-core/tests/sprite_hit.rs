@@ -0,0 +1,81 @@
+//! Runs blargg's sprite 0 hit ROMs, which check when the hit flag is set and cleared, including left column
+//! clipping, flipping, 8x16 sprites and the edges of the screen. The failure codes of each ROM are listed in
+//! `roms/sprite_hit/readme.txt`.
+//!
+//! The timing ROMs are from an older version of the suite, which reports its result differently. Most ROMs
+//! still fail and are ignored, with the first failure they report as the reason.
+
+mod common;
+
+use common::{run_blargg_test, run_legacy_blargg_test};
+
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/sprite_hit");
+
+fn rom_path(name: &str) -> String {
+    format!("{}/spr_hit_{}.nes", ROM_DIR, name)
+}
+
+#[test]
+#[ignore = "hits aren't missed when background rendering is off"]
+fn basics() {
+    run_blargg_test(&rom_path("basics"));
+}
+
+#[test]
+#[ignore = "sprites don't hit the top of a background tile"]
+fn alignment() {
+    run_blargg_test(&rom_path("alignment"));
+}
+
+#[test]
+#[ignore = "the lower-right pixel of a sprite doesn't hit"]
+fn corners() {
+    run_blargg_test(&rom_path("corners"));
+}
+
+#[test]
+#[ignore = "hits ignore horizontal flipping"]
+fn flip() {
+    run_blargg_test(&rom_path("flip"));
+}
+
+#[test]
+#[ignore = "hits aren't missed under left-edge clipping"]
+fn left_clip() {
+    run_blargg_test(&rom_path("left_clip"));
+}
+
+#[test]
+fn right_edge() {
+    run_blargg_test(&rom_path("right_edge"));
+}
+
+#[test]
+#[ignore = "sprites with Y >= 239 still hit"]
+fn screen_bottom() {
+    run_blargg_test(&rom_path("screen_bottom"));
+}
+
+#[test]
+#[ignore = "8x16 sprites aren't emulated"]
+fn double_height() {
+    run_blargg_test(&rom_path("double_height"));
+}
+
+#[test]
+#[ignore = "hits are only found once the scanline is drawn, not at the exact dot"]
+fn timing_basics() {
+    run_legacy_blargg_test(&rom_path("timing_basics"));
+}
+
+#[test]
+#[ignore = "hits are only found once the scanline is drawn, not at the exact dot"]
+fn timing_order() {
+    run_legacy_blargg_test(&rom_path("timing_order"));
+}
+
+#[test]
+#[ignore = "hits are only found once the scanline is drawn, not at the exact dot"]
+fn edge_timing() {
+    run_legacy_blargg_test(&rom_path("edge_timing"));
+}
@@ -0,0 +1,45 @@
+//! Runs blargg's sprite overflow ROMs, which check when the overflow flag is set, including the hardware bug
+//! that makes its search misread sprite data. The failure codes of each ROM are listed in
+//! `roms/sprite_overflow/readme.txt`.
+//!
+//! The flag isn't emulated yet, so every ROM is ignored for now.
+
+mod common;
+
+use common::run_legacy_blargg_test;
+
+const ROM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/roms/sprite_overflow");
+
+fn run_test(name: &str) {
+    run_legacy_blargg_test(&format!("{}/spr_overflow_{}.nes", ROM_DIR, name));
+}
+
+#[test]
+#[ignore = "the sprite overflow flag isn't emulated"]
+fn basics() {
+    run_test("basics");
+}
+
+#[test]
+#[ignore = "the sprite overflow flag isn't emulated"]
+fn details() {
+    run_test("details");
+}
+
+#[test]
+#[ignore = "the sprite overflow flag isn't emulated"]
+fn timing() {
+    run_test("timing");
+}
+
+#[test]
+#[ignore = "the sprite overflow flag isn't emulated"]
+fn obscure() {
+    run_test("obscure");
+}
+
+#[test]
+#[ignore = "the sprite overflow flag isn't emulated"]
+fn emulator() {
+    run_test("emulator");
+}
@@ -0,0 +1,58 @@
+//! Aims the Zapper at a generated ROM that fills the screen with white, checking that the game sees light only
+//! while the Zapper is pointed at the screen
+
+mod common;
+
+use nesemu_core::{screen::Frame, NES};
+
+fn rom() -> Vec<u8> {
+    #[rustfmt::skip]
+    let program = [
+        0x2C, 0x02, 0x20, // C000: BIT $2002   ; Wait for the PPU to warm up
+        0x10, 0xFB,       // C003: BPL $C000
+        0x2C, 0x02, 0x20, // C005: BIT $2002
+        0x10, 0xFB,       // C008: BPL $C005
+        0xA9, 0x3F,       // C00A: LDA #$3F
+        0x8D, 0x06, 0x20, // C00C: STA $2006
+        0xA9, 0x00,       // C00F: LDA #$00
+        0x8D, 0x06, 0x20, // C011: STA $2006
+        0xA9, 0x30,       // C014: LDA #$30
+        0x8D, 0x07, 0x20, // C016: STA $2007   ; The backdrop is white
+        0xA9, 0x0A,       // C019: LDA #$0A
+        0x8D, 0x01, 0x20, // C01B: STA $2001   ; Show the background
+        0xAD, 0x17, 0x40, // C01E: LDA $4017
+        0x29, 0x08,       // C021: AND #$08
+        0xD0, 0xF9,       // C023: BNE $C01E   ; Bit 3 is clear while light is seen
+        0xA9, 0x01,       // C025: LDA #$01
+        0x85, 0x10,       // C027: STA $10
+        0x4C, 0x1E, 0xC0, // C029: JMP $C01E
+    ];
+    common::nrom_image(&program, [0xC000, 0xC000, 0xC000])
+}
+
+#[test]
+fn senses_light_where_aimed() {
+    let mut nes = NES::from_bytes(rom()).unwrap();
+    // Only games listed in the compatibility overrides get a Zapper plugged in by default
+    assert!(!nes.bus().zapper.connected);
+    nes.bus_mut().zapper.connected = true;
+    let mut frame = Frame::new();
+    for _ in 0..3 {
+        nes.run_frame(&mut frame).unwrap();
+    }
+    assert_eq!(nes.bus().cpu_ram()[0x10], 0);
+
+    nes.bus_mut().zapper.aim = Some((128, 120));
+    nes.run_frame(&mut frame).unwrap();
+    assert_eq!(nes.bus().cpu_ram()[0x10], 1);
+}
+
+#[test]
+fn reports_trigger() {
+    let mut nes = NES::from_bytes(rom()).unwrap();
+    nes.bus_mut().zapper.connected = true;
+    let bus = nes.bus_mut();
+    assert_eq!(bus.cpu_read_byte(0x4017).unwrap() & 0x18, 0x08);
+    bus.zapper.trigger = true;
+    assert_eq!(bus.cpu_read_byte(0x4017).unwrap() & 0x18, 0x18);
+}
@@ -0,0 +1,413 @@
+//! Evaluates RetroAchievements trigger conditions against the console's memory once per frame.
+//!
+//! Triggers are written in the rcheevos condition syntax, for example `0xH0010=5_d0xH0011<0xH0011.10.`. A
+//! trigger is made up of a core group of conditions, optionally followed by alternate groups separated by `S`.
+//! It fires once every condition of the core group holds along with every condition of at least one
+//! alternate group. This supports the commonly used subset of the syntax:
+//!
+//! * Memory sizes: bits (`M`-`T`), nibbles (`L`, `U`), 8 bits (`H`), 16 bits (no size or a space), 24 bits
+//!   (`W`), 32 bits (`X`) and bit counts (`K`)
+//! * Current values, along with delta (`d`, the value on the previous frame) and prior (`p`, the value before
+//!   it last changed) values
+//! * Decimal and `h` prefixed hex constants
+//! * Hit count targets (`.N.`), and the reset (`R:`) and pause (`P:`) flags
+//!
+//! Addresses are CPU addresses, matching the memory map RetroAchievements uses for the NES.
+
+/// One value read from memory, tracked across frames so that delta and prior values are available
+struct MemRef {
+    address: usize,
+    size: Size,
+    value: u32,
+    delta: u32,
+    prior: u32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Size {
+    Bit(u8),
+    Lower4,
+    Upper4,
+    Eight,
+    Sixteen,
+    TwentyFour,
+    ThirtyTwo,
+    BitCount,
+}
+
+impl MemRef {
+    fn update(&mut self, read: &mut impl FnMut(usize) -> u8) {
+        let mut read_bytes = |count: usize| {
+            (0..count).fold(0, |value, idx| {
+                value | ((read(self.address + idx) as u32) << (idx * 8))
+            })
+        };
+        let value = match self.size {
+            Size::Bit(bit) => (read_bytes(1) >> bit) & 1,
+            Size::Lower4 => read_bytes(1) & 0x0F,
+            Size::Upper4 => read_bytes(1) >> 4,
+            Size::Eight => read_bytes(1),
+            Size::Sixteen => read_bytes(2),
+            Size::TwentyFour => read_bytes(3),
+            Size::ThirtyTwo => read_bytes(4),
+            Size::BitCount => read_bytes(1).count_ones(),
+        };
+        self.delta = self.value;
+        self.value = value;
+        if self.value != self.delta {
+            self.prior = self.delta;
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Constant(u32),
+    Value(usize),
+    Delta(usize),
+    Prior(usize),
+}
+
+impl Operand {
+    fn get(&self, memrefs: &[MemRef]) -> u32 {
+        match *self {
+            Operand::Constant(value) => value,
+            Operand::Value(idx) => memrefs[idx].value,
+            Operand::Delta(idx) => memrefs[idx].delta,
+            Operand::Prior(idx) => memrefs[idx].prior,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Flag {
+    None,
+    Reset,
+    Pause,
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+struct Condition {
+    flag: Flag,
+    left: Operand,
+    comparison: Comparison,
+    right: Operand,
+    /// How many frames the condition must have held for before it counts as true, or 0 if it only has to hold
+    /// on the current frame
+    target_hits: u32,
+    hits: u32,
+}
+
+impl Condition {
+    fn holds(&self, memrefs: &[MemRef]) -> bool {
+        let (left, right) = (self.left.get(memrefs), self.right.get(memrefs));
+        match self.comparison {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterEqual => left >= right,
+        }
+    }
+}
+
+struct Group {
+    conditions: Vec<Condition>,
+}
+
+impl Group {
+    /// Evaluates the group for this frame, returning whether it holds and whether any of its reset conditions
+    /// were met. A paused group doesn't hold, and doesn't count hits or check its resets
+    fn evaluate(&mut self, memrefs: &[MemRef]) -> (bool, bool) {
+        let paused = self
+            .conditions
+            .iter()
+            .any(|condition| condition.flag == Flag::Pause && condition.holds(memrefs));
+        if paused {
+            return (false, false);
+        }
+
+        let mut holds = true;
+        let mut reset = false;
+        for condition in &mut self.conditions {
+            let true_now = condition.holds(memrefs);
+            match condition.flag {
+                Flag::Pause => {}
+                Flag::Reset => reset |= true_now,
+                Flag::None if condition.target_hits == 0 => holds &= true_now,
+                Flag::None => {
+                    if true_now && condition.hits < condition.target_hits {
+                        condition.hits += 1;
+                    }
+                    holds &= condition.hits == condition.target_hits;
+                }
+            }
+        }
+        (holds, reset)
+    }
+
+    fn reset_hits(&mut self) {
+        for condition in &mut self.conditions {
+            condition.hits = 0;
+        }
+    }
+}
+
+struct Trigger {
+    id: u32,
+    /// The core group first, followed by any alternate groups
+    groups: Vec<Group>,
+    /// Triggers must be seen not firing before they are allowed to fire, so that an achievement whose
+    /// conditions already hold when it is loaded isn't unlocked immediately
+    primed: bool,
+}
+
+impl Trigger {
+    fn evaluate(&mut self, memrefs: &[MemRef]) -> bool {
+        let results: Vec<(bool, bool)> = self
+            .groups
+            .iter_mut()
+            .map(|group| group.evaluate(memrefs))
+            .collect();
+        let reset = results.iter().any(|(_, reset)| *reset);
+        let (core, alternates) = results.split_first().unwrap();
+        let fired = !reset
+            && core.0
+            && (alternates.is_empty() || alternates.iter().any(|(holds, _)| *holds));
+
+        if reset || (fired && !self.primed) {
+            self.groups.iter_mut().for_each(Group::reset_hits);
+        }
+        if !self.primed {
+            self.primed = !fired;
+            return false;
+        }
+        fired
+    }
+}
+
+/// The active achievements of the loaded game
+pub struct AchievementRuntime {
+    memrefs: Vec<MemRef>,
+    triggers: Vec<Trigger>,
+}
+
+impl AchievementRuntime {
+    pub fn new() -> Self {
+        Self {
+            memrefs: Vec::new(),
+            triggers: Vec::new(),
+        }
+    }
+
+    /// Adds an achievement with the given trigger, which is evaluated every frame until it fires
+    pub fn add(&mut self, id: u32, trigger: &str) -> Result<(), String> {
+        let mut parser = Parser {
+            text: trigger.as_bytes(),
+            pos: 0,
+            memrefs: &mut self.memrefs,
+        };
+        let groups = parser
+            .parse_trigger()
+            .map_err(|error| format!("{} at offset {} of '{}'", error, parser.pos, trigger))?;
+        self.triggers.push(Trigger {
+            id,
+            groups,
+            primed: false,
+        });
+        Ok(())
+    }
+
+    /// Evaluates every achievement at the end of a frame, returning the IDs of those that were unlocked.
+    /// Unlocked achievements are removed, and are never evaluated again
+    pub fn do_frame(&mut self, mut read: impl FnMut(usize) -> u8) -> Vec<u32> {
+        for memref in &mut self.memrefs {
+            memref.update(&mut read);
+        }
+        let mut unlocked = Vec::new();
+        let memrefs = &self.memrefs;
+        self.triggers.retain_mut(|trigger| {
+            let fired = trigger.evaluate(memrefs);
+            if fired {
+                unlocked.push(trigger.id);
+            }
+            !fired
+        });
+        unlocked
+    }
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+    /// Memory references are shared between every trigger, so that each value is only read once per frame
+    memrefs: &'a mut Vec<MemRef>,
+}
+
+impl Parser<'_> {
+    fn parse_trigger(&mut self) -> Result<Vec<Group>, &'static str> {
+        let mut groups = vec![Group {
+            conditions: Vec::new(),
+        }];
+        loop {
+            let condition = self.parse_condition()?;
+            groups.last_mut().unwrap().conditions.push(condition);
+            match self.next() {
+                Some(b'_') => {}
+                Some(b'S' | b's') => groups.push(Group {
+                    conditions: Vec::new(),
+                }),
+                Some(_) => return Err("Expected '_' or 'S'"),
+                None => return Ok(groups),
+            }
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, &'static str> {
+        let flag = if self.text.get(self.pos + 1) == Some(&b':') {
+            let flag = match self.next().map(|char| char.to_ascii_uppercase()) {
+                Some(b'R') => Flag::Reset,
+                Some(b'P') => Flag::Pause,
+                _ => return Err("Unsupported condition flag"),
+            };
+            self.pos += 1;
+            flag
+        } else {
+            Flag::None
+        };
+
+        let left = self.parse_operand()?;
+        let comparison = match self.next() {
+            Some(b'=') => {
+                self.eat(b'=');
+                Comparison::Equal
+            }
+            Some(b'!') if self.eat(b'=') => Comparison::NotEqual,
+            Some(b'<') if self.eat(b'=') => Comparison::LessEqual,
+            Some(b'<') => Comparison::Less,
+            Some(b'>') if self.eat(b'=') => Comparison::GreaterEqual,
+            Some(b'>') => Comparison::Greater,
+            _ => return Err("Expected a comparison"),
+        };
+        let right = self.parse_operand()?;
+
+        let target_hits = if self.eat(b'.') {
+            let hits = self.parse_number(10)?;
+            if !self.eat(b'.') {
+                return Err("Expected '.' after hit count");
+            }
+            hits
+        } else {
+            0
+        };
+
+        Ok(Condition {
+            flag,
+            left,
+            comparison,
+            right,
+            target_hits,
+            hits: 0,
+        })
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, &'static str> {
+        let operand: fn(usize) -> Operand = match self.peek().map(|char| char.to_ascii_lowercase())
+        {
+            Some(b'0') if matches!(self.text.get(self.pos + 1), Some(b'x' | b'X')) => {
+                Operand::Value
+            }
+            Some(b'0'..=b'9') => return self.parse_number(10).map(Operand::Constant),
+            Some(b'h') => {
+                self.pos += 1;
+                return self.parse_number(16).map(Operand::Constant);
+            }
+            Some(b'd') => {
+                self.pos += 1;
+                Operand::Delta
+            }
+            Some(b'p') => {
+                self.pos += 1;
+                Operand::Prior
+            }
+            _ => return Err("Expected a value"),
+        };
+        if !(self.eat(b'0') && (self.eat(b'x') || self.eat(b'X'))) {
+            return Err("Expected a memory address");
+        }
+
+        // 16 bit values can be written without a size, which is unambiguous since no size is a hex digit
+        let size = match self.peek().map(|char| char.to_ascii_uppercase()) {
+            Some(char @ b'M'..=b'T') => Some(Size::Bit(char - b'M')),
+            Some(b'L') => Some(Size::Lower4),
+            Some(b'U') => Some(Size::Upper4),
+            Some(b'H') => Some(Size::Eight),
+            Some(b' ') => Some(Size::Sixteen),
+            Some(b'W') => Some(Size::TwentyFour),
+            Some(b'X') => Some(Size::ThirtyTwo),
+            Some(b'K') => Some(Size::BitCount),
+            _ => None,
+        };
+        self.pos += size.is_some() as usize;
+        let size = size.unwrap_or(Size::Sixteen);
+        let address = self.parse_number(16)? as usize;
+
+        let idx = match self
+            .memrefs
+            .iter()
+            .position(|memref| memref.address == address && memref.size == size)
+        {
+            Some(idx) => idx,
+            None => {
+                self.memrefs.push(MemRef {
+                    address,
+                    size,
+                    value: 0,
+                    delta: 0,
+                    prior: 0,
+                });
+                self.memrefs.len() - 1
+            }
+        };
+        Ok(operand(idx))
+    }
+
+    fn parse_number(&mut self, radix: u32) -> Result<u32, &'static str> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|char| (char as char).is_digit(radix))
+        {
+            self.pos += 1;
+        }
+        let digits = std::str::from_utf8(&self.text[start..self.pos]).unwrap();
+        u32::from_str_radix(digits, radix).map_err(|_| "Expected a number")
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let char = self.peek();
+        self.pos += char.is_some() as usize;
+        char
+    }
+
+    /// Consumes the next character if it is the expected one
+    fn eat(&mut self, expected: u8) -> bool {
+        let matches = self.peek() == Some(expected);
+        self.pos += matches as usize;
+        matches
+    }
+}
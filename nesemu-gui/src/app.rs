@@ -0,0 +1,521 @@
+use std::{
+    io::Error,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use bitfield::BitMut;
+use eframe::egui::{Context, Image, Sense, TextureOptions, Vec2, ViewportCommand, Window};
+use nesemu_core::{
+    archive,
+    controller::InputEvent,
+    ppu::ppu_registers::PPUCTRL,
+    screen::{Frame, NullFrameBuffer, SCREEN_HEIGHT, SCREEN_WIDTH},
+    RunResult, NES,
+};
+use serde_json::{json, Value};
+use tock_registers::interfaces::Readable;
+
+use super::{
+    achievements::AchievementRuntime,
+    args::{load_nes, Args},
+    config::Config,
+    control::{Command, ControlServer},
+    discord::DiscordPresence,
+    emulator::{Emulator, Event, Message},
+    headless::write_image,
+    livesplit::Autosplitter,
+    movie::MovieWriter,
+    netplay::SessionChange,
+    overlay::{self, Drawing},
+    screen::Screen,
+    ui::UI,
+};
+
+/// How often the UI wakes up while emulation is halted and nothing is happening, so that a paused emulator
+/// doesn't keep a CPU core busy redrawing the same frame
+const PAUSED_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct App {
+    ctx: Context,
+    emulator: Emulator,
+    ui: UI,
+    screen: Screen,
+    netplay_connected: bool,
+    control: Option<ControlServer>,
+    /// Buttons held down by the control server, in addition to those held on the keyboard
+    control_buttons: u8,
+    /// Shapes drawn over the game image by the control server
+    control_drawing: Drawing,
+    /// The input last sent to the emulation thread
+    input: u8,
+    /// Whether the display's vertical sync is pacing emulation, as configured on startup
+    vsync: bool,
+    /// The frameskip last sent to the emulation thread
+    frameskip: u32,
+    rom_path: String,
+    /// When battery backed RAM was last written to the save file
+    last_save: Instant,
+    /// Present while Discord Rich Presence is enabled
+    discord: Option<DiscordPresence>,
+    /// The LiveSplit server address the current autosplitter sends to, while autosplitting is enabled
+    livesplit_address: Option<String>,
+}
+
+impl App {
+    pub fn new(args: Args, config: Config, ctx: &Context) -> Result<Self, Error> {
+        let vsync = config.video.vsync;
+        let movie = args
+            .record
+            .as_deref()
+            .map(MovieWriter::create)
+            .transpose()?;
+        let mut ui = UI::new(config);
+        // A ROM piped in on standard input can't be opened again, or hashed for achievements from its path
+        if !args.stdin {
+            ui.config.paths.add_recent_rom(&args.rom_path);
+            ui.achievements
+                .load_game(&args.rom_path, &ui.config.achievements);
+        }
+        let nes = args.load_nes()?;
+        set_window_title(ctx, &nes, &args.rom_path);
+        Ok(Self {
+            ctx: ctx.clone(),
+            emulator: Emulator::new(nes, ctx.clone(), vsync, movie),
+            rom_path: args.rom_path,
+            last_save: Instant::now(),
+            discord: None,
+            livesplit_address: None,
+            vsync,
+            frameskip: 0,
+            ui,
+            screen: Screen::new(ctx.clone()),
+            netplay_connected: false,
+            control: args
+                .control_port
+                .map(|port| ControlServer::start(port, ctx.clone()))
+                .transpose()?,
+            control_buttons: 0,
+            control_drawing: Drawing::new(),
+            input: 0,
+        })
+    }
+
+    pub fn handle_window_input(&mut self, ctx: &eframe::egui::Context) -> InputEvent {
+        let mut event = InputEvent { input_state: 0 };
+        let mut reset = false;
+        let mut power_cycle = false;
+        let keys = &self.ui.config.input;
+        // Pausing or resetting only one side of a netplay session would desync it
+        let local_only = !self.netplay_connected;
+        let emulator = &self.emulator;
+        ctx.input(|info| {
+            if local_only && info.key_pressed(keys.pause) {
+                emulator.set_halted(!emulator.halted());
+            }
+            if emulator.halted() && info.key_pressed(keys.frame_advance) {
+                emulator.send(Message::FrameAdvance);
+            }
+            if local_only && info.key_pressed(keys.reset) {
+                if info.modifiers.shift {
+                    power_cycle = true;
+                } else {
+                    reset = true;
+                }
+            }
+
+            event
+                .input_state
+                .set_bit(InputEvent::RIGHT as usize, info.key_down(keys.right));
+            event
+                .input_state
+                .set_bit(InputEvent::LEFT as usize, info.key_down(keys.left));
+            event
+                .input_state
+                .set_bit(InputEvent::DOWN as usize, info.key_down(keys.down));
+            event
+                .input_state
+                .set_bit(InputEvent::UP as usize, info.key_down(keys.up));
+            event
+                .input_state
+                .set_bit(InputEvent::START as usize, info.key_down(keys.start));
+            event
+                .input_state
+                .set_bit(InputEvent::SELECT as usize, info.key_down(keys.select));
+            event
+                .input_state
+                .set_bit(InputEvent::B as usize, info.key_down(keys.b));
+            event
+                .input_state
+                .set_bit(InputEvent::A as usize, info.key_down(keys.a));
+        });
+
+        if power_cycle {
+            let result = self.emulator.nes().power_cycle();
+            if let Err(error) = result {
+                self.emulator.set_halted(true);
+                self.show_error(format!("Power cycle failed with error: {}", error));
+            }
+        } else if reset {
+            let result = self.emulator.nes().soft_reset();
+            if let Err(error) = result {
+                self.emulator.set_halted(true);
+                self.show_error(format!("Reset failed with error: {}", error));
+            }
+        }
+        event
+    }
+
+    /// Logs an error and shows it to the user, who can then open a different ROM
+    fn show_error(&mut self, error: String) {
+        log::error!("{}", error);
+        self.ui.error = Some(error);
+    }
+
+    /// Switches to a different ROM, throwing away all of the state of the current game
+    ///
+    /// Without a patch given, a patch with the same name as the ROM is applied if there is one. The current game
+    /// keeps running if the new ROM fails to load
+    fn load_rom(&mut self, rom_path: String, patch_path: Option<String>) -> Result<(), Error> {
+        let nes = load_nes(rom_path.clone(), patch_path)?;
+        self.save_game();
+        if let Some(patch_path) = nes.patch_path() {
+            log::info!("Applied patch {}", patch_path);
+        }
+        set_window_title(&self.ctx, &nes, &rom_path);
+        *self.emulator.nes() = nes;
+        self.emulator.set_halted(false);
+        self.ui.config.paths.add_recent_rom(&rom_path);
+        self.emulator
+            .send(Message::Achievements(AchievementRuntime::new()));
+        self.ui
+            .achievements
+            .load_game(&rom_path, &self.ui.config.achievements);
+        if self.netplay_connected {
+            self.emulator.send(Message::StopNetplay);
+            self.netplay_connected = false;
+            self.ui
+                .netplay
+                .session_ended("Loaded a different ROM".to_string());
+        }
+        if let Some(discord) = &self.discord {
+            discord.set_game(&rom_path);
+        }
+        log::info!("Loaded {}", rom_path);
+        self.rom_path = rom_path;
+        self.load_autosplitter();
+        Ok(())
+    }
+
+    /// Writes the current game's battery backed RAM or EEPROM to its save file
+    fn save_game(&self) {
+        if let Err(err) = self.emulator.nes().save() {
+            log::warn!("Failed to write save file: {}", err);
+        }
+    }
+
+    /// Starts or stops Discord Rich Presence to match the settings
+    fn update_discord(&mut self) {
+        let config = &self.ui.config.discord;
+        let application_id =
+            Some(&config.application_id).filter(|id| config.enabled && !id.is_empty());
+        let current_id = self.discord.as_ref().map(|discord| &discord.application_id);
+        if application_id != current_id {
+            self.discord = application_id.map(|id| {
+                let discord = DiscordPresence::start(id.clone());
+                discord.set_game(&self.rom_path);
+                discord
+            });
+        }
+    }
+
+    /// Starts or stops autosplitting to match the settings
+    fn update_livesplit(&mut self) {
+        let config = &self.ui.config.livesplit;
+        let address = Some(&config.address).filter(|_| config.enabled);
+        if address != self.livesplit_address.as_ref() {
+            self.livesplit_address = address.cloned();
+            self.load_autosplitter();
+        }
+    }
+
+    /// Loads the autosplitter for the current ROM, if autosplitting is enabled and the game has a splits file
+    fn load_autosplitter(&mut self) {
+        let Some(address) = self.livesplit_address.clone() else {
+            self.emulator.send(Message::Autosplitter(None));
+            return;
+        };
+        let autosplitter = match Autosplitter::load(&self.rom_path, address) {
+            Ok(Some(autosplitter)) => Some(autosplitter),
+            Ok(None) => {
+                log::info!("No splits file found for {}", self.rom_path);
+                None
+            }
+            Err(error) => {
+                log::error!("Failed to load splits with error: {}", error);
+                None
+            }
+        };
+        self.emulator.send(Message::Autosplitter(autosplitter));
+    }
+
+    /// Executes a command received by the control server, returning the response to send back
+    fn execute_command(&mut self, command: Command) -> Result<Value, String> {
+        match command {
+            Command::LoadRom { path, patch } => self
+                .load_rom(path, patch)
+                .map_err(|error| error.to_string())?,
+            Command::Pause => self.emulator.set_halted(true),
+            Command::Resume => self.emulator.set_halted(false),
+            Command::Step { frames } => {
+                // Stepping happens right here rather than on the emulation thread, so that the reply can't be
+                // sent before the frames have run
+                self.emulator.set_halted(true);
+                let mut nes = self.emulator.nes();
+                let mut frame = Frame::new();
+                for _ in 0..frames {
+                    match nes.run_frame(&mut frame)? {
+                        RunResult::FrameComplete => self.screen.show_frame(&frame),
+                        RunResult::Break(reason) => {
+                            return Ok(json!({ "break": reason.to_string() }))
+                        }
+                    }
+                }
+            }
+            Command::ReadMemory { address, length } => {
+                let mut nes = self.emulator.nes();
+                let data = (address..address + length)
+                    .map(|address| nes.bus_mut().cpu_read_byte_no_modify(address))
+                    .collect::<Result<Vec<u8>, _>>()?;
+                return Ok(json!({ "data": data }));
+            }
+            Command::WriteMemory { address, data } => {
+                let mut nes = self.emulator.nes();
+                for (offset, value) in data.into_iter().enumerate() {
+                    nes.bus_mut().cpu_write_byte(address + offset, value)?;
+                }
+            }
+            Command::Screenshot { path } => {
+                let pixels = self.screen.image.pixels.iter();
+                write_image(&path, pixels.map(|pixel| [pixel.r(), pixel.g(), pixel.b()]))
+                    .map_err(|error| error.to_string())?;
+            }
+            Command::Press { buttons } => {
+                let mut state = InputEvent { input_state: 0 };
+                for button in buttons {
+                    let bit = match button.to_ascii_uppercase().as_str() {
+                        "A" => InputEvent::A,
+                        "B" => InputEvent::B,
+                        "SELECT" => InputEvent::SELECT,
+                        "START" => InputEvent::START,
+                        "UP" => InputEvent::UP,
+                        "DOWN" => InputEvent::DOWN,
+                        "LEFT" => InputEvent::LEFT,
+                        "RIGHT" => InputEvent::RIGHT,
+                        _ => return Err(format!("Unknown button {}", button)),
+                    };
+                    state.input_state.set_bit(bit as usize, true);
+                }
+                self.control_buttons = state.input_state;
+            }
+            Command::Draw { shapes } => {
+                for shape in shapes {
+                    self.control_drawing.add(shape);
+                }
+            }
+            Command::ClearDrawing => self.control_drawing.clear(),
+        }
+        Ok(json!({}))
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &eframe::egui::Context, _: &mut eframe::Frame) {
+        while let Some((command, reply)) = self.control.as_ref().and_then(ControlServer::poll) {
+            reply.send(self.execute_command(command));
+        }
+
+        self.screen.texture_options = if self.ui.config.video.smooth {
+            TextureOptions::LINEAR
+        } else {
+            TextureOptions::NEAREST
+        };
+        let mut input_event = self.handle_window_input(ctx);
+        input_event.input_state |= self.control_buttons;
+        if input_event.input_state != self.input {
+            self.input = input_event.input_state;
+            self.emulator.send(Message::Input(self.input));
+        }
+
+        while let Some(event) = self.emulator.poll_event() {
+            match event {
+                Event::Break(reason) => log::info!("{}", reason),
+                Event::Error(error) => {
+                    self.show_error(format!("Emulation failed with error: {}", error))
+                }
+                Event::AchievementUnlocked(id) => self
+                    .ui
+                    .achievements
+                    .unlock(id, &self.ui.config.achievements),
+                Event::RecordingFailed(error) => {
+                    log::error!("Stopped recording movie after error: {}", error)
+                }
+                Event::Timings(timings) => self.ui.profiler.add_emulation(timings),
+                Event::NetplayEnded(reason) => {
+                    self.netplay_connected = false;
+                    self.ui.netplay.session_ended(reason);
+                }
+            }
+        }
+        let profiling = self.ui.config.debug.profiler;
+        // Only the most recent frame is worth presenting if several completed since the last update
+        if let Some(frame) = self.emulator.take_frame() {
+            let start = Instant::now();
+            self.screen.show_frame(&frame);
+            if profiling {
+                self.ui.profiler.add_upload(start.elapsed());
+            }
+        }
+
+        let start = Instant::now();
+        let mut nes = self.emulator.nes();
+        self.ui.render(ctx, &mut nes, self.netplay_connected);
+        if profiling {
+            self.ui.profiler.add_ui(start.elapsed());
+        }
+        // Checked every update rather than only when the setting changes, since loading a ROM replaces the NES
+        if nes.profiler.enabled() != self.ui.config.debug.profiler {
+            nes.profiler.set_enabled(self.ui.config.debug.profiler);
+        }
+        nes.ppu_mut()
+            .set_extra_scanlines(self.ui.config.emulation.extra_scanlines as usize);
+        nes.bus_mut().log_mapper_writes = self.ui.config.debug.mapper_writes;
+        nes.debugger.break_on_nmi = self.ui.config.debug.break_on_nmi;
+        nes.debugger.break_on_irq = self.ui.config.debug.break_on_irq;
+        nes.debugger.break_on_brk = self.ui.config.debug.break_on_brk;
+        nes.debugger.break_on_bank_switch = self.ui.config.debug.break_on_bank_switch;
+        drop(nes);
+        match self.ui.netplay_change.take() {
+            Some(SessionChange::Started(netplay)) => {
+                // Resume first, since the emulation thread ends sessions that start out halted
+                self.netplay_connected = true;
+                self.emulator.set_halted(false);
+                self.emulator.send(Message::StartNetplay(netplay));
+            }
+            Some(SessionChange::Stopped) => {
+                self.netplay_connected = false;
+                self.emulator.send(Message::StopNetplay);
+            }
+            None => {}
+        }
+        if std::mem::take(&mut self.ui.step_requested) {
+            // Like the control server's Step command, stepping happens right here with emulation halted, so that
+            // the emulation thread can't run in the middle of it
+            self.emulator.set_halted(true);
+            let result = self.emulator.nes().step_source_line(&mut NullFrameBuffer);
+            if let Err(error) = result {
+                self.show_error(format!("Failed to step with error: {}", error));
+            }
+        }
+        if let Some(achievements) = self.ui.achievements_change.take() {
+            self.emulator.send(Message::Achievements(achievements));
+        }
+        if self.ui.config.video.frameskip != self.frameskip {
+            self.frameskip = self.ui.config.video.frameskip;
+            self.emulator.send(Message::Frameskip(self.frameskip));
+        }
+        self.update_discord();
+        self.update_livesplit();
+        let interval = self.ui.config.saves.interval;
+        if interval > 0 && self.last_save.elapsed() >= Duration::from_secs(interval.into()) {
+            // Only written if it changed, so a crash loses at most the last interval of progress
+            self.save_game();
+            self.last_save = Instant::now();
+        }
+        if let Some((rom_path, patch_path)) = self.ui.requested_rom.take() {
+            if let Err(error) = self.load_rom(rom_path, patch_path) {
+                self.show_error(format!("Failed to load ROM with error: {}", error));
+            }
+        }
+        let scale = self.ui.config.video.scale;
+        Window::new("Game").show(ctx, |ui| {
+            let size = Vec2::new(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32) * scale;
+            // Sensing clicks keeps them from dragging the window, so they can fire the Zapper
+            let image = Image::new(&self.screen.texture)
+                .fit_to_exact_size(size)
+                .sense(Sense::click());
+            let response = ui.add(image);
+            let painter = ui.painter_at(response.rect);
+            let mut nes = self.emulator.nes();
+            if self.ui.config.debug.tile_grid {
+                overlay::draw_tile_grid(&painter, response.rect, nes.ppu().scroll_origin());
+            }
+            if self.ui.config.debug.sprite_boxes {
+                let bus = nes.bus();
+                let tall_sprites = bus.ppu_get_registers().ppuctrl.is_set(PPUCTRL::SPRITE_SIZE);
+                let mut drawing = Drawing::new();
+                overlay::add_sprite_boxes(&mut drawing, &bus.oam_ram, tall_sprites);
+                drawing.draw(&painter, response.rect);
+            }
+            self.control_drawing.draw(&painter, response.rect);
+            if nes.bus().zapper.connected {
+                // The pointer aims the Zapper, shifted by the calibration offset, and the primary button fires it
+                let input = &self.ui.config.input;
+                let offset = Vec2::new(input.zapper_offset_x, input.zapper_offset_y);
+                let aim = response
+                    .hover_pos()
+                    .map(|pos| (pos - response.rect.min) / scale + offset)
+                    .filter(|aim| {
+                        (0.0..SCREEN_WIDTH as f32).contains(&aim.x)
+                            && (0.0..SCREEN_HEIGHT as f32).contains(&aim.y)
+                    });
+                if let Some(aim) = aim {
+                    overlay::draw_crosshair(&painter, response.rect, aim);
+                }
+                let trigger = response.hovered() && ui.input(|input| input.pointer.primary_down());
+                let zapper = &mut nes.bus_mut().zapper;
+                zapper.aim = aim.map(|aim| (aim.x as usize, aim.y as usize));
+                zapper.trigger = trigger;
+            }
+        });
+
+        if self.emulator.halted() {
+            // Nothing changes on screen while halted, and input and control commands trigger a repaint by
+            // themselves, so the UI only needs to wake up now and then to poll for netplay connections
+            ctx.request_repaint_after(PAUSED_REPAINT_INTERVAL);
+        } else if self.vsync {
+            // Each repaint waits for the display to refresh, so repainting continuously ticks the frame clock
+            self.emulator.send(Message::VSync);
+            ctx.request_repaint();
+        } else {
+            // The emulation thread requests a repaint whenever a frame completes, so this only keeps the UI
+            // polling for control commands and netplay connections in between. Pacing is left to the emulation
+            // thread, so the UI thread never blocks waiting for the next frame
+            ctx.request_repaint_after(Duration::from_secs_f64(NES::FRAME_TIME));
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_game();
+    }
+}
+
+/// Titles the window after the game, using its canonical title when the ROM is in the database
+fn set_window_title(ctx: &Context, nes: &NES, rom_path: &str) {
+    let title = match nes.rom_info().title() {
+        Some(title) => title.to_string(),
+        None if rom_path.is_empty() => "Standard input".to_string(),
+        None => {
+            // Leave out the archive when the ROM is inside one
+            let file = match rom_path.rsplit_once(archive::ENTRY_SEPARATOR) {
+                Some((_, entry)) if archive::is_archive(rom_path) => entry,
+                _ => rom_path,
+            };
+            Path::new(file)
+                .file_stem()
+                .map_or(file.into(), |name| name.to_string_lossy())
+                .into_owned()
+        }
+    };
+    ctx.send_viewport_cmd(ViewportCommand::Title(format!("NESEmu - {}", title)));
+}
@@ -0,0 +1,234 @@
+//! Command line argument parsing
+
+use std::{
+    io::{Error, Read},
+    path::Path,
+};
+
+use nesemu_core::{
+    debug_info::DebugInfo,
+    debugger::{is_register, Access, Interrupt},
+    patch, NES,
+};
+
+use super::ram_map;
+
+pub const USAGE: &str = "Usage: cargo run [options] <path/to/rom>
+
+Options:
+    --patch <file>        Apply an IPS or BPS patch to the ROM, rather than one with the same name as the ROM
+    --stdin               Read the ROM from standard input rather than from a file
+    --headless            Run without opening a window
+    --bench <n>           Run n frames as fast as possible without a window, then report the speed
+    --frames <n>          (headless) Stop after running n frames
+    --break <addr>        (headless) Stop when the CPU reaches the given hex address
+    --break-on <kinds>    (headless) Stop when the CPU takes any of a comma separated list of nmi, irq and brk
+    --break-read <addr>   (headless) Stop after an instruction reads the PPU, APU or I/O register at the given
+                          hex address. Can be given more than once
+    --break-write <addr>  (headless) Stop after an instruction writes the PPU, APU or I/O register at the given
+                          hex address. Can be given more than once
+    --inputs <movie>      (headless) Press the buttons recorded in an input movie or FCEUX .fm2 movie
+    --frame-out <file>    (headless) Write the final frame to a PNG image if the file ends in .png, and to a
+                          PPM image otherwise
+    --ram-out <file>      (headless) Write the contents of CPU RAM to a file
+    --trace-out <file>    (headless) Log every executed instruction to a file
+    --hash-out <file>     (headless) Write a hash of the image and console state at the end of every frame
+    --coverage-out <file> (headless) Write which PRG ROM bytes were executed, as a code/data log if the file
+                          ends in .cdl and as a text report otherwise
+    --apu-log-out <file>  (headless) Log every write to the APU's registers, with the frame and cycle it was made on
+    --audio-out <file>    (headless) Record the cartridge's expansion audio to a WAV file
+    --replay <movie>      Replay an input movie without a window, checking each frame against --baseline
+    --baseline <file>     (replay) Fail at the first frame whose hash differs from the given baseline
+    --baseline-out <file> (replay) Write the hash of every frame to a file, for use as a baseline
+    --compare <rom>       (replay) Replay the movie on a second ROM in lockstep, reporting the first frame where
+                          the two differ
+    --record <movie>      Record the inputs of every frame to an input movie
+    --gdb <port>          Run without a window, waiting for a GDB connection on the given port
+    --control <port>      Accept JSON commands from external tools on the given port";
+
+#[derive(Clone, Default)]
+pub struct Args {
+    pub rom_path: String,
+    pub patch: Option<String>,
+    /// Whether the ROM is piped in on standard input, in which case the ROM path is empty
+    pub stdin: bool,
+    pub headless: bool,
+    pub bench_frames: Option<usize>,
+    pub frames: Option<usize>,
+    pub break_address: Option<usize>,
+    pub break_on: Vec<Interrupt>,
+    pub break_registers: Vec<(usize, Access)>,
+    pub inputs: Option<String>,
+    pub frame_out: Option<String>,
+    pub ram_out: Option<String>,
+    pub trace_out: Option<String>,
+    pub hash_out: Option<String>,
+    pub coverage_out: Option<String>,
+    pub apu_log_out: Option<String>,
+    pub audio_out: Option<String>,
+    pub replay: Option<String>,
+    pub baseline: Option<String>,
+    pub baseline_out: Option<String>,
+    pub compare: Option<String>,
+    pub record: Option<String>,
+    pub gdb_port: Option<u16>,
+    pub control_port: Option<u16>,
+}
+
+impl Args {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut this = Args::default();
+        let mut rom_path = None;
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
+            match arg.as_str() {
+                "--patch" => this.patch = Some(value(&arg)?),
+                "--stdin" => this.stdin = true,
+                "--headless" => this.headless = true,
+                "--bench" => {
+                    this.bench_frames = Some(
+                        value(&arg)?
+                            .parse()
+                            .map_err(|_| "--bench expects a number".to_string())?,
+                    )
+                }
+                "--frames" => {
+                    this.frames = Some(
+                        value(&arg)?
+                            .parse()
+                            .map_err(|_| "--frames expects a number".to_string())?,
+                    )
+                }
+                "--break" => this.break_address = Some(parse_address(&value(&arg)?)?),
+                "--break-on" => this.break_on = parse_interrupts(&value(&arg)?)?,
+                "--break-read" => this
+                    .break_registers
+                    .push((parse_register(&value(&arg)?)?, Access::Read)),
+                "--break-write" => this
+                    .break_registers
+                    .push((parse_register(&value(&arg)?)?, Access::Write)),
+                "--inputs" => this.inputs = Some(value(&arg)?),
+                "--frame-out" => this.frame_out = Some(value(&arg)?),
+                "--ram-out" => this.ram_out = Some(value(&arg)?),
+                "--trace-out" => this.trace_out = Some(value(&arg)?),
+                "--hash-out" => this.hash_out = Some(value(&arg)?),
+                "--coverage-out" => this.coverage_out = Some(value(&arg)?),
+                "--apu-log-out" => this.apu_log_out = Some(value(&arg)?),
+                "--audio-out" => this.audio_out = Some(value(&arg)?),
+                "--replay" => this.replay = Some(value(&arg)?),
+                "--baseline" => this.baseline = Some(value(&arg)?),
+                "--baseline-out" => this.baseline_out = Some(value(&arg)?),
+                "--compare" => this.compare = Some(value(&arg)?),
+                "--record" => this.record = Some(value(&arg)?),
+                "--gdb" => this.gdb_port = Some(parse_port(&arg, &value(&arg)?)?),
+                "--control" => this.control_port = Some(parse_port(&arg, &value(&arg)?)?),
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => rom_path = Some(arg),
+            }
+        }
+
+        this.rom_path = match rom_path {
+            Some(_) if this.stdin => {
+                return Err("--stdin can't be used with a rom path".to_string())
+            }
+            Some(rom_path) => rom_path,
+            None if this.stdin => String::new(),
+            None => return Err("Missing rom path!".to_string()),
+        };
+        if this.headless
+            && this.frames.is_none()
+            && this.break_address.is_none()
+            && this.break_on.is_empty()
+            && this.break_registers.is_empty()
+        {
+            return Err(
+                "Headless mode requires --frames, --break, --break-on, --break-read or --break-write"
+                    .to_string(),
+            );
+        }
+        Ok(this)
+    }
+}
+
+impl Args {
+    /// Loads the ROM, with the patch given on the command line, or else one with the same name as the ROM
+    ///
+    /// With `--stdin`, this reads the ROM from standard input, so it can only be done once
+    pub fn load_nes(&self) -> Result<NES, Error> {
+        if !self.stdin {
+            return load_nes(self.rom_path.clone(), self.patch.clone());
+        }
+        let mut rom = Vec::new();
+        std::io::stdin().lock().read_to_end(&mut rom)?;
+        if let Some(patch_path) = &self.patch {
+            rom = patch::apply(&rom, &std::fs::read(patch_path)?)?;
+        }
+        NES::from_bytes(rom)
+    }
+}
+
+/// Loads a ROM with the given patch, or else one with the same name as the ROM, along with the RAM map and the
+/// debug info written by `ld65 --dbgfile` next to it if there are any
+pub fn load_nes(rom_path: String, patch_path: Option<String>) -> Result<NES, Error> {
+    let mut nes = match patch_path {
+        Some(patch_path) => NES::with_patch(rom_path, Some(patch_path)),
+        None => NES::new(rom_path),
+    }?;
+    if let Some(path) = ram_map::find_ram_map(nes.rom_path()) {
+        match ram_map::import(&path) {
+            Ok(map) => nes.bus_mut().ram_map = map,
+            Err(error) => log::warn!("Failed to import RAM map: {}", error),
+        }
+    }
+    let debug_info_path = Path::new(nes.rom_path()).with_extension("dbg");
+    if debug_info_path.exists() {
+        match DebugInfo::load(&debug_info_path) {
+            Ok(debug_info) => nes.debug_info = debug_info,
+            Err(error) => log::warn!("Failed to load debug info: {}", error),
+        }
+    }
+    Ok(nes)
+}
+
+/// Parses a CPU address written in hex, with or without a leading '$' or '0x'
+pub fn parse_address(text: &str) -> Result<usize, String> {
+    let digits = text.trim_start_matches('$').trim_start_matches("0x");
+    usize::from_str_radix(digits, 16)
+        .ok()
+        .filter(|address| *address <= 0xFFFF)
+        .ok_or_else(|| format!("Invalid address {}", text))
+}
+
+/// Parses the address of a PPU, APU or I/O register written in hex, with or without a leading '$' or '0x'
+pub fn parse_register(text: &str) -> Result<usize, String> {
+    let address = parse_address(text)?;
+    if is_register(address) {
+        Ok(address)
+    } else {
+        Err(format!("{} is not a PPU, APU or I/O register", text))
+    }
+}
+
+/// Parses a comma separated list of the kinds of interrupt
+fn parse_interrupts(text: &str) -> Result<Vec<Interrupt>, String> {
+    text.split(',')
+        .map(|kind| match kind.trim().to_ascii_lowercase().as_str() {
+            "nmi" => Ok(Interrupt::Nmi),
+            "irq" => Ok(Interrupt::Irq),
+            "brk" => Ok(Interrupt::Brk),
+            _ => Err(format!(
+                "Unknown interrupt {}, expected nmi, irq or brk",
+                kind
+            )),
+        })
+        .collect()
+}
+
+fn parse_port(name: &str, text: &str) -> Result<u16, String> {
+    text.parse()
+        .map_err(|_| format!("{} expects a port number", name))
+}
@@ -0,0 +1,52 @@
+//! Measures how fast the emulation core runs, without any of the overhead of the frontend. Nothing is drawn and
+//! frames are not paced, so the results can be compared between versions to catch performance regressions.
+
+use std::time::Instant;
+
+use nesemu_core::{screen::NullFrameBuffer, RunResult, NES};
+
+use super::{
+    args::Args,
+    headless::{EXIT_EMULATION_ERROR, EXIT_LOAD_ERROR, EXIT_SUCCESS},
+};
+
+/// Runs the given number of frames and prints the speed of emulation, returning the exit status code
+pub fn run(args: &Args, frames: usize) -> i32 {
+    let mut nes = match args.load_nes() {
+        Ok(nes) => nes,
+        Err(error) => {
+            eprintln!("Failed to initialize NES with error: {}", error);
+            return EXIT_LOAD_ERROR;
+        }
+    };
+
+    let start_cycles = nes.cycle_count();
+    let start = Instant::now();
+    let mut frames_run = 0;
+    while frames_run < frames {
+        match nes.run_frame(&mut NullFrameBuffer) {
+            Ok(RunResult::FrameComplete) => frames_run += 1,
+            // There are no breakpoints in benchmark mode
+            Ok(RunResult::Break(_)) => {}
+            Err(error) => {
+                eprintln!(
+                    "Emulation failed after {} frames with error: {}",
+                    frames_run, error
+                );
+                return EXIT_EMULATION_ERROR;
+            }
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let cycles = nes.cycle_count() - start_cycles;
+
+    let fps = frames_run as f64 / elapsed;
+    println!("Ran {} frames in {:.3}s", frames_run, elapsed);
+    println!(
+        "{:.1} frames/sec ({:.2}x real time)",
+        fps,
+        fps * NES::FRAME_TIME
+    );
+    println!("{:.0} CPU cycles/sec", cycles as f64 / elapsed);
+    EXIT_SUCCESS
+}
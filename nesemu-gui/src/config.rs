@@ -0,0 +1,275 @@
+//! User settings, persisted to `nesemu.toml` in the working directory. The file is loaded at startup and
+//! rewritten whenever a setting changes. Missing settings take their default values, so the file only needs to
+//! contain the settings that differ from them.
+
+use eframe::egui::Key;
+use serde::{Deserialize, Serialize};
+
+use super::livesplit;
+
+const CONFIG_PATH: &str = "nesemu.toml";
+const MAX_RECENT_ROMS: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct Config {
+    pub video: VideoConfig,
+    pub emulation: EmulationConfig,
+    pub audio: AudioConfig,
+    pub input: InputConfig,
+    pub paths: PathsConfig,
+    pub saves: SavesConfig,
+    pub debug: DebugConfig,
+    pub achievements: AchievementsConfig,
+    pub discord: DiscordConfig,
+    pub livesplit: LiveSplitConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct VideoConfig {
+    /// Size of the game image, as a multiple of the console's 256x240 resolution
+    pub scale: f32,
+    /// Whether to smooth the image when scaling it, rather than keeping pixels sharp
+    pub smooth: bool,
+    /// Whether to use the display's vertical sync as the frame clock, rather than a timer. This gives smoother
+    /// scrolling, but only runs at the right speed on 60Hz displays, and the game runs about 0.16% slow since
+    /// the NES refreshes at 60.0988Hz. Only takes effect on startup
+    // TODO: Offer syncing to the audio buffer instead, once audio is played back
+    pub vsync: bool,
+    /// How many frames to skip drawing after each frame that is drawn, for machines too slow to draw every
+    /// frame. Skipped frames are still emulated
+    pub frameskip: u32,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            scale: 2.0,
+            smooth: true,
+            vsync: false,
+            frameskip: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct EmulationConfig {
+    /// Extra scanlines added to the end of VBLANK each frame, overclocking the CPU to reduce slowdown in games
+    /// that lag. Zero runs at the console's real speed
+    pub extra_scanlines: u32,
+}
+
+/// Audio isn't emulated yet, so these settings are only stored for now
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub muted: bool,
+    pub volume: f32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            volume: 1.0,
+        }
+    }
+}
+
+/// The keyboard key bound to each controller button and emulator action
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct InputConfig {
+    pub a: Key,
+    pub b: Key,
+    pub select: Key,
+    pub start: Key,
+    pub up: Key,
+    pub down: Key,
+    pub left: Key,
+    pub right: Key,
+    pub pause: Key,
+    pub frame_advance: Key,
+    /// Resets the console, or power cycles it when Shift is held
+    pub reset: Key,
+    /// Shifts where the Zapper aims relative to the pointer, in NES pixels
+    pub zapper_offset_x: f32,
+    pub zapper_offset_y: f32,
+}
+
+impl InputConfig {
+    /// Gets every binding along with a name to show for it
+    pub fn bindings_mut(&mut self) -> [(&'static str, &mut Key); 11] {
+        [
+            ("A", &mut self.a),
+            ("B", &mut self.b),
+            ("Select", &mut self.select),
+            ("Start", &mut self.start),
+            ("Up", &mut self.up),
+            ("Down", &mut self.down),
+            ("Left", &mut self.left),
+            ("Right", &mut self.right),
+            ("Pause", &mut self.pause),
+            ("Frame Advance", &mut self.frame_advance),
+            ("Reset", &mut self.reset),
+        ]
+    }
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            a: Key::X,
+            b: Key::Z,
+            select: Key::Backspace,
+            start: Key::Enter,
+            up: Key::ArrowUp,
+            down: Key::ArrowDown,
+            left: Key::ArrowLeft,
+            right: Key::ArrowRight,
+            pause: Key::P,
+            frame_advance: Key::N,
+            reset: Key::R,
+            zapper_offset_x: 0.0,
+            zapper_offset_y: 0.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PathsConfig {
+    /// The most recently opened ROMs, most recent first
+    pub recent_roms: Vec<String>,
+}
+
+impl PathsConfig {
+    pub fn add_recent_rom(&mut self, rom_path: &str) {
+        self.recent_roms.retain(|path| path != rom_path);
+        self.recent_roms.insert(0, rom_path.to_string());
+        self.recent_roms.truncate(MAX_RECENT_ROMS);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SavesConfig {
+    /// How often to write battery backed RAM to the save file while playing, in seconds, if it has changed. It
+    /// is always written when the game is closed. Zero only writes it then
+    pub interval: u32,
+}
+
+impl Default for SavesConfig {
+    fn default() -> Self {
+        Self { interval: 10 }
+    }
+}
+
+/// Which debugging tools are open
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DebugConfig {
+    pub tile_grid: bool,
+    pub sprite_boxes: bool,
+    pub nametable_viewer: bool,
+    pub memory_editor: bool,
+    pub profiler: bool,
+    pub mapper_writes: bool,
+    pub break_on_nmi: bool,
+    pub break_on_irq: bool,
+    pub break_on_brk: bool,
+    pub break_on_bank_switch: bool,
+    pub watch: bool,
+    /// The expressions shown in the watch window
+    pub watches: Vec<String>,
+    pub coverage: bool,
+    pub ram_map: bool,
+    pub memory_diff: bool,
+    pub disassembly: bool,
+}
+
+impl Default for DebugConfig {
+    fn default() -> Self {
+        Self {
+            tile_grid: false,
+            sprite_boxes: false,
+            nametable_viewer: false,
+            memory_editor: true,
+            profiler: false,
+            mapper_writes: false,
+            break_on_nmi: false,
+            break_on_irq: false,
+            break_on_brk: false,
+            break_on_bank_switch: false,
+            watch: false,
+            watches: Vec::new(),
+            coverage: false,
+            ram_map: false,
+            memory_diff: false,
+            disassembly: false,
+        }
+    }
+}
+
+/// The RetroAchievements account to log in with. The token stands in for the password, which is never saved
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AchievementsConfig {
+    pub username: String,
+    /// Empty when logged out
+    pub token: String,
+}
+
+/// Discord Rich Presence, which needs the ID of an application registered on the Discord developer portal
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub enabled: bool,
+    pub application_id: String,
+}
+
+/// Autosplitting with a LiveSplit timer, for games with a splits file
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LiveSplitConfig {
+    pub enabled: bool,
+    /// The address of LiveSplit's server component
+    pub address: String,
+}
+
+impl Default for LiveSplitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: livesplit::DEFAULT_ADDRESS.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the default settings if it doesn't exist or can't be parsed
+    pub fn load() -> Self {
+        let Ok(text) = std::fs::read_to_string(CONFIG_PATH) else {
+            return Config::default();
+        };
+        toml::from_str(&text).unwrap_or_else(|error| {
+            log::error!(
+                "Failed to parse {}, using default settings: {}",
+                CONFIG_PATH,
+                error
+            );
+            Config::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = toml::to_string_pretty(self)
+            .map_err(|error| error.to_string())
+            .and_then(|text| std::fs::write(CONFIG_PATH, text).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            log::error!("Failed to save {} with error: {}", CONFIG_PATH, error);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! An optional server that lets external tools and test harnesses drive the running emulator over TCP.
+//!
+//! Each command is sent as a single line containing a JSON object, and is answered with a single line of JSON.
+//! For example, `{"command": "read_memory", "address": 768, "length": 2}` is answered with
+//! `{"ok": true, "data": [0, 0]}`. Failed commands are answered with `{"ok": false, "error": "..."}`.
+//!
+//! Commands are executed by the UI thread between frames, while it holds the lock on the emulated console, so the
+//! server threads only parse and forward them.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+use eframe::egui::Context;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::overlay::Shape;
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    LoadRom {
+        path: String,
+        /// Defaults to a patch with the same name as the ROM, if there is one
+        #[serde(default)]
+        patch: Option<String>,
+    },
+    Pause,
+    Resume,
+    /// Runs the given number of frames, then pauses
+    Step {
+        #[serde(default = "one")]
+        frames: usize,
+    },
+    ReadMemory {
+        address: usize,
+        length: usize,
+    },
+    WriteMemory {
+        address: usize,
+        data: Vec<u8>,
+    },
+    /// Writes the last completed frame to a PNG image if the path ends in .png, or a PPM image otherwise
+    Screenshot {
+        path: String,
+    },
+    /// Holds down the given buttons on the first controller, releasing any others held by a previous command
+    Press {
+        buttons: Vec<String>,
+    },
+    /// Adds shapes to draw over the game image, which stay until the drawing is cleared
+    Draw {
+        shapes: Vec<Shape>,
+    },
+    ClearDrawing,
+}
+
+fn one() -> usize {
+    1
+}
+
+/// A way to answer the client that sent a command
+pub struct Reply(Sender<Value>);
+
+impl Reply {
+    /// Answers the client. Successful results must be JSON objects, which are sent with `"ok": true` added
+    pub fn send(self, result: Result<Value, String>) {
+        let response = match result {
+            Ok(mut value) => {
+                value["ok"] = json!(true);
+                value
+            }
+            Err(error) => json!({ "ok": false, "error": error }),
+        };
+        // The client may have disconnected while waiting, which is fine
+        let _ = self.0.send(response);
+    }
+}
+
+pub struct ControlServer {
+    requests: Receiver<(Command, Reply)>,
+}
+
+impl ControlServer {
+    /// Starts accepting clients on the given port in the background. The UI is woken up whenever a command
+    /// arrives, so that commands are answered promptly even while it is idle
+    pub fn start(port: u16, ctx: Context) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let (sender, requests) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                let ctx = ctx.clone();
+                thread::spawn(move || {
+                    if let Err(error) = serve_client(stream, sender, ctx) {
+                        log::warn!("Control client disconnected with error: {}", error);
+                    }
+                });
+            }
+        });
+        log::info!("Control server listening on port {}", port);
+        Ok(Self { requests })
+    }
+
+    /// Gets the next command sent by any client, if there is one
+    pub fn poll(&self) -> Option<(Command, Reply)> {
+        self.requests.try_recv().ok()
+    }
+}
+
+fn serve_client(
+    mut stream: TcpStream,
+    sender: Sender<(Command, Reply)>,
+    ctx: Context,
+) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Command>(&line) {
+            Ok(command) => {
+                let (reply, response) = channel();
+                if sender.send((command, Reply(reply))).is_err() {
+                    return Ok(()); // The emulator is shutting down
+                }
+                ctx.request_repaint();
+                response
+                    .recv()
+                    .unwrap_or_else(|_| json!({ "ok": false, "error": "Command was dropped" }))
+            }
+            Err(error) => json!({ "ok": false, "error": error.to_string() }),
+        };
+        writeln!(stream, "{}", response)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! Debug window for recording which parts of the ROM's code have been executed, and exporting that for use in
+//! other tools. See [`nesemu_core::coverage`] for the formats it can be exported in.
+
+use std::path::Path;
+
+use eframe::{
+    egui::{Context, Window},
+    epaint::Color32,
+};
+
+use nesemu_core::NES;
+
+pub struct CoverageWindow {
+    /// The ROM the export path was suggested for, so that a new one is suggested when another ROM is loaded
+    rom_path: String,
+    export_path: String,
+    /// The outcome of the last export, and whether it succeeded
+    status: Option<(String, bool)>,
+}
+
+impl CoverageWindow {
+    pub fn new() -> Self {
+        Self {
+            rom_path: String::new(),
+            export_path: String::new(),
+            status: None,
+        }
+    }
+
+    /// Draws the coverage window, if it is open
+    pub fn render(&mut self, ctx: &Context, open: &mut bool, nes: &mut NES) {
+        if !*open {
+            return;
+        }
+        if self.rom_path != nes.rom_path() {
+            self.rom_path = nes.rom_path().to_string();
+            self.export_path = Path::new(nes.rom_path())
+                .with_extension("cdl")
+                .to_string_lossy()
+                .into_owned();
+        }
+
+        Window::new("Coverage").open(open).show(ctx, |ui| {
+            let coverage = &mut nes.coverage;
+            let mut recording = coverage.enabled();
+            if ui.checkbox(&mut recording, "Record").changed() {
+                coverage.set_enabled(recording);
+            }
+            let executed = coverage.executed_bytes();
+            let size = coverage.prg_rom_size();
+            ui.label(format!(
+                "Executed {} of {} bytes of PRG ROM ({:.1}%)",
+                executed,
+                size,
+                100.0 * executed as f64 / size.max(1) as f64
+            ));
+            if ui.button("Clear").clicked() {
+                coverage.clear();
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.export_path).on_hover_text(
+                    "Files ending in .cdl are written as a code/data log, and others as a text report",
+                );
+                if ui.button("Export").clicked() {
+                    self.status = Some(match coverage.export(Path::new(&self.export_path)) {
+                        Ok(()) => (format!("Exported to {}", self.export_path), true),
+                        Err(error) => (format!("Failed to export: {}", error), false),
+                    });
+                }
+            });
+            match &self.status {
+                Some((message, true)) => {
+                    ui.label(message);
+                }
+                Some((message, false)) => {
+                    ui.colored_label(Color32::RED, message);
+                }
+                None => {}
+            }
+        });
+    }
+}
@@ -0,0 +1,240 @@
+//! Debug window listing the code at an address, with breakpoints that can be toggled on each instruction. Its
+//! shortcuts to the handlers of the NMI, reset and IRQ vectors are the usual first step when exploring an
+//! unfamiliar ROM. See [`nesemu_core::disassembly`]. Breakpoints can also be set on reads and writes of the
+//! PPU, APU and I/O registers.
+//!
+//! When the ROM has debug info from `ld65 --dbgfile`, the listing also shows labels and the line of source each
+//! instruction came from, and labels can be used wherever an address can.
+
+use std::path::Path;
+
+use eframe::{
+    egui::{Button, Context, Grid, RichText, TextEdit, Window},
+    epaint::Color32,
+};
+
+use nesemu_core::{
+    debugger::Access,
+    disassembly::{disassemble_range, read_vector, VECTORS},
+    NES,
+};
+
+use super::args::{parse_address, parse_register};
+
+/// How many instructions are listed at once
+const LINES: usize = 24;
+
+pub struct DisassemblyWindow {
+    /// The address the listing starts at
+    address: usize,
+    address_text: String,
+    /// Whether the listing starts at the program counter, following it as emulation runs
+    follow_pc: bool,
+    /// Whether jumping to a vector's handler also sets a breakpoint on it
+    break_on_handler: bool,
+    register_text: String,
+    error: Option<String>,
+}
+
+impl DisassemblyWindow {
+    pub fn new() -> Self {
+        Self {
+            address: 0,
+            address_text: String::new(),
+            follow_pc: true,
+            break_on_handler: false,
+            register_text: String::new(),
+            error: None,
+        }
+    }
+
+    /// Draws the disassembly window, if it is open
+    ///
+    /// Returns whether the user asked to step to the next line of source, which the app has to do with emulation
+    /// halted
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        open: &mut bool,
+        nes: &mut NES,
+        netplay_connected: bool,
+    ) -> bool {
+        if !*open {
+            return false;
+        }
+
+        let mut step_requested = false;
+        Window::new("Disassembly").open(open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.address_text).desired_width(80.0))
+                    .on_hover_text("Address in hex, or a label from the debug info");
+                if ui.button("Go").clicked() {
+                    match resolve_address(nes, &self.address_text) {
+                        Ok(address) => {
+                            self.address = address;
+                            self.follow_pc = false;
+                            self.error = None;
+                        }
+                        Err(error) => self.error = Some(error),
+                    }
+                }
+                if ui
+                    .button("Break")
+                    .on_hover_text("Set a breakpoint there")
+                    .clicked()
+                {
+                    match resolve_address(nes, &self.address_text) {
+                        Ok(address) => {
+                            nes.debugger.add_breakpoint(address);
+                            self.error = None;
+                        }
+                        Err(error) => self.error = Some(error),
+                    }
+                }
+                ui.checkbox(&mut self.follow_pc, "Follow PC");
+                // Stepping only one side of a netplay session would desync it
+                let (text, hover) = if nes.debug_info.is_empty() {
+                    ("Step", "Pause and run a single instruction")
+                } else {
+                    ("Step Line", "Pause and run until the next line of source")
+                };
+                let step = ui.add_enabled(!netplay_connected, Button::new(text));
+                if step.on_hover_text(hover).clicked() {
+                    self.follow_pc = true;
+                    step_requested = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                for (name, vector) in VECTORS {
+                    let handler = read_vector(nes.bus_mut(), vector);
+                    let text = match handler {
+                        Ok(handler) => format!("{} ${:04X}", name, handler),
+                        Err(_) => name.to_string(),
+                    };
+                    let button = ui.button(text).on_hover_text(format!(
+                        "Jump to the handler the vector at ${:04X} points to",
+                        vector
+                    ));
+                    if button.clicked() {
+                        match handler {
+                            Ok(handler) => {
+                                self.address = handler;
+                                self.follow_pc = false;
+                                self.error = None;
+                                if self.break_on_handler {
+                                    nes.debugger.add_breakpoint(handler);
+                                }
+                            }
+                            Err(error) => self.error = Some(error.to_string()),
+                        }
+                    }
+                }
+                ui.checkbox(&mut self.break_on_handler, "Break there");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Register");
+                ui.add(TextEdit::singleline(&mut self.register_text).desired_width(50.0))
+                    .on_hover_text("PPU, APU or I/O register address, in hex, such as 2001");
+                for access in [Access::Read, Access::Write] {
+                    let text = match access {
+                        Access::Read => "Break on Read",
+                        Access::Write => "Break on Write",
+                    };
+                    if ui.button(text).clicked() {
+                        match parse_register(&self.register_text) {
+                            Ok(address) => {
+                                nes.debugger.add_register_breakpoint(address, access);
+                                self.error = None;
+                            }
+                            Err(error) => self.error = Some(error),
+                        }
+                    }
+                }
+            });
+            let register_breakpoints: Vec<_> =
+                nes.debugger.register_breakpoints().copied().collect();
+            for (address, access) in register_breakpoints {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} ${:04X}", access, address));
+                    if ui.small_button("Remove").clicked() {
+                        nes.debugger.remove_register_breakpoint(address, access);
+                    }
+                });
+            }
+            if let Some(error) = &self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+
+            ui.separator();
+            let program_counter = nes.cpu().program_counter();
+            if self.follow_pc {
+                self.address = program_counter;
+            }
+            let instructions = disassemble_range(nes.bus_mut(), self.address, LINES);
+            Grid::new("Disassembly").striped(true).show(ui, |ui| {
+                let mut last_location = None;
+                for instruction in instructions {
+                    let address = instruction.address;
+                    if let Some(symbol) = nes.debug_info.symbol_at(address) {
+                        ui.label("");
+                        ui.monospace(format!("{}:", symbol.name));
+                        ui.end_row();
+                    }
+                    let mut breakpoint = nes.debugger.breakpoints().any(|&b| b == address);
+                    if ui
+                        .checkbox(&mut breakpoint, "")
+                        .on_hover_text("Breakpoint")
+                        .changed()
+                    {
+                        if breakpoint {
+                            nes.debugger.add_breakpoint(address);
+                        } else {
+                            nes.debugger.remove_breakpoint(address);
+                        }
+                    }
+                    let bytes: Vec<String> = instruction
+                        .bytes
+                        .iter()
+                        .map(|byte| format!("{:02X}", byte))
+                        .collect();
+                    // The line of source is only shown on the first of the instructions it produced
+                    let location = nes.source_line(address).map(|line| (line.file, line.line));
+                    let source = match location {
+                        Some((file, line)) if location != last_location => {
+                            let file = &nes.debug_info.files()[file];
+                            let name = Path::new(file)
+                                .file_name()
+                                .map_or(file.clone(), |name| name.to_string_lossy().into_owned());
+                            format!("{}:{}", name, line)
+                        }
+                        _ => String::new(),
+                    };
+                    last_location = location;
+                    let mut line = [
+                        RichText::new(format!("${:04X}", address)),
+                        RichText::new(bytes.join(" ")),
+                        RichText::new(instruction.text),
+                        RichText::new(source),
+                    ];
+                    // Highlight the instruction about to run
+                    if address == program_counter {
+                        line = line.map(|text| text.color(Color32::YELLOW));
+                    }
+                    for text in line {
+                        ui.monospace(text);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        step_requested
+    }
+}
+
+/// Parses an address written in hex, or looks up a label with that name in the debug info
+fn resolve_address(nes: &NES, text: &str) -> Result<usize, String> {
+    match nes.debug_info.symbol(text.trim()) {
+        Some(symbol) => Ok(symbol.address),
+        None => parse_address(text),
+    }
+}
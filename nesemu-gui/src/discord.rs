@@ -0,0 +1,94 @@
+//! Discord Rich Presence, showing the game being played and for how long on the user's Discord profile.
+//!
+//! Discord is talked to over its local IPC socket from a thread of its own, since Discord may be slow to respond,
+//! or not running at all. The thread keeps trying to connect in the background until it succeeds.
+
+use std::{
+    path::Path,
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use discord_rich_presence::{
+    activity::{Activity, Timestamps},
+    DiscordIpc, DiscordIpcClient,
+};
+
+/// How long to wait between attempts to connect to Discord
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The game currently being played, as shown on Discord
+struct Game {
+    name: String,
+    /// When the game was loaded, in milliseconds since the Unix epoch
+    started: i64,
+}
+
+/// A connection to Discord, which is closed once this is dropped
+pub struct DiscordPresence {
+    pub application_id: String,
+    games: Sender<Game>,
+}
+
+impl DiscordPresence {
+    /// Starts showing presence for the Discord application with the given ID
+    pub fn start(application_id: String) -> Self {
+        let (games, receiver) = channel::<Game>();
+        let id = application_id.clone();
+        thread::spawn(move || {
+            let mut client = DiscordIpcClient::new(id);
+            let mut connected = false;
+            let mut game = None;
+            let mut changed = false;
+            loop {
+                match receiver.recv_timeout(RETRY_INTERVAL) {
+                    Ok(new_game) => {
+                        game = Some(new_game);
+                        changed = true;
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                if !connected {
+                    connected = client.connect().is_ok();
+                    changed = connected;
+                }
+                if let (true, Some(game)) = (changed, &game) {
+                    let activity = Activity::new()
+                        .details(&game.name)
+                        .timestamps(Timestamps::new().start(game.started));
+                    if let Err(error) = client.set_activity(activity) {
+                        log::warn!("Lost connection to Discord: {}", error);
+                        connected = false;
+                    }
+                    changed = false;
+                }
+            }
+            if connected {
+                let _ = client.close();
+            }
+        });
+        Self {
+            application_id,
+            games,
+        }
+    }
+
+    /// Shows a newly loaded ROM as the game being played, named after its file
+    pub fn set_game(&self, rom_path: &str) {
+        let name = Path::new(rom_path)
+            .file_stem()
+            .map_or(rom_path.into(), |name| name.to_string_lossy());
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        // The thread only exits once we are dropped, so this can't fail
+        let _ = self.games.send(Game {
+            name: name.into_owned(),
+            started,
+        });
+    }
+}
@@ -0,0 +1,321 @@
+//! Runs the emulation on a dedicated thread, so that a heavy debug UI or a slow GPU can't make the game stutter,
+//! and a slow frame of emulation can't make the UI stutter either.
+//!
+//! The UI thread sends input and commands to the emulation thread over a channel, and receives events back over
+//! another. Completed frames are handed over separately, through a [`frame_exchange`] that always has the latest
+//! one ready. The NES itself is shared behind a mutex, which the emulation thread only holds while running a
+//! frame, so that debugging tools on the UI thread can inspect and modify it in between.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui::Context;
+use nesemu_core::{
+    controller::InputEvent, debugger::BreakReason, profiler::Timings, screen::Frame, RunResult, NES,
+};
+
+use super::{
+    achievements::AchievementRuntime,
+    frame_exchange::{frame_exchange, FrameReader, FrameWriter},
+    livesplit::Autosplitter,
+    movie::MovieWriter,
+    netplay::Netplay,
+};
+
+/// Sent from the UI thread to the emulation thread
+pub enum Message {
+    /// The buttons currently held on the local controller
+    Input(u8),
+    /// Runs a single frame while paused
+    FrameAdvance,
+    /// The display has refreshed, allowing the next frame to run when emulation is paced to vsync
+    VSync,
+    StartNetplay(Netplay),
+    StopNetplay,
+    /// Replaces the achievements being evaluated at the end of every frame
+    Achievements(AchievementRuntime),
+    /// Replaces the autosplitter checked at the end of every frame
+    Autosplitter(Option<Autosplitter>),
+    /// Sets how many frames to skip drawing after each frame that is drawn
+    Frameskip(u32),
+}
+
+/// Sent from the emulation thread to the UI thread
+pub enum Event {
+    Break(BreakReason),
+    Error(&'static str),
+    NetplayEnded(String),
+    RecordingFailed(String),
+    AchievementUnlocked(u32),
+    /// The time spent emulating the last frame, sent while the NES's profiler is enabled
+    Timings(Timings),
+}
+
+pub struct Emulator {
+    nes: Arc<Mutex<NES>>,
+    halt: Arc<AtomicBool>,
+    messages: Sender<Message>,
+    events: Receiver<Event>,
+    frames: FrameReader,
+}
+
+impl Emulator {
+    /// Starts running the given NES on a new thread, which repaints the given context whenever it has an event
+    /// to deliver. The thread exits once the emulator is dropped
+    ///
+    /// With `vsync` set, the thread runs a frame for every [`Message::VSync`] it receives rather than pacing
+    /// itself with a timer. With a movie given, the inputs of every frame are recorded to it
+    pub fn new(nes: NES, ctx: Context, vsync: bool, movie: Option<MovieWriter>) -> Self {
+        let nes = Arc::new(Mutex::new(nes));
+        let halt = Arc::new(AtomicBool::new(false));
+        let (messages, message_receiver) = channel();
+        let (event_sender, events) = channel();
+        let (frame_writer, frames) = frame_exchange();
+        let mut thread = EmulationThread {
+            nes: nes.clone(),
+            halt: halt.clone(),
+            messages: message_receiver,
+            events: event_sender,
+            ctx,
+            vsync,
+            vsync_ticked: false,
+            input: 0,
+            frame_advance: false,
+            netplay: None,
+            movie,
+            achievements: AchievementRuntime::new(),
+            autosplitter: None,
+            frames: frame_writer,
+            frameskip: 0,
+            frames_to_skip: 0,
+        };
+        thread::spawn(move || thread.run());
+
+        Self {
+            nes,
+            halt,
+            messages,
+            events,
+            frames,
+        }
+    }
+
+    /// Locks the NES for inspection or modification. Emulation can't progress until the guard is dropped
+    pub fn nes(&self) -> MutexGuard<'_, NES> {
+        // A panic on the emulation thread would already have been reported, so keep going with whatever state
+        // was left behind
+        self.nes.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halt.load(Ordering::Relaxed)
+    }
+
+    pub fn set_halted(&self, halt: bool) {
+        self.halt.store(halt, Ordering::Relaxed);
+    }
+
+    pub fn send(&self, message: Message) {
+        // The thread only exits once we are dropped, so this can't fail
+        let _ = self.messages.send(message);
+    }
+
+    pub fn poll_event(&self) -> Option<Event> {
+        self.events.try_recv().ok()
+    }
+
+    /// Takes the most recently completed frame, if one completed since the last call
+    pub fn take_frame(&mut self) -> Option<MutexGuard<'_, Frame>> {
+        self.frames.take_latest()
+    }
+}
+
+struct EmulationThread {
+    nes: Arc<Mutex<NES>>,
+    halt: Arc<AtomicBool>,
+    messages: Receiver<Message>,
+    events: Sender<Event>,
+    ctx: Context,
+    vsync: bool,
+    /// Whether the display has refreshed since the last frame ran
+    vsync_ticked: bool,
+    input: u8,
+    frame_advance: bool,
+    netplay: Option<Netplay>,
+    movie: Option<MovieWriter>,
+    achievements: AchievementRuntime,
+    autosplitter: Option<Autosplitter>,
+    frames: FrameWriter,
+    frameskip: u32,
+    /// How many more frames to skip drawing before the next one is drawn
+    frames_to_skip: u32,
+}
+
+impl EmulationThread {
+    fn run(&mut self) {
+        let frame_time = Duration::from_secs_f64(NES::FRAME_TIME);
+        let mut next_frame = Instant::now();
+        loop {
+            // Wait for messages while halted or waiting for vsync, but wake up regularly to notice being resumed
+            let halted = self.halt.load(Ordering::Relaxed) && !self.frame_advance;
+            if halted {
+                // Stopping on only one side of a netplay session would leave the other side waiting forever
+                self.end_netplay("Emulation stopped".to_string());
+            }
+            let waiting = halted || (self.vsync && !self.vsync_ticked && !self.frame_advance);
+            let timeout = if waiting { frame_time } else { Duration::ZERO };
+            match self.messages.recv_timeout(timeout) {
+                Ok(message) => {
+                    self.handle_message(message);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) if waiting => {
+                    next_frame = Instant::now();
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            // Catch up on any remaining messages before running the frame
+            loop {
+                match self.messages.try_recv() {
+                    Ok(message) => self.handle_message(message),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            self.vsync_ticked = false;
+            self.run_frame();
+            self.frame_advance = false;
+            if self.vsync {
+                continue;
+            }
+
+            // Pace emulation to the console's frame rate. If we fell behind, don't try to catch up
+            next_frame += frame_time;
+            let now = Instant::now();
+            if next_frame > now {
+                spin_sleep::sleep(next_frame - now);
+            } else {
+                next_frame = now;
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Input(input) => self.input = input,
+            Message::FrameAdvance => self.frame_advance = true,
+            Message::VSync => self.vsync_ticked = true,
+            Message::StartNetplay(netplay) => self.netplay = Some(netplay),
+            Message::StopNetplay => self.netplay = None,
+            Message::Achievements(achievements) => self.achievements = achievements,
+            Message::Autosplitter(autosplitter) => self.autosplitter = autosplitter,
+            Message::Frameskip(frameskip) => {
+                self.frameskip = frameskip;
+                self.frames_to_skip = 0;
+            }
+        }
+    }
+
+    fn run_frame(&mut self) {
+        let inputs = self.controller_inputs();
+        // Frames run one at a time while paused are always drawn, since they're meant to be looked at
+        let skip = self.frames_to_skip > 0 && !self.frame_advance;
+        if skip {
+            self.frames_to_skip -= 1;
+        } else {
+            self.frames_to_skip = self.frameskip;
+        }
+        let mut nes = self.nes.lock().unwrap_or_else(|error| error.into_inner());
+        nes.ppu_mut().set_skip_rendering(skip);
+        let bus = nes.bus_mut();
+        bus.controller.set_state_from_window(InputEvent {
+            input_state: inputs[0],
+        });
+        bus.controller_two.set_state_from_window(InputEvent {
+            input_state: inputs[1],
+        });
+
+        let result = nes.run_frame(&mut *self.frames.back());
+        let timings = nes.profiler.enabled().then(|| nes.profiler.take_timings());
+        let mut unlocked = Vec::new();
+        if let Ok(RunResult::FrameComplete) = result {
+            let bus = nes.bus_mut();
+            let mut read = |address| bus.cpu_read_byte_no_modify(address).unwrap_or(0);
+            unlocked = self.achievements.do_frame(&mut read);
+            if let Some(autosplitter) = &mut self.autosplitter {
+                autosplitter.do_frame(&mut read);
+            }
+        }
+        drop(nes);
+        if let Some(timings) = timings {
+            self.send_event(Event::Timings(timings));
+        }
+        for id in unlocked {
+            self.send_event(Event::AchievementUnlocked(id));
+        }
+        let event = match result {
+            Ok(RunResult::FrameComplete) => {
+                self.record_frame(inputs);
+                if !skip {
+                    // The PPU draws every pixel of every frame, so the old contents of the buffer taken over
+                    // don't matter
+                    self.frames.publish();
+                    self.ctx.request_repaint();
+                }
+                return;
+            }
+            Ok(RunResult::Break(reason)) => Event::Break(reason),
+            Err(error) => Event::Error(error),
+        };
+        self.halt.store(true, Ordering::Relaxed);
+        self.send_event(event);
+    }
+
+    /// Gets the state of both controllers for the next frame. During netplay, this waits for the remote
+    /// player's input, without holding the lock on the NES
+    fn controller_inputs(&mut self) -> [u8; 2] {
+        let Some(netplay) = &mut self.netplay else {
+            return [self.input, 0];
+        };
+        let state_hash = self
+            .nes
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .state_hash();
+        match netplay.exchange_inputs(self.input, state_hash) {
+            Ok(inputs) => inputs,
+            Err(reason) => {
+                self.end_netplay(reason);
+                [self.input, 0]
+            }
+        }
+    }
+
+    fn record_frame(&mut self, inputs: [u8; 2]) {
+        if let Some(Err(error)) = self.movie.as_mut().map(|movie| movie.write_frame(inputs)) {
+            self.movie = None;
+            self.send_event(Event::RecordingFailed(error.to_string()));
+        }
+    }
+
+    fn end_netplay(&mut self, reason: String) {
+        if self.netplay.take().is_some() {
+            self.send_event(Event::NetplayEnded(reason));
+        }
+    }
+
+    fn send_event(&self, event: Event) {
+        let _ = self.events.send(event);
+        self.ctx.request_repaint();
+    }
+}
@@ -0,0 +1,89 @@
+//! Hands completed frames from the emulation thread to the UI thread through three buffers, so that neither
+//! side ever waits on the other. The emulation thread always has a buffer of its own to draw into, the UI thread
+//! always has the frame it is presenting, and the third buffer holds the most recently completed frame. Frames
+//! that complete before the UI gets around to presenting them are drawn over rather than queued up.
+//!
+//! Which buffer belongs to which side is decided by swapping an index atomically. Each buffer sits behind a
+//! mutex only to let the threads share it in safe code: a buffer is only ever locked by the side that owns it,
+//! so locking never has to wait.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+
+use nesemu_core::screen::Frame;
+
+/// Set on the shared index when its buffer holds a frame the UI hasn't taken yet
+const FRESH: usize = 0b100;
+const INDEX: usize = 0b011;
+
+struct Buffers {
+    frames: [Mutex<Frame>; 3],
+    /// The index of the buffer holding the latest completed frame, along with [`FRESH`]
+    latest: AtomicUsize,
+}
+
+impl Buffers {
+    fn lock(&self, index: usize) -> MutexGuard<'_, Frame> {
+        // A panic while drawing a frame only leaves a partly drawn frame behind
+        self.frames[index]
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+/// Creates the two ends of an exchange, for the emulation and UI threads
+pub fn frame_exchange() -> (FrameWriter, FrameReader) {
+    let buffers = Arc::new(Buffers {
+        frames: [(); 3].map(|_| Mutex::new(Frame::new())),
+        latest: AtomicUsize::new(1),
+    });
+    let writer = FrameWriter {
+        buffers: buffers.clone(),
+        back: 0,
+    };
+    let reader = FrameReader { buffers, front: 2 };
+    (writer, reader)
+}
+
+/// The emulation thread's end of the exchange
+pub struct FrameWriter {
+    buffers: Arc<Buffers>,
+    back: usize,
+}
+
+impl FrameWriter {
+    /// Gets the buffer to draw the next frame into. It keeps its contents until the frame is published, so a
+    /// frame that was interrupted partway through can be finished later
+    pub fn back(&self) -> MutexGuard<'_, Frame> {
+        self.buffers.lock(self.back)
+    }
+
+    /// Makes the frame in the back buffer the latest one, and takes over the buffer of the frame it replaces
+    pub fn publish(&mut self) {
+        let previous = self
+            .buffers
+            .latest
+            .swap(self.back | FRESH, Ordering::AcqRel);
+        self.back = previous & INDEX;
+    }
+}
+
+/// The UI thread's end of the exchange
+pub struct FrameReader {
+    buffers: Arc<Buffers>,
+    front: usize,
+}
+
+impl FrameReader {
+    /// Takes the latest completed frame, if one was published since the last call
+    pub fn take_latest(&mut self) -> Option<MutexGuard<'_, Frame>> {
+        if self.buffers.latest.load(Ordering::Acquire) & FRESH == 0 {
+            return None;
+        }
+        let latest = self.buffers.latest.swap(self.front, Ordering::AcqRel);
+        self.front = latest & INDEX;
+        Some(self.buffers.lock(self.front))
+    }
+}
@@ -0,0 +1,461 @@
+//! A server for the GDB remote serial protocol, which lets GDB, or any IDE that speaks the protocol, debug the
+//! program running on the emulated 6502 over TCP. Like headless mode, no window is opened.
+//!
+//! GDB has no built-in knowledge of the 6502, so the register layout is sent to the client as a target
+//! description: A, X, Y, P and SP as single bytes, followed by the 16 bit PC in little endian.
+
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use nesemu_core::{
+    debugger::{is_register, Access, BreakReason},
+    screen::Frame,
+    RunResult, NES,
+};
+use tock_registers::interfaces::{Readable, Writeable};
+
+use super::{
+    args::Args,
+    headless::{EXIT_LOAD_ERROR, EXIT_SUCCESS},
+};
+
+pub const EXIT_CONNECTION_ERROR: i32 = 3;
+
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// Sent by the client outside of a packet to interrupt a running program
+const INTERRUPT: u8 = 0x03;
+
+/// Sizes in bytes of the registers, in the order GDB numbers them
+const REGISTER_SIZES: [usize; 6] = [1, 1, 1, 1, 1, 2];
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.nesemu.6502">
+    <reg name="a" bitsize="8" type="uint8"/>
+    <reg name="x" bitsize="8" type="uint8"/>
+    <reg name="y" bitsize="8" type="uint8"/>
+    <reg name="p" bitsize="8" type="uint8"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+  </feature>
+</target>"#;
+
+/// Loads the ROM and serves a single GDB connection on the given port, returning the exit status code
+pub fn run(args: &Args, port: u16) -> i32 {
+    let nes = match args.load_nes() {
+        Ok(nes) => nes,
+        Err(error) => {
+            eprintln!("Failed to initialize NES with error: {}", error);
+            return EXIT_LOAD_ERROR;
+        }
+    };
+
+    let stream = match TcpListener::bind(("127.0.0.1", port)).and_then(|listener| {
+        println!("Waiting for GDB to connect on port {}", port);
+        listener.accept()
+    }) {
+        Ok((stream, _)) => stream,
+        Err(error) => {
+            eprintln!("Failed to accept a GDB connection with error: {}", error);
+            return EXIT_CONNECTION_ERROR;
+        }
+    };
+
+    let mut session = Session {
+        nes,
+        connection: Connection { stream },
+        frame: Frame::new(),
+    };
+    match session.serve() {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => {
+            println!("GDB disconnected");
+            EXIT_SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Connection to GDB failed with error: {}", error);
+            EXIT_CONNECTION_ERROR
+        }
+    }
+}
+
+enum Request {
+    Packet(String),
+    Interrupt,
+}
+
+/// The packet framing layer of the protocol. Packets are sent as `$data#checksum`, and acknowledged by the
+/// receiver with a `+`, or a `-` if the checksum didn't match
+struct Connection {
+    stream: TcpStream,
+}
+
+impl Connection {
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8];
+        self.stream.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    /// Blocks until the client sends either a valid packet or an interrupt
+    fn receive(&mut self) -> io::Result<Request> {
+        loop {
+            match self.read_byte()? {
+                b'$' => {}
+                INTERRUPT => return Ok(Request::Interrupt),
+                _ => continue, // Acknowledgements of our own packets
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    b'#' => break,
+                    byte => data.push(byte),
+                }
+            }
+            let checksum = [self.read_byte()?, self.read_byte()?];
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|text| u8::from_str_radix(text, 16).ok());
+            if expected == Some(checksum_of(&data)) {
+                self.stream.write_all(b"+")?;
+                return Ok(Request::Packet(String::from_utf8_lossy(&data).into_owned()));
+            }
+            self.stream.write_all(b"-")?;
+        }
+    }
+
+    /// Checks, without blocking, whether the client has asked for the running program to be interrupted
+    fn interrupt_requested(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut byte = [0u8];
+        let result = self.stream.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+        match result {
+            Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) => Ok(byte[0] == INTERRUPT),
+            Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        write!(
+            self.stream,
+            "${}#{:02x}",
+            data,
+            checksum_of(data.as_bytes())
+        )
+    }
+}
+
+struct Session {
+    nes: NES,
+    connection: Connection,
+    frame: Frame,
+}
+
+impl Session {
+    fn serve(&mut self) -> io::Result<()> {
+        loop {
+            let packet = match self.connection.receive()? {
+                Request::Packet(packet) => packet,
+                // The program is already stopped, so there is nothing to interrupt
+                Request::Interrupt => {
+                    self.connection.send(&stop_reply(SIGINT))?;
+                    continue;
+                }
+            };
+
+            let (command, body) = packet.split_at(packet.len().min(1));
+            let reply = match command {
+                "?" => stop_reply(SIGTRAP),
+                "g" => to_hex(&self.register_bytes()),
+                "G" => self.write_registers(body),
+                "p" => self.read_register(body),
+                "P" => self.write_register(body),
+                "m" => self.read_memory(body),
+                "M" => self.write_memory(body),
+                "Z" => self.set_breakpoint(body, true),
+                "z" => self.set_breakpoint(body, false),
+                "s" => self.step(body),
+                "c" => self.resume(body)?,
+                "H" => "OK".to_string(),
+                "q" => self.query(body),
+                "D" => {
+                    self.connection.send("OK")?;
+                    return Ok(());
+                }
+                "k" => return Ok(()),
+                _ => String::new(), // An empty reply tells the client the packet is unsupported
+            };
+            self.connection.send(&reply)?;
+        }
+    }
+
+    fn query(&self, body: &str) -> String {
+        if body.starts_with("Supported") {
+            "PacketSize=1000;qXfer:features:read+".to_string()
+        } else if body == "Attached" {
+            "1".to_string()
+        } else if let Some(range) = body.strip_prefix("Xfer:features:read:target.xml:") {
+            match parse_range(range) {
+                Some((offset, length)) => {
+                    let start = offset.min(TARGET_XML.len());
+                    let end = (start + length).min(TARGET_XML.len());
+                    let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+                    format!("{}{}", marker, &TARGET_XML[start..end])
+                }
+                None => error_reply(),
+            }
+        } else {
+            String::new()
+        }
+    }
+
+    fn register_bytes(&self) -> Vec<u8> {
+        let registers = self.nes.cpu().registers();
+        let mut bytes = vec![
+            registers.accumulator,
+            registers.x_reg,
+            registers.y_reg,
+            registers.status_register.get(),
+            registers.stack_ptr as u8,
+        ];
+        bytes.extend_from_slice(&(registers.program_counter as u16).to_le_bytes());
+        bytes
+    }
+
+    fn set_register_bytes(&mut self, bytes: &[u8]) {
+        let registers = self.nes.cpu_mut().registers_mut();
+        registers.accumulator = bytes[0];
+        registers.x_reg = bytes[1];
+        registers.y_reg = bytes[2];
+        registers.status_register.set(bytes[3]);
+        registers.stack_ptr = bytes[4] as usize;
+        registers.program_counter = u16::from_le_bytes([bytes[5], bytes[6]]) as usize;
+    }
+
+    fn write_registers(&mut self, body: &str) -> String {
+        match from_hex(body) {
+            Some(bytes) if bytes.len() == REGISTER_SIZES.iter().sum::<usize>() => {
+                self.set_register_bytes(&bytes);
+                "OK".to_string()
+            }
+            _ => error_reply(),
+        }
+    }
+
+    /// Gets the byte range of a register within the full register list, from its hex register number
+    fn register_range(number: &str) -> Option<std::ops::Range<usize>> {
+        let index = usize::from_str_radix(number, 16).ok()?;
+        let size = *REGISTER_SIZES.get(index)?;
+        let offset = REGISTER_SIZES[..index].iter().sum();
+        Some(offset..offset + size)
+    }
+
+    fn read_register(&self, body: &str) -> String {
+        match Session::register_range(body) {
+            Some(range) => to_hex(&self.register_bytes()[range]),
+            None => error_reply(),
+        }
+    }
+
+    fn write_register(&mut self, body: &str) -> String {
+        let Some((number, value)) = body.split_once('=') else {
+            return error_reply();
+        };
+        match (Session::register_range(number), from_hex(value)) {
+            (Some(range), Some(value)) if range.len() == value.len() => {
+                let mut bytes = self.register_bytes();
+                bytes[range].copy_from_slice(&value);
+                self.set_register_bytes(&bytes);
+                "OK".to_string()
+            }
+            _ => error_reply(),
+        }
+    }
+
+    /// Reads memory as the CPU sees it, without triggering the side effects of reading I/O registers
+    fn read_memory(&mut self, body: &str) -> String {
+        let Some((address, length)) = parse_range(body) else {
+            return error_reply();
+        };
+        let mut bytes = Vec::with_capacity(length);
+        for offset in 0..length {
+            match self
+                .nes
+                .bus_mut()
+                .cpu_read_byte_no_modify((address + offset) & 0xFFFF)
+            {
+                Ok(byte) => bytes.push(byte),
+                Err(_) => break,
+            }
+        }
+
+        // Partial reads are allowed, but a read that fails entirely must return an error
+        if bytes.is_empty() && length != 0 {
+            error_reply()
+        } else {
+            to_hex(&bytes)
+        }
+    }
+
+    fn write_memory(&mut self, body: &str) -> String {
+        let Some((range, data)) = body.split_once(':') else {
+            return error_reply();
+        };
+        let (Some((address, length)), Some(data)) = (parse_range(range), from_hex(data)) else {
+            return error_reply();
+        };
+        if data.len() != length {
+            return error_reply();
+        }
+        for (offset, byte) in data.into_iter().enumerate() {
+            if self
+                .nes
+                .bus_mut()
+                .cpu_write_byte((address + offset) & 0xFFFF, byte)
+                .is_err()
+            {
+                return error_reply();
+            }
+        }
+        "OK".to_string()
+    }
+
+    /// Handles the Z and z packets. Software and hardware breakpoints are treated the same. Watchpoints are only
+    /// supported on the PPU, APU and I/O registers, where they become register breakpoints
+    fn set_breakpoint(&mut self, body: &str, insert: bool) -> String {
+        let mut fields = body.split(',');
+        let kind = fields.next();
+        let address = fields
+            .next()
+            .and_then(|address| usize::from_str_radix(address, 16).ok());
+        match (kind, address) {
+            (Some("0" | "1"), Some(address)) => {
+                if insert {
+                    self.nes.debugger.add_breakpoint(address);
+                } else {
+                    self.nes.debugger.remove_breakpoint(address);
+                }
+                "OK".to_string()
+            }
+            (Some(kind @ ("2" | "3" | "4")), Some(address)) if is_register(address) => {
+                let accesses = match kind {
+                    "2" => &[Access::Write][..],
+                    "3" => &[Access::Read],
+                    _ => &[Access::Read, Access::Write],
+                };
+                for &access in accesses {
+                    if insert {
+                        self.nes.debugger.add_register_breakpoint(address, access);
+                    } else {
+                        self.nes
+                            .debugger
+                            .remove_register_breakpoint(address, access);
+                    }
+                }
+                "OK".to_string()
+            }
+            (Some(_), Some(_)) => String::new(),
+            _ => error_reply(),
+        }
+    }
+
+    /// The step and continue packets can optionally give an address to resume execution from
+    fn jump_to(&mut self, body: &str) -> Result<(), String> {
+        if !body.is_empty() {
+            let address = usize::from_str_radix(body, 16).map_err(|_| error_reply())?;
+            self.nes.cpu_mut().registers_mut().program_counter = address & 0xFFFF;
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, body: &str) -> String {
+        if let Err(reply) = self.jump_to(body) {
+            return reply;
+        }
+        match self.nes.step_instruction(&mut self.frame) {
+            Ok(()) => stop_reply(SIGTRAP),
+            Err(error) => emulation_error(error),
+        }
+    }
+
+    /// Runs until a breakpoint is hit, the CPU fails, or the client interrupts. Interrupts are only checked
+    /// for between frames
+    fn resume(&mut self, body: &str) -> io::Result<String> {
+        if let Err(reply) = self.jump_to(body) {
+            return Ok(reply);
+        }
+        loop {
+            match self.nes.run_frame(&mut self.frame) {
+                Ok(RunResult::FrameComplete) => {
+                    if self.connection.interrupt_requested()? {
+                        return Ok(stop_reply(SIGINT));
+                    }
+                }
+                Ok(RunResult::Break(BreakReason::RegisterAccess {
+                    access, address, ..
+                })) => return Ok(watch_reply(access, address)),
+                Ok(RunResult::Break(_)) => return Ok(stop_reply(SIGTRAP)),
+                Err(error) => return Ok(emulation_error(error)),
+            }
+        }
+    }
+}
+
+fn emulation_error(error: &str) -> String {
+    eprintln!("Emulation failed with error: {}", error);
+    stop_reply(SIGILL)
+}
+
+fn stop_reply(signal: u8) -> String {
+    format!("S{:02x}", signal)
+}
+
+/// Tells the client which watchpoint was hit, so that it reports the access rather than a bare SIGTRAP
+fn watch_reply(access: Access, address: usize) -> String {
+    let kind = match access {
+        Access::Read => "rwatch",
+        Access::Write => "watch",
+    };
+    format!("T{:02x}{}:{:x};", SIGTRAP, kind, address)
+}
+
+fn error_reply() -> String {
+    "E01".to_string()
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Parses the `start,length` pair, in hex, used by several packets
+fn parse_range(text: &str) -> Option<(usize, usize)> {
+    let (start, length) = text.split_once(',')?;
+    Some((
+        usize::from_str_radix(start, 16).ok()?,
+        usize::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
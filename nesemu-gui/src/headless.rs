@@ -0,0 +1,170 @@
+//! Runs the emulator without a window, for use in scripts and automated testing. Once emulation stops, the
+//! requested artifacts are written to disk and the process exits with a status code describing the outcome.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Error, Write},
+    path::Path,
+};
+
+use nesemu_core::{
+    audio::Audio,
+    controller::InputEvent,
+    debugger::Interrupt,
+    screen::{Frame, SCREEN_HEIGHT, SCREEN_WIDTH},
+    RunResult, NES,
+};
+
+use super::{
+    args::Args,
+    movie::{read_movie, write_hashes},
+};
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_EMULATION_ERROR: i32 = 1;
+pub const EXIT_LOAD_ERROR: i32 = 2;
+
+/// Runs the headless emulator according to the command line options, returning the exit status code
+pub fn run(args: &Args) -> i32 {
+    let loaded = args.load_nes().and_then(|nes| {
+        let inputs = args.inputs.as_deref().map(read_movie).transpose()?;
+        Ok((nes, inputs.unwrap_or_default()))
+    });
+    let (mut nes, inputs) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("Failed to initialize NES with error: {}", error);
+            return EXIT_LOAD_ERROR;
+        }
+    };
+    if let Some(path) = &args.trace_out {
+        nes.set_trace_file(path);
+    }
+    if let Some(address) = args.break_address {
+        nes.debugger.add_breakpoint(address);
+    }
+    for &(address, access) in &args.break_registers {
+        nes.debugger.add_register_breakpoint(address, access);
+    }
+    for kind in &args.break_on {
+        match kind {
+            Interrupt::Nmi => nes.debugger.break_on_nmi = true,
+            Interrupt::Irq => nes.debugger.break_on_irq = true,
+            Interrupt::Brk => nes.debugger.break_on_brk = true,
+        }
+    }
+    nes.coverage.set_enabled(args.coverage_out.is_some());
+    nes.apu_log.set_enabled(args.apu_log_out.is_some());
+    nes.audio.set_enabled(args.audio_out.is_some());
+
+    let mut frame = Frame::new();
+    let mut frames_run = 0;
+    let mut hashes = Vec::new();
+    let mut status = EXIT_SUCCESS;
+    while args.frames.is_none_or(|frames| frames_run < frames) {
+        // Once the movie runs out, every button is released
+        let [first, second] = inputs.get(frames_run).copied().unwrap_or_default();
+        let bus = nes.bus_mut();
+        bus.controller
+            .set_state_from_window(InputEvent { input_state: first });
+        bus.controller_two.set_state_from_window(InputEvent {
+            input_state: second,
+        });
+        match nes.run_frame(&mut frame) {
+            Ok(RunResult::FrameComplete) => {
+                frames_run += 1;
+                if args.hash_out.is_some() {
+                    hashes.push(nes.frame_hash(&frame));
+                }
+            }
+            Ok(RunResult::Break(reason)) => {
+                println!("{} after {} frames", reason, frames_run);
+                break;
+            }
+            Err(error) => {
+                eprintln!(
+                    "Emulation failed after {} frames with error: {}",
+                    frames_run, error
+                );
+                status = EXIT_EMULATION_ERROR;
+                break;
+            }
+        }
+    }
+
+    if let Err(error) = write_artifacts(args, &mut nes, &frame, &hashes) {
+        eprintln!("Failed to write output with error: {}", error);
+        status = EXIT_EMULATION_ERROR;
+    }
+    status
+}
+
+fn write_artifacts(args: &Args, nes: &mut NES, frame: &Frame, hashes: &[u64]) -> Result<(), Error> {
+    if let Some(path) = &args.frame_out {
+        let pixels = frame.pixels.iter().map(|pixel| [pixel.r, pixel.g, pixel.b]);
+        write_image(path, pixels)?;
+    }
+    if let Some(path) = &args.ram_out {
+        std::fs::write(path, nes.bus().cpu_ram())?;
+    }
+    if let Some(path) = &args.hash_out {
+        write_hashes(path, hashes)?;
+    }
+    if let Some(path) = &args.coverage_out {
+        nes.coverage.export(Path::new(path))?;
+    }
+    if let Some(path) = &args.apu_log_out {
+        nes.apu_log.export(Path::new(path))?;
+    }
+    if let Some(path) = &args.audio_out {
+        write_wav(path, &nes.audio.take_samples())?;
+    }
+    Ok(())
+}
+
+/// Writes mono samples to a 16-bit PCM WAV file at the mixer's sample rate
+fn write_wav(path: &str, samples: &[f32]) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let data_size = samples.len() as u32 * 2;
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_size).to_le_bytes())?;
+    file.write_all(b"WAVEfmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    // PCM, one channel
+    file.write_all(&1u16.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?;
+    file.write_all(&Audio::SAMPLE_RATE.to_le_bytes())?;
+    file.write_all(&(Audio::SAMPLE_RATE * 2).to_le_bytes())?;
+    // Two bytes per frame, sixteen bits per sample
+    file.write_all(&2u16.to_le_bytes())?;
+    file.write_all(&16u16.to_le_bytes())?;
+    file.write_all(b"data")?;
+    file.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        file.write_all(&sample.to_le_bytes())?;
+    }
+    file.flush()
+}
+
+/// Writes a full screen of RGB pixels as a PNG image if the path ends in .png, and as a binary PPM image, which
+/// almost every image viewer can open, otherwise
+pub fn write_image(path: &str, pixels: impl Iterator<Item = [u8; 3]>) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let is_png = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    if is_png {
+        let mut encoder = png::Encoder::new(&mut file, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = pixels.flatten().collect();
+        encoder.write_header()?.write_image_data(&data)?;
+    } else {
+        write!(file, "P6\n{} {}\n255\n", SCREEN_WIDTH, SCREEN_HEIGHT)?;
+        for pixel in pixels {
+            file.write_all(&pixel)?;
+        }
+    }
+    file.flush()
+}
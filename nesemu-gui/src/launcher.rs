@@ -0,0 +1,98 @@
+//! Starts the app, or, if the ROM given on the command line can't be loaded, shows why and lets the user pick a
+//! different one rather than closing straight away.
+
+use eframe::{
+    egui::{CentralPanel, Context, Key},
+    epaint::Color32,
+};
+
+use super::{app::App, args::Args, config::Config};
+
+pub enum Launcher {
+    Running(Box<App>),
+    Failed(Box<Failure>),
+}
+
+/// Starting the app failed, and the user is being asked for another ROM to try
+pub struct Failure {
+    args: Args,
+    config: Config,
+    error: String,
+    /// The ROM path being typed in
+    rom_path: String,
+}
+
+impl Launcher {
+    pub fn new(args: Args, config: Config, ctx: &Context) -> Self {
+        match App::new(args.clone(), config.clone(), ctx) {
+            Ok(app) => Launcher::Running(Box::new(app)),
+            Err(error) => {
+                log::error!("Failed to start with error: {}", error);
+                Launcher::Failed(Box::new(Failure {
+                    rom_path: args.rom_path.clone(),
+                    args,
+                    config,
+                    error: error.to_string(),
+                }))
+            }
+        }
+    }
+}
+
+impl eframe::App for Launcher {
+    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+        let Failure {
+            args,
+            config,
+            error,
+            rom_path,
+        } = match self {
+            Launcher::Running(app) => return app.update(ctx, frame),
+            Launcher::Failed(failure) => failure.as_mut(),
+        };
+        let mut requested_rom = None;
+        CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Failed to start");
+            let source = if args.stdin {
+                "Standard input"
+            } else {
+                &args.rom_path
+            };
+            ui.colored_label(Color32::RED, format!("{}: {}", source, error));
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Open a different ROM");
+                let response = ui.text_edit_singleline(rom_path);
+                let submitted =
+                    response.lost_focus() && ui.input(|info| info.key_pressed(Key::Enter));
+                if ui.button("Open").clicked() || submitted {
+                    requested_rom = Some(rom_path.clone());
+                }
+            });
+            ui.label("Recent");
+            for path in &config.paths.recent_roms {
+                if ui.button(path).clicked() {
+                    requested_rom = Some(path.clone());
+                }
+            }
+            ui.separator();
+            if ui.button("Quit").clicked() {
+                ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Close);
+            }
+        });
+
+        if let Some(path) = requested_rom.filter(|path| !path.is_empty()) {
+            // The patch given on the command line was meant for the ROM that failed
+            args.rom_path = path;
+            args.patch = None;
+            args.stdin = false;
+            *self = Launcher::new(args.clone(), config.clone(), ctx);
+        }
+    }
+
+    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
+        if let Launcher::Running(app) = self {
+            app.on_exit(gl);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Controls a LiveSplit timer from the emulator's state, so that speedrunners don't have to start and split it
+//! by hand.
+//!
+//! Timer commands are sent to LiveSplit's server component over TCP. When the timer should start, split and
+//! reset is defined per game, in a `<rom name>.splits.toml` file next to the ROM. Each condition uses the same
+//! syntax as RetroAchievements triggers:
+//!
+//! ```toml
+//! start = "0xH0770=1_d0xH0770=0"
+//! reset = "0xH0770=0"
+//! splits = ["0xH075F=1_d0xH075F=0", "0xH075F=2_d0xH075F=1"]
+//! ```
+//!
+//! The start condition is checked until the timer starts, after which each split condition is checked in turn,
+//! along with the optional reset condition.
+
+use std::{
+    io::{self, Write},
+    net::TcpStream,
+    path::Path,
+    sync::mpsc::{channel, Sender},
+    thread,
+};
+
+use serde::Deserialize;
+
+use super::achievements::AchievementRuntime;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:16834";
+
+enum TimerCommand {
+    Start,
+    Split,
+    Reset,
+}
+
+impl TimerCommand {
+    fn text(&self) -> &'static str {
+        match self {
+            TimerCommand::Start => "starttimer",
+            TimerCommand::Split => "split",
+            TimerCommand::Reset => "reset",
+        }
+    }
+}
+
+/// A connection to the LiveSplit server, which is made on a thread of its own so that sending a command never
+/// holds up emulation. The thread connects when the first command is sent, and reconnects if it is lost
+struct LiveSplitClient {
+    commands: Sender<TimerCommand>,
+}
+
+impl LiveSplitClient {
+    fn new(address: String) -> Self {
+        let (commands, receiver) = channel::<TimerCommand>();
+        thread::spawn(move || {
+            let mut stream: Option<TcpStream> = None;
+            for command in receiver {
+                let result = match &mut stream {
+                    Some(stream) => writeln!(stream, "{}\r", command.text()),
+                    None => TcpStream::connect(&address).and_then(|mut new_stream| {
+                        writeln!(new_stream, "{}\r", command.text())?;
+                        stream = Some(new_stream);
+                        Ok(())
+                    }),
+                };
+                if let Err(error) = result {
+                    log::warn!(
+                        "Failed to send command to LiveSplit at {}: {}",
+                        address,
+                        error
+                    );
+                    stream = None;
+                }
+            }
+        });
+        Self { commands }
+    }
+
+    fn send(&self, command: TimerCommand) {
+        // The thread only exits once we are dropped, so this can't fail
+        let _ = self.commands.send(command);
+    }
+}
+
+/// The conditions read from a game's splits file
+#[derive(Deserialize)]
+struct SplitDefinitions {
+    start: String,
+    reset: Option<String>,
+    splits: Vec<String>,
+}
+
+/// IDs used to tell the conditions apart when they fire
+const START: u32 = 0;
+const RESET: u32 = 1;
+const SPLIT: u32 = 2;
+
+/// Evaluates a game's split conditions every frame, sending the timer commands to LiveSplit
+pub struct Autosplitter {
+    definitions: SplitDefinitions,
+    client: LiveSplitClient,
+    /// The index of the next split once the timer is running
+    next_split: Option<usize>,
+    /// The conditions that can fire in the current state
+    runtime: AchievementRuntime,
+}
+
+impl Autosplitter {
+    /// Loads the splits file for a ROM, returning `None` if the game has none
+    pub fn load(rom_path: &str, address: String) -> io::Result<Option<Self>> {
+        let path = Path::new(rom_path).with_extension("splits.toml");
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let invalid = |error: String| {
+            let message = format!("{}: {}", path.display(), error);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        };
+        let definitions: SplitDefinitions =
+            toml::from_str(&text).map_err(|error| invalid(error.to_string()))?;
+
+        // Check every condition up front, so that they can't fail to parse later on
+        let mut runtime = AchievementRuntime::new();
+        let conditions = [Some(&definitions.start), definitions.reset.as_ref()];
+        for condition in conditions.into_iter().flatten().chain(&definitions.splits) {
+            runtime.add(0, condition).map_err(invalid)?;
+        }
+
+        let mut autosplitter = Self {
+            definitions,
+            client: LiveSplitClient::new(address),
+            next_split: None,
+            runtime,
+        };
+        autosplitter.arm();
+        Ok(Some(autosplitter))
+    }
+
+    /// Sets up the conditions that can fire next
+    fn arm(&mut self) {
+        // Every condition was checked on load, so adding them can't fail
+        self.runtime = AchievementRuntime::new();
+        let Some(next_split) = self.next_split else {
+            let _ = self.runtime.add(START, &self.definitions.start);
+            return;
+        };
+        if let Some(reset) = &self.definitions.reset {
+            let _ = self.runtime.add(RESET, reset);
+        }
+        // Once every split is done, only a reset can happen
+        if let Some(split) = self.definitions.splits.get(next_split) {
+            let _ = self.runtime.add(SPLIT, split);
+        }
+    }
+
+    /// Checks the conditions at the end of a frame
+    pub fn do_frame(&mut self, read: impl FnMut(usize) -> u8) {
+        let fired = self.runtime.do_frame(read);
+        // A reset takes priority over a split on the same frame
+        let (command, next_split) = if fired.contains(&RESET) {
+            (TimerCommand::Reset, None)
+        } else if fired.contains(&SPLIT) {
+            (TimerCommand::Split, self.next_split.map(|split| split + 1))
+        } else if fired.contains(&START) {
+            (TimerCommand::Start, Some(0))
+        } else {
+            return;
+        };
+        self.client.send(command);
+        self.next_split = next_split;
+        self.arm();
+    }
+}
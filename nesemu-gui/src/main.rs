@@ -0,0 +1,74 @@
+use args::{Args, USAGE};
+use config::Config;
+use eframe::egui::ViewportBuilder;
+use launcher::Launcher;
+use std::env;
+
+mod achievements;
+mod app;
+mod args;
+mod bench;
+mod config;
+mod control;
+mod coverage;
+mod disassembly;
+mod discord;
+mod emulator;
+mod frame_exchange;
+mod gdb;
+mod headless;
+mod launcher;
+mod livesplit;
+mod memory_diff;
+mod movie;
+mod nametable_viewer;
+mod netplay;
+mod overlay;
+mod profiler;
+mod ram_map;
+mod replay;
+mod retroachievements;
+mod screen;
+mod ui;
+mod watch;
+
+fn main() {
+    let args = match Args::parse(env::args()) {
+        Ok(args) => args,
+        Err(error) => {
+            println!("{}\n{}", error, USAGE);
+            std::process::exit(-1);
+        }
+    };
+
+    if let Some(frames) = args.bench_frames {
+        std::process::exit(bench::run(&args, frames));
+    }
+    if let Some(port) = args.gdb_port {
+        std::process::exit(gdb::run(&args, port));
+    }
+    if let Some(movie_path) = &args.replay {
+        std::process::exit(replay::run(&args, movie_path));
+    }
+    if args.headless {
+        std::process::exit(headless::run(&args));
+    }
+
+    // The logger has to be up before loading the config, so that problems with the config file get shown
+    egui_logger::init().unwrap();
+    let config = Config::load();
+    let native_options = eframe::NativeOptions {
+        vsync: config.video.vsync,
+        viewport: ViewportBuilder::default().with_inner_size([1024.0, 768.0]),
+        ..Default::default()
+    };
+    let result = eframe::run_native(
+        "NESEmu",
+        native_options,
+        Box::new(|cc| Box::new(Launcher::new(args, config, &cc.egui_ctx))),
+    );
+    if let Err(error) = result {
+        println!("Failed to start the window with error: {}", error);
+        std::process::exit(-1);
+    }
+}
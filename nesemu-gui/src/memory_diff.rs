@@ -0,0 +1,139 @@
+//! Debug window comparing a snapshot of the console's memory against its current memory, or against another
+//! snapshot loaded from a file, to help find which addresses a game keeps a piece of state at. See
+//! [`nesemu_core::snapshot`].
+
+use std::path::Path;
+
+use eframe::{
+    egui::{Context, Grid, ScrollArea, Window},
+    epaint::Color32,
+};
+
+use nesemu_core::{
+    snapshot::{MemoryRegion, MemorySnapshot},
+    NES,
+};
+
+pub struct MemoryDiffWindow {
+    baseline: Option<MemorySnapshot>,
+    /// Compared against the baseline instead of the current memory, when loaded
+    other: Option<MemorySnapshot>,
+    baseline_path: String,
+    other_path: String,
+    /// The outcome of the last file operation, and whether it succeeded
+    status: Option<(String, bool)>,
+}
+
+impl MemoryDiffWindow {
+    pub fn new() -> Self {
+        Self {
+            baseline: None,
+            other: None,
+            baseline_path: String::from("baseline.snap"),
+            other_path: String::new(),
+            status: None,
+        }
+    }
+
+    /// Draws the memory diff window, if it is open
+    pub fn render(&mut self, ctx: &Context, open: &mut bool, nes: &NES) {
+        if !*open {
+            return;
+        }
+
+        Window::new("Memory Diff").open(open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Baseline");
+                if ui.button("Take Snapshot").clicked() {
+                    self.baseline = Some(MemorySnapshot::capture(nes.bus()));
+                }
+                ui.text_edit_singleline(&mut self.baseline_path);
+                if ui.button("Save").clicked() {
+                    if let Some(baseline) = &self.baseline {
+                        self.status = Some(match baseline.save(Path::new(&self.baseline_path)) {
+                            Ok(()) => (format!("Saved to {}", self.baseline_path), true),
+                            Err(error) => (format!("Failed to save: {}", error), false),
+                        });
+                    }
+                }
+                if ui.button("Load").clicked() {
+                    match MemorySnapshot::load(Path::new(&self.baseline_path)) {
+                        Ok(snapshot) => {
+                            self.baseline = Some(snapshot);
+                            self.status = None;
+                        }
+                        Err(error) => {
+                            self.status = Some((format!("Failed to load: {}", error), false))
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Compare with");
+                if ui
+                    .selectable_label(self.other.is_none(), "Current Memory")
+                    .clicked()
+                {
+                    self.other = None;
+                }
+                ui.text_edit_singleline(&mut self.other_path);
+                if ui.button("Load").clicked() {
+                    match MemorySnapshot::load(Path::new(&self.other_path)) {
+                        Ok(snapshot) => {
+                            self.other = Some(snapshot);
+                            self.status = None;
+                        }
+                        Err(error) => {
+                            self.status = Some((format!("Failed to load: {}", error), false))
+                        }
+                    }
+                }
+            });
+            match &self.status {
+                Some((message, true)) => {
+                    ui.label(message);
+                }
+                Some((message, false)) => {
+                    ui.colored_label(Color32::RED, message);
+                }
+                None => {}
+            }
+
+            ui.separator();
+            let Some(baseline) = &self.baseline else {
+                ui.label("Take or load a snapshot to compare against");
+                return;
+            };
+            let current;
+            let other = match &self.other {
+                Some(other) => other,
+                None => {
+                    current = MemorySnapshot::capture(nes.bus());
+                    &current
+                }
+            };
+            let differences = baseline.diff(other);
+            ui.label(format!("{} bytes differ", differences.len()));
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                Grid::new("Memory Diff").striped(true).show(ui, |ui| {
+                    for header in ["Region", "Address", "Baseline", "Compared"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for difference in differences {
+                        ui.label(difference.region.to_string());
+                        ui.monospace(match difference.region {
+                            MemoryRegion::CpuRam => format!("${:04X}", difference.offset),
+                            MemoryRegion::PpuRam | MemoryRegion::Oam => {
+                                format!("${:03X}", difference.offset)
+                            }
+                        });
+                        ui.monospace(format!("${:02X} ({})", difference.old, difference.old));
+                        ui.monospace(format!("${:02X} ({})", difference.new, difference.new));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    }
+}
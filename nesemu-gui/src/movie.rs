@@ -0,0 +1,123 @@
+//! Input movies, which record the controller inputs of every frame from power on, so that a run can be replayed
+//! exactly. Emulation is deterministic, so replaying a movie always produces the same frames.
+//!
+//! Movies are text files with one line per frame, holding the state of the first and second controllers as two
+//! hex bytes. For example, `08 00` holds Start on the first controller. Lines starting with `#` are comments.
+//! Resets and power cycles aren't recorded, so a movie is only accurate for a session which didn't use them.
+//!
+//! FCEUX's text .fm2 movies can be read too, so that movies recorded there can be replayed. Only their input
+//! lines are used, and movies that reset or power cycle the console partway through are rejected.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Error, ErrorKind, Write},
+    path::Path,
+};
+
+/// Writes a movie as it is being recorded
+pub struct MovieWriter {
+    file: BufWriter<File>,
+}
+
+impl MovieWriter {
+    pub fn create(path: &str) -> Result<Self, Error> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "# NESEmu movie")?;
+        Ok(Self { file })
+    }
+
+    /// Records the controller inputs of the next frame
+    pub fn write_frame(&mut self, inputs: [u8; 2]) -> Result<(), Error> {
+        writeln!(self.file, "{:02X} {:02X}", inputs[0], inputs[1])?;
+        // Flush every frame, since the process may exit without dropping the writer
+        self.file.flush()
+    }
+}
+
+/// Reads the controller inputs of every frame of a movie, as an FCEUX movie if the path ends in .fm2
+pub fn read_movie(path: &str) -> Result<Vec<[u8; 2]>, Error> {
+    if Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("fm2"))
+    {
+        return read_fm2(path);
+    }
+    parse_lines(path, |line| {
+        let mut bytes = line
+            .split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16).ok());
+        match (bytes.next(), bytes.next(), bytes.next()) {
+            (Some(Some(first)), Some(Some(second)), None) => Some([first, second]),
+            _ => None,
+        }
+    })
+}
+
+/// Reads the controller inputs of every frame of an FCEUX movie
+///
+/// Input lines look like `|0|RLDUTSBA|........||`: a field of commands such as resets, then a field for each
+/// port with a character for each button, where anything other than '.' or a space means it is held
+fn read_fm2(path: &str) -> Result<Vec<[u8; 2]>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    let invalid = |idx: usize, reason: &str| {
+        let message = format!("{}:{}: {}", path, idx + 1, reason);
+        Error::new(ErrorKind::InvalidData, message)
+    };
+    let mut frames = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.trim() == "binary 1" {
+            return Err(invalid(idx, "binary movies aren't supported"));
+        }
+        // Every other line is a header field, such as the ROM's name
+        let Some(fields) = line.strip_prefix('|') else {
+            continue;
+        };
+        let mut fields = fields.split('|');
+        match fields.next().map(str::parse::<u8>) {
+            Some(Ok(0)) => {}
+            Some(Ok(_)) => return Err(invalid(idx, "resets and power cycles aren't supported")),
+            _ => return Err(invalid(idx, "invalid commands field")),
+        }
+        let mut inputs = [0u8; 2];
+        for (port, field) in inputs.iter_mut().zip(fields) {
+            // Buttons are written from Right down to A, the reverse of the order the controller reports them in
+            for (i, c) in field.chars().take(8).enumerate() {
+                if c != '.' && c != ' ' {
+                    *port |= 0x80 >> i;
+                }
+            }
+        }
+        frames.push(inputs);
+    }
+    Ok(frames)
+}
+
+/// Reads a list of frame hashes, such as a replay's baseline, which has a hex number on each line
+pub fn read_hashes(path: &str) -> Result<Vec<u64>, Error> {
+    parse_lines(path, |line| u64::from_str_radix(line, 16).ok())
+}
+
+pub fn write_hashes(path: &str, hashes: &[u64]) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    for hash in hashes {
+        writeln!(file, "{:016x}", hash)?;
+    }
+    file.flush()
+}
+
+/// Parses every line of a file other than blank lines and comments
+fn parse_lines<T>(path: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, Error> {
+    let text = std::fs::read_to_string(path)?;
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| {
+            parse(line).ok_or_else(|| {
+                let message = format!("{}:{}: invalid line '{}'", path, idx + 1, line);
+                Error::new(ErrorKind::InvalidData, message)
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+//! Debug window which displays the full 512x480 pixel space spanned by all four nametables, as seen through
+//! the cartridge's current mirroring mode. The area that is visible on screen is outlined, which makes it easy
+//! to see how a game is scrolling.
+
+use bitfield::{Bit, BitRange};
+use eframe::{
+    egui::{Context, Image, Rect, Stroke, TextureOptions, Vec2, Window},
+    epaint::{Color32, ColorImage, TextureHandle},
+};
+
+use nesemu_core::bus::Bus;
+
+use super::screen::to_color32;
+
+pub struct NametableViewer {
+    image: ColorImage,
+    texture: Option<TextureHandle>,
+}
+
+impl NametableViewer {
+    const WIDTH: usize = 512;
+    const HEIGHT: usize = 480;
+    const SCREEN_WIDTH: usize = 256;
+    const SCREEN_HEIGHT: usize = 240;
+
+    pub fn new() -> Self {
+        Self {
+            image: ColorImage::new(
+                [NametableViewer::WIDTH, NametableViewer::HEIGHT],
+                Color32::BLACK,
+            ),
+            texture: None,
+        }
+    }
+
+    /// Draws the nametable viewer window, if it is open
+    ///
+    /// `origin` is the pixel coordinate within the nametable space that is situated at the top-left corner of
+    /// the screen.
+    pub fn render(&mut self, ctx: &Context, open: &mut bool, bus: &Bus, origin: (usize, usize)) {
+        if !*open {
+            return;
+        }
+
+        self.draw_nametables(bus);
+        let texture = match &mut self.texture {
+            Some(texture) => {
+                texture.set(self.image.clone(), TextureOptions::NEAREST);
+                texture
+            }
+            None => self.texture.insert(ctx.load_texture(
+                "Nametables",
+                self.image.clone(),
+                TextureOptions::NEAREST,
+            )),
+        };
+
+        Window::new("Nametables").open(open).show(ctx, |ui| {
+            let response = ui.add(Image::new(&*texture).fit_to_exact_size(Vec2::new(
+                NametableViewer::WIDTH as f32,
+                NametableViewer::HEIGHT as f32,
+            )));
+            let painter = ui.painter_at(response.rect);
+            let scale = response.rect.width() / NametableViewer::WIDTH as f32;
+            let stroke = Stroke::new(2.0, Color32::RED);
+
+            // The viewport can wrap around the edges of the nametable space, in which case it needs to be
+            // drawn as up to four separate pieces
+            let x_spans = NametableViewer::wrapped_spans(
+                origin.0 % NametableViewer::WIDTH,
+                NametableViewer::SCREEN_WIDTH,
+                NametableViewer::WIDTH,
+            );
+            let y_spans = NametableViewer::wrapped_spans(
+                origin.1 % NametableViewer::HEIGHT,
+                NametableViewer::SCREEN_HEIGHT,
+                NametableViewer::HEIGHT,
+            );
+            for (x, width) in &x_spans {
+                for (y, height) in &y_spans {
+                    let min = response.rect.min + Vec2::new(*x as f32, *y as f32) * scale;
+                    let max = min + Vec2::new(*width as f32, *height as f32) * scale;
+                    painter.rect_stroke(Rect::from_min_max(min, max), 0.0, stroke);
+                }
+            }
+        });
+    }
+
+    /// Splits a span of `len` pixels starting at `start` into the pieces that remain after wrapping around at
+    /// `wrap`
+    fn wrapped_spans(start: usize, len: usize, wrap: usize) -> Vec<(usize, usize)> {
+        if start + len <= wrap {
+            vec![(start, len)]
+        } else {
+            vec![(start, wrap - start), (0, start + len - wrap)]
+        }
+    }
+
+    fn draw_nametables(&mut self, bus: &Bus) {
+        for nametable in 0..4 {
+            let base_addr = 0x2000 + nametable * 0x400;
+            let nametable_x = (nametable % 2) * NametableViewer::SCREEN_WIDTH;
+            let nametable_y = (nametable / 2) * NametableViewer::SCREEN_HEIGHT;
+
+            // Each nametable is 32x30 tiles, followed by the 64 byte attribute table
+            for coarse_y in 0..30 {
+                for coarse_x in 0..32 {
+                    let pt_idx = bus
+                        .ppu_read_nametable(base_addr + coarse_y * 32 + coarse_x)
+                        .unwrap_or(0);
+                    let attrib_val = bus
+                        .ppu_read_nametable(base_addr + 0x3C0 + (coarse_y / 4) * 8 + coarse_x / 4)
+                        .unwrap_or(0);
+                    // Each attribute byte holds the palettes for a 2x2 grid of 16x16 pixel quadrants
+                    let shift = ((coarse_y % 4) / 2) * 4 + ((coarse_x % 4) / 2) * 2;
+                    let palette_num: u8 = attrib_val.bit_range(shift + 1, shift);
+                    // Tiles the cartridge has no CHR memory for are shown as the background color
+                    let tile = bus.ppu_get_pattern_entry(pt_idx, true).unwrap_or(&[0; 16]);
+
+                    for fine_y in 0..8 {
+                        for fine_x in 0..8 {
+                            let bit_idx = 7 - fine_x;
+                            let low_bit = u8::from(tile[fine_y].bit(bit_idx));
+                            let high_bit = u8::from(tile[fine_y + 8].bit(bit_idx));
+                            let color = bus
+                                .palette_memory
+                                .get_color_by_idx(palette_num, low_bit + (high_bit << 1))
+                                .map_or(Color32::BLACK, to_color32);
+
+                            let x = nametable_x + coarse_x * 8 + fine_x;
+                            let y = nametable_y + coarse_y * 8 + fine_y;
+                            self.image.pixels[y * NametableViewer::WIDTH + x] = color;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
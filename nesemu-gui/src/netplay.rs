@@ -0,0 +1,209 @@
+//! Two player netplay over TCP.
+//!
+//! Netplay runs in lockstep: before every frame, each peer sends its local input to the other and waits to
+//! receive the remote input, so both consoles always run the same frame with the same inputs. Along with its
+//! input, each peer sends a hash of its console state, which the other compares against its own to detect
+//! when the two consoles have desynced. Since every frame waits on a round trip, this is best suited to
+//! local networks.
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{channel, Receiver},
+    thread,
+    time::Duration,
+};
+
+use eframe::egui::{Context, TextEdit, Window};
+use nesemu_core::NES;
+
+/// Sent by both peers when connecting, to make sure the other end is actually NESEmu
+const HANDSHAKE: &[u8; 8] = b"NESNET01";
+const DEFAULT_PORT: u16 = 7845;
+/// How long to wait for the remote input before giving up on the connection
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An established netplay connection
+pub struct Netplay {
+    stream: TcpStream,
+    host: bool,
+    frame: u32,
+}
+
+impl Netplay {
+    fn new(mut stream: TcpStream, host: bool) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.write_all(HANDSHAKE)?;
+        let mut handshake = [0u8; 8];
+        stream.read_exact(&mut handshake)?;
+        if &handshake != HANDSHAKE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Peer is not a NESEmu netplay session",
+            ));
+        }
+        Ok(Self {
+            stream,
+            host,
+            frame: 0,
+        })
+    }
+
+    /// Describes which controller the local player is using
+    pub fn player(&self) -> usize {
+        if self.host {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Exchanges inputs with the peer for the next frame, blocking until the remote input arrives
+    ///
+    /// `state_hash` must be the hash of the console state before the frame is run. Returns the inputs for the
+    /// first and second controllers, or an error if the connection failed or the consoles have desynced
+    pub fn exchange_inputs(&mut self, local_input: u8, state_hash: u64) -> Result<[u8; 2], String> {
+        let mut message = [0u8; 13];
+        message[0..4].copy_from_slice(&self.frame.to_le_bytes());
+        message[4] = local_input;
+        message[5..13].copy_from_slice(&state_hash.to_le_bytes());
+        self.stream
+            .write_all(&message)
+            .map_err(|error| error.to_string())?;
+
+        self.stream
+            .read_exact(&mut message)
+            .map_err(|error| error.to_string())?;
+        let remote_frame = u32::from_le_bytes(message[0..4].try_into().unwrap());
+        let remote_input = message[4];
+        let remote_hash = u64::from_le_bytes(message[5..13].try_into().unwrap());
+        if remote_frame != self.frame {
+            return Err(format!(
+                "Expected input for frame {} but received frame {}",
+                self.frame, remote_frame
+            ));
+        }
+        if remote_hash != state_hash {
+            return Err(format!("Desync detected at frame {}", self.frame));
+        }
+
+        self.frame += 1;
+        Ok(if self.host {
+            [local_input, remote_input]
+        } else {
+            [remote_input, local_input]
+        })
+    }
+}
+
+/// A change to the netplay session, to be passed on to the emulation thread
+pub enum SessionChange {
+    Started(Netplay),
+    Stopped,
+}
+
+/// The window used to host or join a netplay session
+pub struct NetplayWindow {
+    pub open: bool,
+    address: String,
+    port: String,
+    status: String,
+    pending: Option<Receiver<io::Result<Netplay>>>,
+}
+
+impl NetplayWindow {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            address: format!("127.0.0.1:{}", DEFAULT_PORT),
+            port: DEFAULT_PORT.to_string(),
+            status: String::new(),
+            pending: None,
+        }
+    }
+
+    /// Renders the window, returning a change to the session that the user asked for or that has just connected
+    ///
+    /// Both peers power cycle their console when the session starts so that emulation begins in the same state
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        nes: &mut NES,
+        connected: bool,
+    ) -> Option<SessionChange> {
+        let mut change = None;
+        if let Some(result) = self
+            .pending
+            .as_ref()
+            .and_then(|pending| pending.try_recv().ok())
+        {
+            self.pending = None;
+            match result.and_then(|netplay| nes.power_cycle().map(|_| netplay)) {
+                Ok(netplay) => {
+                    self.status = format!("Connected as player {}", netplay.player());
+                    change = Some(SessionChange::Started(netplay));
+                }
+                Err(error) => self.status = format!("Failed to connect: {}", error),
+            }
+        }
+
+        Window::new("Netplay").open(&mut self.open).show(ctx, |ui| {
+            let idle = !connected && self.pending.is_none();
+            ui.add_enabled_ui(idle, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Port");
+                    ui.add(TextEdit::singleline(&mut self.port).desired_width(60.0));
+                    if ui.button("Host").clicked() {
+                        match self.port.parse::<u16>() {
+                            Ok(port) => {
+                                self.status = format!("Waiting for player 2 on port {}", port);
+                                self.pending = Some(spawn(move || {
+                                    let (stream, _) =
+                                        TcpListener::bind(("0.0.0.0", port))?.accept()?;
+                                    Netplay::new(stream, true)
+                                }));
+                            }
+                            Err(_) => self.status = "Invalid port".to_string(),
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Address");
+                    ui.text_edit_singleline(&mut self.address);
+                    if ui.button("Join").clicked() {
+                        let address = self.address.clone();
+                        self.status = format!("Connecting to {}", address);
+                        self.pending = Some(spawn(move || {
+                            Netplay::new(TcpStream::connect(address)?, false)
+                        }));
+                    }
+                });
+            });
+            if connected && ui.button("Disconnect").clicked() {
+                change = Some(SessionChange::Stopped);
+                self.status = "Disconnected".to_string();
+            }
+            ui.label(&self.status);
+        });
+        change
+    }
+
+    /// Shows why the session ended in the window
+    pub fn session_ended(&mut self, reason: String) {
+        log::error!("Netplay session ended: {}", reason);
+        self.status = reason;
+    }
+}
+
+/// Establishes a connection in the background, since accepting or connecting can block for a long time
+fn spawn(
+    connect: impl FnOnce() -> io::Result<Netplay> + Send + 'static,
+) -> Receiver<io::Result<Netplay>> {
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        // The window may have been closed in the meantime, in which case nobody is listening
+        let _ = sender.send(connect());
+    });
+    receiver
+}
@@ -0,0 +1,217 @@
+//! Debug overlays that are drawn on top of the game image. Each overlay is drawn in screen space, so they
+//! must be provided with the rect that the game image occupies in order to scale from NES pixel coordinates.
+//!
+//! Tools that want to draw their own overlays, such as hitbox viewers or practice HUDs, describe them as a
+//! [`Drawing`] of shapes in NES pixel coordinates instead, which takes care of the scaling. Scripts driving the
+//! emulator through the control server draw with the same shapes, sent as JSON.
+
+use bitfield::Bit;
+use eframe::{
+    egui::{Align2, FontId, Painter, Pos2, Rect, Stroke, Vec2},
+    epaint::Color32,
+};
+use serde::Deserialize;
+
+const SCREEN_WIDTH: f32 = 256.0;
+const SCREEN_HEIGHT: f32 = 240.0;
+
+/// Draws the 8x8 tile grid and the 16x16 attribute quadrant grid, aligned to the current scroll position
+///
+/// `origin` is the pixel coordinate within the nametable space that is situated at the top-left corner of
+/// the screen.
+pub fn draw_tile_grid(painter: &Painter, rect: Rect, origin: (usize, usize)) {
+    let tile_stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 255, 255, 40));
+    let attrib_stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(255, 64, 64, 140));
+
+    draw_grid(painter, rect, origin, 8, tile_stroke);
+    draw_grid(painter, rect, origin, 16, attrib_stroke);
+}
+
+/// A shape to draw over the game image, with coordinates and sizes in NES pixels
+///
+/// Colors are RGBA, and default to opaque white
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Shape {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(default = "white")]
+        color: [u8; 4],
+        /// Whether to fill the rectangle rather than only drawing its outline
+        #[serde(default)]
+        filled: bool,
+    },
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        #[serde(default = "white")]
+        color: [u8; 4],
+    },
+    /// Text with its top left corner at the given position, in a font that is 8 pixels tall like the NES's
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        #[serde(default = "white")]
+        color: [u8; 4],
+    },
+}
+
+/// Draws a crosshair where the Zapper is aimed, given in NES pixel coordinates
+pub fn draw_crosshair(painter: &Painter, rect: Rect, aim: Vec2) {
+    let scale = rect.width() / SCREEN_WIDTH;
+    let center = rect.min + aim * scale;
+    let stroke = Stroke::new(2.0, Color32::RED);
+    let arm = 6.0 * scale;
+    painter.circle_stroke(center, arm * 0.6, stroke);
+    painter.line_segment([center - Vec2::X * arm, center + Vec2::X * arm], stroke);
+    painter.line_segment([center - Vec2::Y * arm, center + Vec2::Y * arm], stroke);
+}
+
+fn white() -> [u8; 4] {
+    [255; 4]
+}
+
+/// A list of shapes drawn over the game image, in the order they were added
+#[derive(Default)]
+pub struct Drawing {
+    shapes: Vec<Shape>,
+}
+
+impl Drawing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    /// Draws every shape, scaled to the rect that the game image occupies. Shapes are clipped to the painter
+    pub fn draw(&self, painter: &Painter, rect: Rect) {
+        let scale = rect.width() / SCREEN_WIDTH;
+        let to_screen = |x: f32, y: f32| rect.min + Vec2::new(x, y) * scale;
+        let to_color = |[r, g, b, a]: [u8; 4]| Color32::from_rgba_unmultiplied(r, g, b, a);
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                    filled,
+                } => {
+                    let shape_rect =
+                        Rect::from_min_size(to_screen(*x, *y), Vec2::new(*width, *height) * scale);
+                    if *filled {
+                        painter.rect_filled(shape_rect, 0.0, to_color(*color));
+                    } else {
+                        painter.rect_stroke(shape_rect, 0.0, Stroke::new(1.0, to_color(*color)));
+                    }
+                }
+                Shape::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                } => {
+                    painter.line_segment(
+                        [to_screen(*x1, *y1), to_screen(*x2, *y2)],
+                        Stroke::new(1.0, to_color(*color)),
+                    );
+                }
+                Shape::Text { x, y, text, color } => {
+                    painter.text(
+                        to_screen(*x, *y),
+                        Align2::LEFT_TOP,
+                        text,
+                        FontId::monospace(8.0 * scale),
+                        to_color(*color),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Adds a rectangle around every sprite in OAM to a drawing
+///
+/// Sprite 0 is drawn in yellow, sprites which are drawn behind the background are drawn in blue, and all other
+/// sprites are drawn in green. Sprites which have been hidden offscreen are skipped.
+pub fn add_sprite_boxes(drawing: &mut Drawing, oam: &[u8], tall_sprites: bool) {
+    let sprite_height = if tall_sprites { 16.0 } else { 8.0 };
+
+    for (i, sprite_data) in oam.chunks(4).enumerate() {
+        let y_coord = sprite_data[0] as f32;
+        let x_coord = sprite_data[3] as f32;
+        if y_coord >= SCREEN_HEIGHT - 1.0 {
+            continue; // Games hide unused sprites by moving them below the bottom of the screen
+        }
+
+        let color = if i == 0 {
+            Color32::YELLOW
+        } else if sprite_data[2].bit(5) {
+            Color32::LIGHT_BLUE
+        } else {
+            Color32::GREEN
+        };
+
+        drawing.add(Shape::Rect {
+            x: x_coord,
+            y: y_coord,
+            width: 8.0,
+            height: sprite_height,
+            color: color.to_array(),
+            filled: false,
+        });
+    }
+}
+
+fn draw_grid(
+    painter: &Painter,
+    rect: Rect,
+    origin: (usize, usize),
+    spacing: usize,
+    stroke: Stroke,
+) {
+    let scale = rect.width() / SCREEN_WIDTH;
+
+    // The first grid line appears wherever the scroll position next crosses a multiple of the grid spacing
+    let mut x = ((spacing - origin.0 % spacing) % spacing) as f32;
+    while x < SCREEN_WIDTH {
+        let screen_x = rect.left() + x * scale;
+        painter.line_segment(
+            [
+                Pos2::new(screen_x, rect.top()),
+                Pos2::new(screen_x, rect.bottom()),
+            ],
+            stroke,
+        );
+        x += spacing as f32;
+    }
+
+    let mut y = ((spacing - origin.1 % spacing) % spacing) as f32;
+    while y < SCREEN_HEIGHT {
+        let screen_y = rect.top() + y * scale;
+        painter.line_segment(
+            [
+                Pos2::new(rect.left(), screen_y),
+                Pos2::new(rect.right(), screen_y),
+            ],
+            stroke,
+        );
+        y += spacing as f32;
+    }
+}
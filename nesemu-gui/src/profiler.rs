@@ -0,0 +1,89 @@
+//! Debug window showing where the time goes each frame, averaged over the last second. Emulation is timed by
+//! the core's profiler on the emulation thread, while UI rendering and texture upload are timed on the UI
+//! thread. Each part is shown against the time budget of a single frame, which everything on its own thread
+//! has to fit within for the game to run at full speed.
+
+use std::{collections::VecDeque, time::Duration};
+
+use eframe::egui::{Context, Grid, ProgressBar, Window};
+
+use nesemu_core::{profiler::Timings, NES};
+
+/// How many frames to average over
+const HISTORY: usize = 60;
+
+pub struct ProfilerWindow {
+    emulation: VecDeque<Timings>,
+    ui: VecDeque<Duration>,
+    upload: VecDeque<Duration>,
+}
+
+impl ProfilerWindow {
+    pub fn new() -> Self {
+        Self {
+            emulation: VecDeque::with_capacity(HISTORY),
+            ui: VecDeque::with_capacity(HISTORY),
+            upload: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    /// Records the time the emulation thread spent on a frame
+    pub fn add_emulation(&mut self, timings: Timings) {
+        push(&mut self.emulation, timings);
+    }
+
+    /// Records the time spent rendering the UI for a frame
+    pub fn add_ui(&mut self, time: Duration) {
+        push(&mut self.ui, time);
+    }
+
+    /// Records the time spent converting a frame and handing it to egui for upload to the GPU
+    pub fn add_upload(&mut self, time: Duration) {
+        push(&mut self.upload, time);
+    }
+
+    /// Draws the profiler window, if it is open
+    pub fn render(&mut self, ctx: &Context, open: &mut bool) {
+        if !*open {
+            return;
+        }
+
+        let emulation = |part: fn(&Timings) -> Duration| average(self.emulation.iter().map(part));
+        let rows = [
+            ("CPU", emulation(|timings| timings.cpu)),
+            ("PPU", emulation(|timings| timings.ppu)),
+            ("DMA", emulation(|timings| timings.dma)),
+            ("UI", average(self.ui.iter().copied())),
+            ("Texture upload", average(self.upload.iter().copied())),
+        ];
+        let budget = Duration::from_secs_f64(NES::FRAME_TIME);
+
+        Window::new("Profiler").open(open).show(ctx, |ui| {
+            Grid::new("Timings").show(ui, |ui| {
+                for (name, time) in rows {
+                    ui.label(name);
+                    ui.label(format!("{:.2} ms", time.as_secs_f64() * 1000.0));
+                    let fraction = time.as_secs_f32() / budget.as_secs_f32();
+                    ui.add(ProgressBar::new(fraction).desired_width(150.0));
+                    ui.end_row();
+                }
+            });
+            ui.label(format!(
+                "Frame budget: {:.2} ms",
+                budget.as_secs_f64() * 1000.0
+            ));
+        });
+    }
+}
+
+fn push<T>(history: &mut VecDeque<T>, value: T) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn average(times: impl ExactSizeIterator<Item = Duration>) -> Duration {
+    let count = times.len().max(1) as u32;
+    times.sum::<Duration>() / count
+}
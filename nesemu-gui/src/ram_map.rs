@@ -0,0 +1,266 @@
+//! Importing and exporting RAM maps, and the window for editing them. See [`nesemu_core::ram_map`] for how
+//! they are used.
+//!
+//! RAM maps are written as TOML, or as JSON if the file ends in .json, with a list of entries:
+//!
+//! ```toml
+//! [[entries]]
+//! address = "$0300"
+//! name = "player_x"
+//! type = "u8"
+//! description = "Horizontal position of the player on screen"
+//! ```
+//!
+//! Addresses can also be given as plain numbers. A RAM map next to the ROM, with the same name and a .ram.toml
+//! or .ram.json extension, is imported when the ROM is loaded.
+
+use std::{
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use eframe::{
+    egui::{ComboBox, Context, Grid, TextEdit, Window},
+    epaint::Color32,
+};
+use serde::{Deserialize, Serialize};
+
+use nesemu_core::{
+    ram_map::{RamEntry, RamMap, ValueType},
+    NES,
+};
+
+use super::args::parse_address;
+
+#[derive(Serialize, Deserialize)]
+struct RamMapFile {
+    #[serde(default)]
+    entries: Vec<EntryFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryFile {
+    address: AddressField,
+    name: String,
+    #[serde(rename = "type", default = "default_type")]
+    value_type: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Addresses are exported as hex strings, which are easier to read, but plain numbers are accepted too
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum AddressField {
+    Number(u16),
+    Text(String),
+}
+
+fn default_type() -> String {
+    ValueType::U8.name().to_string()
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Reads a RAM map from a file
+///
+/// Fails if the file can't be read or parsed, or has an entry with a bad address, type or name
+pub fn import(path: &Path) -> io::Result<RamMap> {
+    let text = std::fs::read_to_string(path)?;
+    let invalid = |error: String| {
+        let message = format!("{}: {}", path.display(), error);
+        io::Error::new(ErrorKind::InvalidData, message)
+    };
+    let file: RamMapFile = if is_json(path) {
+        serde_json::from_str(&text).map_err(|error| invalid(error.to_string()))?
+    } else {
+        toml::from_str(&text).map_err(|error| invalid(error.to_string()))?
+    };
+
+    let mut map = RamMap::new();
+    for entry in file.entries {
+        let address = match entry.address {
+            AddressField::Number(address) => address,
+            AddressField::Text(text) => parse_address(&text).map_err(invalid)? as u16,
+        };
+        let value_type = ValueType::from_name(&entry.value_type)
+            .map_err(|error| invalid(format!("{}: {}", entry.name, error)))?;
+        map.add(RamEntry {
+            address,
+            value_type,
+            description: entry.description,
+            name: entry.name.clone(),
+        })
+        .map_err(|error| invalid(format!("{}: {}", entry.name, error)))?;
+    }
+    Ok(map)
+}
+
+/// Writes a RAM map to a file
+pub fn export(map: &RamMap, path: &Path) -> io::Result<()> {
+    let file = RamMapFile {
+        entries: map
+            .entries()
+            .iter()
+            .map(|entry| EntryFile {
+                address: AddressField::Text(format!("${:04X}", entry.address)),
+                name: entry.name.clone(),
+                value_type: entry.value_type.name().to_string(),
+                description: entry.description.clone(),
+            })
+            .collect(),
+    };
+    let text = if is_json(path) {
+        serde_json::to_string_pretty(&file).map_err(io::Error::other)?
+    } else {
+        toml::to_string_pretty(&file).map_err(io::Error::other)?
+    };
+    std::fs::write(path, text)
+}
+
+/// Finds the RAM map next to a ROM, if there is one
+pub fn find_ram_map(rom_path: &str) -> Option<PathBuf> {
+    ["ram.toml", "ram.json"]
+        .into_iter()
+        .map(|extension| Path::new(rom_path).with_extension(extension))
+        .find(|path| path.exists())
+}
+
+pub struct RamMapWindow {
+    /// The ROM the file path was suggested for, so that a new one is suggested when another ROM is loaded
+    rom_path: String,
+    file_path: String,
+    new_address: String,
+    new_name: String,
+    new_type: ValueType,
+    new_description: String,
+    /// The outcome of the last change, and whether it succeeded
+    status: Option<(String, bool)>,
+}
+
+impl RamMapWindow {
+    pub fn new() -> Self {
+        Self {
+            rom_path: String::new(),
+            file_path: String::new(),
+            new_address: String::new(),
+            new_name: String::new(),
+            new_type: ValueType::U8,
+            new_description: String::new(),
+            status: None,
+        }
+    }
+
+    /// Draws the RAM map window, if it is open
+    pub fn render(&mut self, ctx: &Context, open: &mut bool, nes: &mut NES) {
+        if self.rom_path != nes.rom_path() {
+            self.rom_path = nes.rom_path().to_string();
+            self.file_path = Path::new(nes.rom_path())
+                .with_extension("ram.toml")
+                .to_string_lossy()
+                .into_owned();
+        }
+        if !*open {
+            return;
+        }
+
+        Window::new("RAM Map").open(open).show(ctx, |ui| {
+            let bus = nes.bus_mut();
+            let mut removed = None;
+            Grid::new("RAM Map").striped(true).show(ui, |ui| {
+                for header in ["Address", "Name", "Type", "Value", "Description", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (i, entry) in bus.ram_map.clone().entries().iter().enumerate() {
+                    ui.monospace(format!("${:04X}", entry.address));
+                    ui.monospace(&entry.name);
+                    ui.label(entry.value_type.name());
+                    match entry.read(bus) {
+                        Ok(value) => ui.monospace(value.to_string()),
+                        Err(error) => ui.colored_label(Color32::RED, error),
+                    };
+                    ui.label(&entry.description);
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = removed {
+                bus.ram_map.remove(i);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.new_address).desired_width(50.0))
+                    .on_hover_text("Address, in hex");
+                ui.add(TextEdit::singleline(&mut self.new_name).desired_width(100.0))
+                    .on_hover_text("Name, for use in watch expressions");
+                ComboBox::from_id_source("RAM Map Type")
+                    .selected_text(self.new_type.name())
+                    .show_ui(ui, |ui| {
+                        for value_type in ValueType::ALL {
+                            ui.selectable_value(&mut self.new_type, value_type, value_type.name());
+                        }
+                    });
+                ui.text_edit_singleline(&mut self.new_description)
+                    .on_hover_text("Description");
+                if ui.button("Add").clicked() {
+                    let result = parse_address(&self.new_address).and_then(|address| {
+                        let entry = RamEntry {
+                            address: address as u16,
+                            name: self.new_name.trim().to_string(),
+                            value_type: self.new_type,
+                            description: self.new_description.trim().to_string(),
+                        };
+                        bus.ram_map.add(entry).map_err(str::to_string)
+                    });
+                    match result {
+                        Ok(()) => {
+                            self.new_address.clear();
+                            self.new_name.clear();
+                            self.new_description.clear();
+                            self.status = None;
+                        }
+                        Err(error) => self.status = Some((error, false)),
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.file_path)
+                    .on_hover_text("Files ending in .json are JSON, and others are TOML");
+                let path = Path::new(&self.file_path);
+                if ui.button("Import").clicked() {
+                    self.status = Some(match import(path) {
+                        Ok(map) => {
+                            bus.ram_map = map;
+                            (format!("Imported from {}", self.file_path), true)
+                        }
+                        Err(error) => (format!("Failed to import: {}", error), false),
+                    });
+                }
+                if ui.button("Export").clicked() {
+                    self.status = Some(match export(&bus.ram_map, path) {
+                        Ok(()) => (format!("Exported to {}", self.file_path), true),
+                        Err(error) => (format!("Failed to export: {}", error), false),
+                    });
+                }
+            });
+            match &self.status {
+                Some((message, true)) => {
+                    ui.label(message);
+                }
+                Some((message, false)) => {
+                    ui.colored_label(Color32::RED, message);
+                }
+                None => {}
+            }
+        });
+    }
+}
@@ -0,0 +1,199 @@
+//! Replays an input movie without a window, checking that emulation still produces exactly the same frames.
+//!
+//! A hash of the image and console state at the end of every frame is compared against a baseline recorded from
+//! an earlier replay, and the first frame that differs is reported. This makes any recorded play session into a
+//! regression test for the emulator.
+//!
+//! The movie can also be replayed on a second ROM in lockstep with the first, such as the same game with and
+//! without a patch applied. The first frame where the two instances differ is reported along with what differs
+//! between them: the image, the CPU registers and the contents of memory.
+
+use nesemu_core::{
+    controller::InputEvent,
+    screen::{Frame, SCREEN_WIDTH},
+    snapshot::{MemoryRegion, MemorySnapshot},
+    NES,
+};
+use tock_registers::interfaces::Readable;
+
+use super::{
+    args::{load_nes, Args},
+    headless::{EXIT_EMULATION_ERROR, EXIT_LOAD_ERROR, EXIT_SUCCESS},
+    movie::{read_hashes, read_movie, write_hashes},
+};
+
+/// Some frame of the replay didn't match the baseline, or the two instances being compared differed
+pub const EXIT_DIVERGED: i32 = 4;
+
+/// The most differing bytes of memory listed when two instances diverge
+const MAX_LISTED_DIFFERENCES: usize = 32;
+
+/// Replays the movie at the given path according to the command line options, returning the exit status code
+pub fn run(args: &Args, movie_path: &str) -> i32 {
+    let loaded = args.load_nes().and_then(|nes| {
+        let movie = read_movie(movie_path)?;
+        let baseline = args.baseline.as_deref().map(read_hashes).transpose()?;
+        let other = args
+            .compare
+            .clone()
+            .map(|rom_path| load_nes(rom_path, None))
+            .transpose()?;
+        Ok((nes, movie, baseline, other))
+    });
+    let (mut nes, movie, baseline, mut other) = match loaded {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("Failed to load replay with error: {}", error);
+            return EXIT_LOAD_ERROR;
+        }
+    };
+
+    let mut frame = Frame::new();
+    let mut other_frame = Frame::new();
+    let mut hashes = Vec::with_capacity(movie.len());
+    for inputs in &movie {
+        // Breakpoints are never set during a replay, so a frame always runs to completion unless it fails
+        if let Err(error) = run_frame(&mut nes, &mut frame, *inputs) {
+            eprintln!(
+                "Emulation failed after {} frames with error: {}",
+                hashes.len(),
+                error
+            );
+            return EXIT_EMULATION_ERROR;
+        }
+
+        let hash = nes.frame_hash(&frame);
+        if let Some(expected) = baseline
+            .as_ref()
+            .and_then(|baseline| baseline.get(hashes.len()))
+        {
+            if hash != *expected {
+                println!(
+                    "Frame {} diverged from the baseline: expected {:016x}, got {:016x}",
+                    hashes.len(),
+                    expected,
+                    hash
+                );
+                return EXIT_DIVERGED;
+            }
+        }
+        if let Some(other) = &mut other {
+            if let Err(error) = run_frame(other, &mut other_frame, *inputs) {
+                eprintln!(
+                    "Emulation of the compared ROM failed after {} frames with error: {}",
+                    hashes.len(),
+                    error
+                );
+                return EXIT_EMULATION_ERROR;
+            }
+            if other.frame_hash(&other_frame) != hash {
+                println!(
+                    "Frame {} diverged between {} and {}",
+                    hashes.len(),
+                    nes.rom_path(),
+                    other.rom_path()
+                );
+                report_divergence((&nes, &frame), (other, &other_frame));
+                return EXIT_DIVERGED;
+            }
+        }
+        hashes.push(hash);
+    }
+
+    if let Some(path) = &args.baseline_out {
+        if let Err(error) = write_hashes(path, &hashes) {
+            eprintln!("Failed to write baseline with error: {}", error);
+            return EXIT_EMULATION_ERROR;
+        }
+    }
+    match baseline {
+        Some(baseline) if baseline.len() != hashes.len() => {
+            println!(
+                "Replayed {} frames, but the baseline has {} frames",
+                hashes.len(),
+                baseline.len()
+            );
+            EXIT_DIVERGED
+        }
+        Some(_) => {
+            println!("Replayed {} frames matching the baseline", hashes.len());
+            EXIT_SUCCESS
+        }
+        None if other.is_some() => {
+            println!("Replayed {} frames matching on both ROMs", hashes.len());
+            EXIT_SUCCESS
+        }
+        None => {
+            println!("Replayed {} frames", hashes.len());
+            EXIT_SUCCESS
+        }
+    }
+}
+
+fn run_frame(nes: &mut NES, frame: &mut Frame, inputs: [u8; 2]) -> Result<(), &'static str> {
+    let bus = nes.bus_mut();
+    bus.controller.set_state_from_window(InputEvent {
+        input_state: inputs[0],
+    });
+    bus.controller_two.set_state_from_window(InputEvent {
+        input_state: inputs[1],
+    });
+    nes.run_frame(frame).map(|_| ())
+}
+
+/// Prints everything that differs between two instances at the end of a frame
+fn report_divergence((nes, frame): (&NES, &Frame), (other, other_frame): (&NES, &Frame)) {
+    let mut differing_pixels = 0;
+    let (mut min, mut max) = ((usize::MAX, usize::MAX), (0, 0));
+    for (i, (pixel, other_pixel)) in frame.pixels.iter().zip(&other_frame.pixels).enumerate() {
+        if pixel != other_pixel {
+            let (x, y) = (i % SCREEN_WIDTH, i / SCREEN_WIDTH);
+            differing_pixels += 1;
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    if differing_pixels > 0 {
+        println!(
+            "  Image: {} pixels differ, between ({}, {}) and ({}, {})",
+            differing_pixels, min.0, min.1, max.0, max.1
+        );
+    }
+
+    let describe = |nes: &NES| {
+        let registers = nes.cpu().registers();
+        format!(
+            "PC:{:04X} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
+            registers.program_counter,
+            registers.accumulator,
+            registers.x_reg,
+            registers.y_reg,
+            registers.status_register.get(),
+            registers.stack_ptr
+        )
+    };
+    let (registers, other_registers) = (describe(nes), describe(other));
+    if registers != other_registers {
+        println!("  CPU:   {}", registers);
+        println!("         {}", other_registers);
+    }
+
+    let differences =
+        MemorySnapshot::capture(nes.bus()).diff(&MemorySnapshot::capture(other.bus()));
+    for difference in differences.iter().take(MAX_LISTED_DIFFERENCES) {
+        let address = match difference.region {
+            MemoryRegion::CpuRam => format!("${:04X}", difference.offset),
+            MemoryRegion::PpuRam | MemoryRegion::Oam => format!("${:03X}", difference.offset),
+        };
+        println!(
+            "  {} {}: {:02X} vs {:02X}",
+            difference.region, address, difference.old, difference.new
+        );
+    }
+    if differences.len() > MAX_LISTED_DIFFERENCES {
+        println!(
+            "  ...and {} more bytes of memory",
+            differences.len() - MAX_LISTED_DIFFERENCES
+        );
+    }
+}
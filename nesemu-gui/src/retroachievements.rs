@@ -0,0 +1,349 @@
+//! A client for the RetroAchievements web API, along with the window used to log in and browse the loaded
+//! game's achievements.
+//!
+//! Logging in exchanges the password for a token, which is saved in the config file in place of the password.
+//! Whenever a ROM is loaded, it is identified by the MD5 hash of its contents without the iNES header, and the
+//! game's achievements are fetched. Those not already unlocked are handed to the emulation thread, which
+//! evaluates them every frame. Unlocks are shown as toasts and reported back to the server.
+//!
+//! Only softcore unlocks are reported, since hardcore mode would require disabling save states, cheats and
+//! debugging tools. Note that the server may reject unlocks from emulators it doesn't recognize.
+
+use std::{
+    io,
+    sync::mpsc::{channel, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::{
+    egui::{Align2, Area, Button, Context, Frame, Grid, Order, TextEdit, Window},
+    epaint::Color32,
+};
+use nesemu_core::archive;
+use serde_json::Value;
+
+use super::{achievements::AchievementRuntime, config::AchievementsConfig};
+
+const API_URL: &str = "https://retroachievements.org/dorequest.php";
+const USER_AGENT: &str = concat!("NESEmu/", env!("CARGO_PKG_VERSION"));
+/// How long each unlock toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// Achievements flagged as unofficial are still being worked on, so only core achievements are loaded
+const CORE_FLAG: u64 = 3;
+
+/// Sends a request to the API, returning the response if it succeeded
+fn request(params: &[(&str, &str)]) -> Result<Value, String> {
+    let response: Value = ureq::post(API_URL)
+        .set("User-Agent", USER_AGENT)
+        .send_form(params)
+        .map_err(|error| error.to_string())?
+        .into_json()
+        .map_err(|error| error.to_string())?;
+    if response["Success"].as_bool() != Some(true) {
+        let error = response["Error"].as_str().unwrap_or("Request failed");
+        return Err(error.to_string());
+    }
+    Ok(response)
+}
+
+/// Logs in with a password, returning the token to use for all further requests
+fn login(username: &str, password: &str) -> Result<String, String> {
+    let response = request(&[("r", "login"), ("u", username), ("p", password)])?;
+    response["Token"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Login response is missing the token".to_string())
+}
+
+/// An achievement of the loaded game
+struct Achievement {
+    id: u32,
+    title: String,
+    description: String,
+    points: u32,
+    unlocked: bool,
+}
+
+struct Game {
+    title: String,
+    achievements: Vec<Achievement>,
+    /// The game's achievements which are still locked, ready to be evaluated
+    runtime: AchievementRuntime,
+}
+
+/// Identifies a ROM by its hash, then fetches its achievements and which of them the user has unlocked
+fn fetch_game(username: &str, token: &str, rom_hash: &str) -> Result<Game, String> {
+    let response = request(&[("r", "gameid"), ("m", rom_hash)])?;
+    let game_id = response["GameID"].as_u64().unwrap_or(0).to_string();
+    if game_id == "0" {
+        return Err("This ROM isn't recognized by RetroAchievements".to_string());
+    }
+    let auth = [("u", username), ("t", token), ("g", game_id.as_str())];
+    let patch = request(&[&[("r", "patch")], &auth[..]].concat())?;
+    let unlocks = request(&[&[("r", "unlocks"), ("h", "0")], &auth[..]].concat())?;
+    let unlocked: Vec<u64> = unlocks["UserUnlocks"]
+        .as_array()
+        .map(|ids| ids.iter().filter_map(Value::as_u64).collect())
+        .unwrap_or_default();
+
+    let data = &patch["PatchData"];
+    let mut game = Game {
+        title: data["Title"].as_str().unwrap_or_default().to_string(),
+        achievements: Vec::new(),
+        runtime: AchievementRuntime::new(),
+    };
+    let definitions = data["Achievements"].as_array().cloned().unwrap_or_default();
+    for definition in definitions {
+        if definition["Flags"].as_u64() != Some(CORE_FLAG) {
+            continue;
+        }
+        let id = definition["ID"].as_u64().unwrap_or_default();
+        let mut achievement = Achievement {
+            id: id as u32,
+            title: definition["Title"].as_str().unwrap_or_default().to_string(),
+            description: definition["Description"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            points: definition["Points"].as_u64().unwrap_or_default() as u32,
+            unlocked: unlocked.contains(&id),
+        };
+        if !achievement.unlocked {
+            let trigger = definition["MemAddr"].as_str().unwrap_or_default();
+            if let Err(error) = game.runtime.add(achievement.id, trigger) {
+                log::warn!("Skipping achievement '{}': {}", achievement.title, error);
+                // Show it as locked for good, since it can't be evaluated
+                achievement.description = format!("(Unsupported) {}", achievement.description);
+            }
+        }
+        game.achievements.push(achievement);
+    }
+    Ok(game)
+}
+
+/// Reports an unlock to the server
+fn award(username: &str, token: &str, id: u32) -> Result<(), String> {
+    let id = id.to_string();
+    // The server checks that the request came from the user it claims to be from with this signature
+    let signature = format!("{:x}", md5::compute(format!("{}{}0", id, username)));
+    request(&[
+        ("r", "awardachievement"),
+        ("u", username),
+        ("t", token),
+        ("a", &id),
+        ("h", "0"),
+        ("v", &signature),
+    ])
+    .map(|_| ())
+}
+
+/// Identifies a ROM the same way RetroAchievements does, by the MD5 hash of its contents after the iNES header
+fn rom_hash(rom_path: &str) -> io::Result<String> {
+    let rom = archive::read_rom(rom_path)?;
+    let contents = rom.get(16..).unwrap_or_default();
+    Ok(format!("{:x}", md5::compute(contents)))
+}
+
+/// The result of a request made in the background
+enum Response {
+    LoggedIn(Result<String, String>),
+    GameLoaded(Result<Game, String>),
+}
+
+/// The window used to log in and view the loaded game's achievements, which also shows toasts for unlocks
+pub struct AchievementsWindow {
+    pub open: bool,
+    password: String,
+    status: String,
+    game: Option<Game>,
+    /// The hash of the loaded ROM, once it has been read
+    rom_hash: Option<String>,
+    pending: Option<Receiver<Response>>,
+    toasts: Vec<(String, Instant)>,
+}
+
+impl AchievementsWindow {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            password: String::new(),
+            status: String::new(),
+            game: None,
+            rom_hash: None,
+            pending: None,
+            toasts: Vec::new(),
+        }
+    }
+
+    /// Fetches the achievements for a newly loaded ROM, throwing away those of the previous game
+    pub fn load_game(&mut self, rom_path: &str, config: &AchievementsConfig) {
+        self.game = None;
+        self.rom_hash = match rom_hash(rom_path) {
+            Ok(hash) => Some(hash),
+            Err(error) => {
+                self.status = format!("Failed to hash ROM: {}", error);
+                None
+            }
+        };
+        self.fetch_game(config);
+    }
+
+    fn fetch_game(&mut self, config: &AchievementsConfig) {
+        let Some(rom_hash) = self.rom_hash.clone() else {
+            return;
+        };
+        if config.token.is_empty() {
+            return;
+        }
+        self.status = "Loading achievements...".to_string();
+        let (username, token) = (config.username.clone(), config.token.clone());
+        self.pending = Some(spawn(move || {
+            Response::GameLoaded(fetch_game(&username, &token, &rom_hash))
+        }));
+    }
+
+    /// Marks an achievement as unlocked by the emulation thread, showing a toast and reporting it to the server
+    pub fn unlock(&mut self, id: u32, config: &AchievementsConfig) {
+        let Some(achievement) = self.game.as_mut().and_then(|game| {
+            game.achievements
+                .iter_mut()
+                .find(|achievement| achievement.id == id)
+        }) else {
+            return;
+        };
+        achievement.unlocked = true;
+        log::info!("Achievement unlocked: {}", achievement.title);
+        self.toasts.push((
+            format!(
+                "Achievement unlocked: {} ({} points)\n{}",
+                achievement.title, achievement.points, achievement.description
+            ),
+            Instant::now(),
+        ));
+
+        let (username, token) = (config.username.clone(), config.token.clone());
+        thread::spawn(move || {
+            if let Err(error) = award(&username, &token, id) {
+                log::error!("Failed to report achievement unlock: {}", error);
+            }
+        });
+    }
+
+    /// Renders the window and any toasts, returning the achievements the emulation thread should now evaluate,
+    /// if they have changed
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        config: &mut AchievementsConfig,
+    ) -> Option<AchievementRuntime> {
+        let mut change = None;
+        if let Some(response) = self
+            .pending
+            .as_ref()
+            .and_then(|pending| pending.try_recv().ok())
+        {
+            self.pending = None;
+            match response {
+                Response::LoggedIn(Ok(token)) => {
+                    config.token = token;
+                    self.password.clear();
+                    self.status = format!("Logged in as {}", config.username);
+                    self.fetch_game(config);
+                }
+                Response::LoggedIn(Err(error)) => self.status = format!("Login failed: {}", error),
+                Response::GameLoaded(Ok(mut game)) => {
+                    self.status = format!("Loaded achievements for {}", game.title);
+                    change = Some(std::mem::replace(
+                        &mut game.runtime,
+                        AchievementRuntime::new(),
+                    ));
+                    self.game = Some(game);
+                }
+                Response::GameLoaded(Err(error)) => {
+                    self.status = format!("Failed to load achievements: {}", error)
+                }
+            }
+        }
+
+        Window::new("Achievements")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                let idle = self.pending.is_none();
+                if config.token.is_empty() {
+                    ui.add_enabled_ui(idle, |ui| {
+                        Grid::new("Login").show(ui, |ui| {
+                            ui.label("Username");
+                            ui.text_edit_singleline(&mut config.username);
+                            ui.end_row();
+                            ui.label("Password");
+                            ui.add(TextEdit::singleline(&mut self.password).password(true));
+                            ui.end_row();
+                        });
+                        if ui.button("Log In").clicked() {
+                            let (username, password) =
+                                (config.username.clone(), self.password.clone());
+                            self.status = "Logging in...".to_string();
+                            self.pending = Some(spawn(move || {
+                                Response::LoggedIn(login(&username, &password))
+                            }));
+                        }
+                    });
+                } else if ui.add_enabled(idle, Button::new("Log Out")).clicked() {
+                    config.token.clear();
+                    self.game = None;
+                    // Stop evaluating the achievements too
+                    change = Some(AchievementRuntime::new());
+                    self.status = "Logged out".to_string();
+                }
+                ui.label(&self.status);
+
+                if let Some(game) = &self.game {
+                    ui.separator();
+                    Grid::new("Achievements").striped(true).show(ui, |ui| {
+                        for achievement in &game.achievements {
+                            let color = if achievement.unlocked {
+                                Color32::GOLD
+                            } else {
+                                Color32::GRAY
+                            };
+                            ui.colored_label(color, &achievement.title);
+                            ui.label(&achievement.description);
+                            ui.label(format!("{} points", achievement.points));
+                            ui.end_row();
+                        }
+                    });
+                }
+            });
+
+        self.render_toasts(ctx);
+        change
+    }
+
+    fn render_toasts(&mut self, ctx: &Context) {
+        self.toasts
+            .retain(|(_, shown)| shown.elapsed() < TOAST_DURATION);
+        for (idx, (text, _)) in self.toasts.iter().enumerate() {
+            Area::new(format!("Toast {}", idx))
+                .order(Order::Foreground)
+                .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0 - 60.0 * idx as f32])
+                .show(ctx, |ui| {
+                    Frame::popup(ui.style()).show(ui, |ui| ui.label(text));
+                });
+        }
+        if !self.toasts.is_empty() {
+            // Keep repainting so that toasts disappear on time, even while nothing else is happening
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Makes a request in the background, since requests can take a long time
+fn spawn(request: impl FnOnce() -> Response + Send + 'static) -> Receiver<Response> {
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        // The window may have been closed in the meantime, in which case nobody is listening
+        let _ = sender.send(request());
+    });
+    receiver
+}
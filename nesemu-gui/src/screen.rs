@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use eframe::{
+    egui::{Context, TextureOptions},
+    epaint::{Color32, ColorImage, TextureHandle},
+};
+
+use nesemu_core::screen::{Color, Frame, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+pub struct Screen {
+    /// The last frame shown. It is shared with egui until egui has uploaded it to the GPU, after which it is
+    /// ours alone again, and the next frame can be written into it without allocating
+    pub image: Arc<ColorImage>,
+    pub texture: TextureHandle,
+    pub texture_options: TextureOptions,
+}
+
+impl Screen {
+    pub fn new(ctx: Context) -> Self {
+        let image = Arc::new(ColorImage::new(
+            [SCREEN_WIDTH, SCREEN_HEIGHT],
+            Color32::BLACK,
+        ));
+        let texture = ctx.load_texture("Screen", image.clone(), TextureOptions::default());
+        Self {
+            image,
+            texture,
+            texture_options: TextureOptions::default(),
+        }
+    }
+
+    /// Presents a frame completed by the emulation thread
+    pub fn show_frame(&mut self, frame: &Frame) {
+        // Only copies the image if egui is somehow still holding on to the previous frame
+        let image = Arc::make_mut(&mut self.image);
+        for (pixel, color) in image.pixels.iter_mut().zip(&frame.pixels) {
+            *pixel = to_color32(*color);
+        }
+        self.texture.set(self.image.clone(), self.texture_options)
+    }
+}
+
+pub fn to_color32(color: Color) -> Color32 {
+    Color32::from_rgb(color.r, color.g, color.b)
+}
@@ -0,0 +1,525 @@
+use std::path::Path;
+
+use eframe::{
+    egui::{menu, Context, Grid, Key, Slider, TextEdit, TopBottomPanel, Window},
+    epaint::Color32,
+};
+use egui_memory_editor::MemoryEditor;
+
+use nesemu_core::{archive, cheats::CheatEngine, ram_map::RamEntry, rom_db::DumpStatus, NES};
+
+use super::{
+    achievements::AchievementRuntime,
+    config::Config,
+    coverage::CoverageWindow,
+    disassembly::DisassemblyWindow,
+    memory_diff::MemoryDiffWindow,
+    nametable_viewer::NametableViewer,
+    netplay::{NetplayWindow, SessionChange},
+    profiler::ProfilerWindow,
+    ram_map::RamMapWindow,
+    retroachievements::AchievementsWindow,
+    watch::WatchWindow,
+};
+
+struct ArchiveChoice {
+    archive_path: String,
+    patch_path: Option<String>,
+    roms: Vec<String>,
+}
+
+pub struct UI {
+    mem_editor: MemoryEditor,
+    /// The RAM map entries the memory editor has address ranges for
+    annotated_entries: Vec<RamEntry>,
+    pub config: Config,
+    saved_config: Config,
+    settings_open: bool,
+    rebinding: Option<usize>,
+    open_rom_open: bool,
+    rom_info_open: bool,
+    open_rom_path: String,
+    open_patch_path: String,
+    /// A ROM the user asked to switch to, along with the patch to apply to it, waiting to be loaded by the app
+    pub requested_rom: Option<(String, Option<String>)>,
+    /// An archive holding several ROMs, waiting for the user to choose which of them to open
+    archive_choice: Option<ArchiveChoice>,
+    nametable_viewer: NametableViewer,
+    cheats_open: bool,
+    new_cheat_name: String,
+    new_cheat_code: String,
+    cheat_error: Option<&'static str>,
+    pub netplay: NetplayWindow,
+    /// A change to the netplay session, waiting to be applied by the app
+    pub netplay_change: Option<SessionChange>,
+    pub achievements: AchievementsWindow,
+    /// New achievements to evaluate, waiting to be passed on by the app
+    pub achievements_change: Option<AchievementRuntime>,
+    pub profiler: ProfilerWindow,
+    watch: WatchWindow,
+    coverage: CoverageWindow,
+    ram_map: RamMapWindow,
+    memory_diff: MemoryDiffWindow,
+    disassembly: DisassemblyWindow,
+    /// A request to step to the next line of source, waiting to be run by the app
+    pub step_requested: bool,
+    /// An error loading a ROM or running emulation, waiting for the user to dismiss it
+    pub error: Option<String>,
+}
+
+impl UI {
+    pub fn new(config: Config) -> Self {
+        Self {
+            mem_editor: memory_editor(&[]),
+            annotated_entries: Vec::new(),
+            saved_config: config.clone(),
+            config,
+            settings_open: false,
+            rebinding: None,
+            open_rom_open: false,
+            rom_info_open: false,
+            open_rom_path: String::new(),
+            open_patch_path: String::new(),
+            requested_rom: None,
+            archive_choice: None,
+            nametable_viewer: NametableViewer::new(),
+            cheats_open: false,
+            new_cheat_name: String::new(),
+            new_cheat_code: String::new(),
+            cheat_error: None,
+            netplay: NetplayWindow::new(),
+            netplay_change: None,
+            achievements: AchievementsWindow::new(),
+            achievements_change: None,
+            profiler: ProfilerWindow::new(),
+            watch: WatchWindow::new(),
+            coverage: CoverageWindow::new(),
+            ram_map: RamMapWindow::new(),
+            memory_diff: MemoryDiffWindow::new(),
+            disassembly: DisassemblyWindow::new(),
+            step_requested: false,
+            error: None,
+        }
+    }
+
+    pub fn render(&mut self, ctx: &Context, nes: &mut NES, netplay_connected: bool) {
+        let scroll_origin = nes.ppu().scroll_origin();
+        let bus = nes.bus_mut();
+        TopBottomPanel::top("Menu").show(ctx, |ui| {
+            menu::bar(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    ui.checkbox(&mut self.open_rom_open, "Open ROM");
+                    ui.checkbox(&mut self.rom_info_open, "ROM Info");
+                    ui.checkbox(&mut self.settings_open, "Settings");
+                });
+                ui.menu_button("Debug", |ui| {
+                    let debug = &mut self.config.debug;
+                    ui.checkbox(&mut debug.tile_grid, "Tile Grid");
+                    ui.checkbox(&mut debug.sprite_boxes, "Sprite Bounding Boxes");
+                    ui.checkbox(&mut debug.nametable_viewer, "Nametable Viewer");
+                    ui.checkbox(&mut debug.memory_editor, "Memory Editor");
+                    ui.checkbox(&mut debug.profiler, "Profiler");
+                    ui.checkbox(&mut debug.watch, "Watch");
+                    ui.checkbox(&mut debug.coverage, "Coverage");
+                    ui.checkbox(&mut debug.ram_map, "RAM Map");
+                    ui.checkbox(&mut debug.memory_diff, "Memory Diff");
+                    ui.checkbox(&mut debug.disassembly, "Disassembly");
+                    ui.separator();
+                    ui.checkbox(&mut debug.break_on_nmi, "Break on NMI");
+                    ui.checkbox(&mut debug.break_on_irq, "Break on IRQ");
+                    ui.checkbox(&mut debug.break_on_brk, "Break on BRK");
+                    ui.checkbox(&mut debug.break_on_bank_switch, "Break on Bank Switch");
+                    ui.checkbox(&mut debug.mapper_writes, "Log Mapper Writes")
+                        .on_hover_text(
+                            "Logs every write to the cartridge, and the banks selected after it",
+                        );
+                });
+                ui.menu_button("Tools", |ui| {
+                    ui.checkbox(&mut self.cheats_open, "Game Genie");
+                    ui.checkbox(&mut self.netplay.open, "Netplay");
+                    ui.checkbox(&mut self.achievements.open, "Achievements");
+                });
+            });
+        });
+        self.render_cheats(ctx, &mut bus.cheats);
+        if let Some(change) = self.netplay.render(ctx, nes, netplay_connected) {
+            self.netplay_change = Some(change);
+        }
+        if let Some(runtime) = self.achievements.render(ctx, &mut self.config.achievements) {
+            self.achievements_change = Some(runtime);
+        }
+        let debug = &mut self.config.debug;
+        self.watch
+            .render(ctx, &mut debug.watch, &mut debug.watches, nes);
+        self.coverage.render(ctx, &mut debug.coverage, nes);
+        self.ram_map.render(ctx, &mut debug.ram_map, nes);
+        self.memory_diff.render(ctx, &mut debug.memory_diff, nes);
+        if self
+            .disassembly
+            .render(ctx, &mut debug.disassembly, nes, netplay_connected)
+        {
+            self.step_requested = true;
+        }
+        if nes.bus().ram_map.entries() != self.annotated_entries {
+            self.annotated_entries = nes.bus().ram_map.entries().to_vec();
+            self.mem_editor = memory_editor(&self.annotated_entries);
+        }
+        let bus = nes.bus_mut();
+        self.nametable_viewer.render(
+            ctx,
+            &mut self.config.debug.nametable_viewer,
+            bus,
+            scroll_origin,
+        );
+        self.profiler.render(ctx, &mut self.config.debug.profiler);
+        self.mem_editor.window_ui(
+            ctx,
+            &mut self.config.debug.memory_editor,
+            bus,
+            |bus, address| bus.cpu_read_byte_no_modify(address).ok(),
+            |bus, address, val| {
+                // Discard error result, this memory editor doesn't need it
+                let _ = bus.cpu_write_byte(address, val);
+            },
+        );
+        Window::new("Log").show(ctx, |ui| {
+            ui.style_mut().override_text_style = Some(eframe::egui::TextStyle::Monospace);
+            // draws the logger ui.
+            egui_logger::logger_ui(ui);
+        });
+        self.render_rom_info(ctx, nes);
+        self.render_settings(ctx);
+        self.render_open_rom(ctx);
+        self.render_archive_choice(ctx);
+        self.render_error(ctx);
+
+        // Wait until the user lets go of the mouse, so that dragging a slider doesn't save on every frame
+        if self.config != self.saved_config && !ctx.input(|info| info.pointer.any_down()) {
+            self.config.save();
+            self.saved_config = self.config.clone();
+        }
+    }
+
+    fn render_open_rom(&mut self, ctx: &Context) {
+        let mut requested_rom = None;
+        Window::new("Open ROM")
+            .open(&mut self.open_rom_open)
+            .show(ctx, |ui| {
+                let mut submitted = false;
+                Grid::new("Open ROM").show(ui, |ui| {
+                    ui.label("Path");
+                    let response = ui.text_edit_singleline(&mut self.open_rom_path);
+                    submitted |=
+                        response.lost_focus() && ui.input(|info| info.key_pressed(Key::Enter));
+                    ui.end_row();
+
+                    ui.label("Patch");
+                    let response = ui.text_edit_singleline(&mut self.open_patch_path)
+                        .on_hover_text("An IPS or BPS patch to apply. If left empty, a patch with the same name as the ROM is applied");
+                    submitted |=
+                        response.lost_focus() && ui.input(|info| info.key_pressed(Key::Enter));
+                    ui.end_row();
+                });
+                if (ui.button("Open").clicked() || submitted) && !self.open_rom_path.is_empty() {
+                    let patch_path = Some(self.open_patch_path.clone()).filter(|path| !path.is_empty());
+                    requested_rom = Some((self.open_rom_path.clone(), patch_path));
+                }
+
+                ui.separator();
+                ui.label("Recent");
+                for path in &self.config.paths.recent_roms {
+                    if ui.button(path).clicked() {
+                        requested_rom = Some((path.clone(), None));
+                    }
+                }
+            });
+
+        if let Some((rom_path, patch_path)) = requested_rom {
+            self.open_rom_open = false;
+            // Archives holding a single ROM are opened straight away, but otherwise the user picks one. Failing
+            // to list the ROMs is left for loading to report
+            let roms = Path::new(&rom_path)
+                .is_file()
+                .then(|| archive::list_roms(&rom_path).ok())
+                .flatten()
+                .filter(|roms| roms.len() > 1);
+            match roms {
+                Some(roms) => {
+                    self.archive_choice = Some(ArchiveChoice {
+                        archive_path: rom_path,
+                        patch_path,
+                        roms,
+                    })
+                }
+                None => self.requested_rom = Some((rom_path, patch_path)),
+            }
+        }
+    }
+
+    fn render_rom_info(&mut self, ctx: &Context, nes: &NES) {
+        let info = nes.rom_info();
+        Window::new("ROM Info")
+            .open(&mut self.rom_info_open)
+            .show(ctx, |ui| {
+                Grid::new("ROM Info").show(ui, |ui| {
+                    ui.label("Title");
+                    ui.label(info.title().unwrap_or("Unknown"));
+                    ui.end_row();
+
+                    ui.label("Dump");
+                    match info.dump_status() {
+                        DumpStatus::Verified => {
+                            ui.colored_label(Color32::GREEN, "Verified good dump")
+                        }
+                        DumpStatus::Bad => ui.colored_label(Color32::RED, "Known bad dump"),
+                        DumpStatus::Unknown => ui.label("Not in the database"),
+                    };
+                    ui.end_row();
+
+                    ui.label("CRC32");
+                    ui.monospace(format!("{:08x}", info.hash.crc32));
+                    ui.end_row();
+
+                    ui.label("SHA-1");
+                    ui.monospace(info.hash.sha1_hex());
+                    ui.end_row();
+
+                    ui.label("Patch");
+                    ui.label(nes.patch_path().unwrap_or("None"));
+                    ui.end_row();
+
+                    ui.label("Mapper");
+                    ui.label(format!("{} (submapper {})", info.mapper, info.submapper));
+                    ui.end_row();
+
+                    ui.label("Region");
+                    ui.label(format!("{:?}", info.region));
+                    ui.end_row();
+
+                    ui.label("Controller");
+                    ui.label(format!("{:?}", info.controller));
+                    ui.end_row();
+
+                    ui.label("Overrides");
+                    ui.label(if info.overridden { "Applied" } else { "None" });
+                    ui.end_row();
+                });
+            });
+    }
+
+    fn render_archive_choice(&mut self, ctx: &Context) {
+        let Some(choice) = &self.archive_choice else {
+            return;
+        };
+        let mut open = true;
+        let mut chosen = None;
+        Window::new("Choose ROM").open(&mut open).show(ctx, |ui| {
+            ui.label(format!("{} holds several ROMs", choice.archive_path));
+            for rom in &choice.roms {
+                if ui.button(rom).clicked() {
+                    chosen = Some(archive::entry_path(&choice.archive_path, rom));
+                }
+            }
+        });
+
+        if let Some(rom_path) = chosen {
+            self.requested_rom = Some((rom_path, choice.patch_path.clone()));
+            self.archive_choice = None;
+        } else if !open {
+            self.archive_choice = None;
+        }
+    }
+
+    fn render_error(&mut self, ctx: &Context) {
+        let Some(error) = &self.error else {
+            return;
+        };
+        let mut open = true;
+        let mut dismissed = false;
+        Window::new("Error")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.colored_label(Color32::RED, error);
+                ui.horizontal(|ui| {
+                    if ui.button("Open ROM").clicked() {
+                        self.open_rom_open = true;
+                        dismissed = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        dismissed = true;
+                    }
+                });
+            });
+
+        if dismissed || !open {
+            self.error = None;
+        }
+    }
+
+    fn render_settings(&mut self, ctx: &Context) {
+        // A binding waiting to be changed takes the next key that is pressed
+        if let Some(idx) = self.rebinding {
+            let pressed = ctx.input(|info| {
+                info.events.iter().find_map(|event| match event {
+                    eframe::egui::Event::Key {
+                        key, pressed: true, ..
+                    } => Some(*key),
+                    _ => None,
+                })
+            });
+            if let Some(key) = pressed {
+                *self.config.input.bindings_mut()[idx].1 = key;
+                self.rebinding = None;
+            }
+        }
+
+        Window::new("Settings")
+            .open(&mut self.settings_open)
+            .show(ctx, |ui| {
+                ui.heading("Video");
+                let video = &mut self.config.video;
+                ui.add(Slider::new(&mut video.scale, 1.0..=4.0).text("Scale"));
+                ui.checkbox(&mut video.smooth, "Smooth scaling");
+                ui.checkbox(
+                    &mut video.vsync,
+                    "Pace to VSync (60Hz displays, requires restart)",
+                );
+                ui.add(Slider::new(&mut video.frameskip, 0..=9).text("Frameskip"));
+
+                ui.heading("Emulation");
+                ui.add(
+                    Slider::new(&mut self.config.emulation.extra_scanlines, 0..=262)
+                        .text("Overclock (extra scanlines)"),
+                )
+                .on_hover_text(
+                    "Gives the CPU more time each frame. Must match the other player's during netplay",
+                );
+
+                ui.heading("Saves");
+                ui.add(
+                    Slider::new(&mut self.config.saves.interval, 0..=300)
+                        .text("Seconds between saves (0 saves on exit only)"),
+                );
+
+                ui.heading("Audio");
+                ui.label("Audio is not emulated yet");
+                let audio = &mut self.config.audio;
+                ui.checkbox(&mut audio.muted, "Mute");
+                ui.add(Slider::new(&mut audio.volume, 0.0..=1.0).text("Volume"));
+
+                ui.heading("Discord");
+                let discord = &mut self.config.discord;
+                ui.checkbox(&mut discord.enabled, "Show the current game on Discord");
+                ui.horizontal(|ui| {
+                    ui.label("Application ID");
+                    ui.text_edit_singleline(&mut discord.application_id);
+                });
+
+                ui.heading("LiveSplit");
+                let livesplit = &mut self.config.livesplit;
+                ui.checkbox(
+                    &mut livesplit.enabled,
+                    "Autosplit using the game's splits file",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Server address");
+                    ui.text_edit_singleline(&mut livesplit.address);
+                });
+
+                ui.heading("Input");
+                Grid::new("Bindings").striped(true).show(ui, |ui| {
+                    for (idx, (name, key)) in
+                        self.config.input.bindings_mut().into_iter().enumerate()
+                    {
+                        ui.label(name);
+                        let text = if self.rebinding == Some(idx) {
+                            "Press a key..."
+                        } else {
+                            key.name()
+                        };
+                        if ui.button(text).clicked() {
+                            self.rebinding = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+                let input = &mut self.config.input;
+                let zapper_x = Slider::new(&mut input.zapper_offset_x, -16.0..=16.0);
+                ui.add(zapper_x.text("Zapper X offset")).on_hover_text(
+                    "Moves the Zapper's aim, and its crosshair, relative to the pointer",
+                );
+                let zapper_y = Slider::new(&mut input.zapper_offset_y, -16.0..=16.0);
+                ui.add(zapper_y.text("Zapper Y offset"));
+            });
+    }
+
+    fn render_cheats(&mut self, ctx: &Context, cheats: &mut CheatEngine) {
+        let mut changed = false;
+        Window::new("Game Genie")
+            .open(&mut self.cheats_open)
+            .show(ctx, |ui| {
+                let mut removed = None;
+                Grid::new("Cheats").striped(true).show(ui, |ui| {
+                    for (i, cheat) in cheats.cheats_mut().iter_mut().enumerate() {
+                        changed |= ui.checkbox(&mut cheat.enabled, "").changed();
+                        ui.monospace(&cheat.code);
+                        changed |= ui.text_edit_singleline(&mut cheat.name).lost_focus();
+                        if ui.button("Remove").clicked() {
+                            removed = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(idx) = removed {
+                    cheats.remove(idx);
+                    changed = true;
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Code");
+                    ui.add(TextEdit::singleline(&mut self.new_cheat_code).desired_width(80.0));
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.new_cheat_name);
+                    if ui.button("Add").clicked() {
+                        match cheats.add(&self.new_cheat_name, &self.new_cheat_code, true) {
+                            Ok(()) => {
+                                self.new_cheat_name.clear();
+                                self.new_cheat_code.clear();
+                                self.cheat_error = None;
+                                changed = true;
+                            }
+                            Err(error) => self.cheat_error = Some(error),
+                        }
+                    }
+                });
+                if let Some(error) = self.cheat_error {
+                    ui.colored_label(Color32::RED, error);
+                }
+            });
+
+        if changed {
+            if let Err(error) = cheats.save() {
+                log::error!("Failed to save cheats with error: {}", error);
+            }
+        }
+    }
+}
+
+/// Creates the memory editor, with an address range for each RAM map entry so that the variables can be found by
+/// name
+fn memory_editor(entries: &[RamEntry]) -> MemoryEditor {
+    let mut editor = MemoryEditor::new()
+        .with_address_range("All", 0..0xFFFF)
+        .with_address_range("RAM", 0..0x0800)
+        .with_window_title("Memory");
+    for entry in entries {
+        let start = entry.address as usize;
+        editor.set_address_range(
+            format!("{} (${:04X})", entry.name, entry.address),
+            start..start + entry.value_type.size(),
+        );
+    }
+    editor
+}
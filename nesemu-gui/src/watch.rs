@@ -0,0 +1,94 @@
+//! Debug window showing the values of expressions over the CPU registers and memory, re-evaluated every frame.
+//! Expressions are described in [`nesemu_core::expression`], and are kept in the config so that they are still
+//! there the next time the emulator is started.
+
+use eframe::{
+    egui::{Context, Grid, Key, Window},
+    epaint::Color32,
+};
+
+use nesemu_core::{expression::Expression, NES};
+
+pub struct WatchWindow {
+    new_expression: String,
+    error: Option<&'static str>,
+}
+
+impl WatchWindow {
+    pub fn new() -> Self {
+        Self {
+            new_expression: String::new(),
+            error: None,
+        }
+    }
+
+    /// Draws the watch window, if it is open
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        open: &mut bool,
+        watches: &mut Vec<String>,
+        nes: &mut NES,
+    ) {
+        if !*open {
+            return;
+        }
+
+        Window::new("Watch").open(open).show(ctx, |ui| {
+            let mut removed = None;
+            Grid::new("Watches").striped(true).show(ui, |ui| {
+                for (i, text) in watches.iter().enumerate() {
+                    let response = ui.monospace(text);
+                    if let Some(entry) = nes.bus().ram_map.find(text.trim()) {
+                        response.on_hover_text(&entry.description);
+                    }
+                    // Expressions are checked when added, so they only fail to evaluate, such as by dividing by 0
+                    // or using a name that was removed from the RAM map
+                    match Expression::parse(text).and_then(|expression| expression.evaluate(nes)) {
+                        Ok(value) => ui.monospace(format_value(value)),
+                        Err(error) => ui.colored_label(Color32::RED, error),
+                    };
+                    if ui.button("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = removed {
+                watches.remove(i);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let response = ui
+                    .text_edit_singleline(&mut self.new_expression)
+                    .on_hover_text(
+                        "For example [$0300]+[$0301]*256. Registers are A, X, Y, P, SP and PC, and other names come from the RAM map",
+                    );
+                let submitted =
+                    response.lost_focus() && ui.input(|info| info.key_pressed(Key::Enter));
+                if ui.button("Add").clicked() || submitted {
+                    match Expression::parse(&self.new_expression) {
+                        Ok(_) => {
+                            watches.push(std::mem::take(&mut self.new_expression));
+                            self.error = None;
+                        }
+                        Err(error) => self.error = Some(error),
+                    }
+                }
+            });
+            if let Some(error) = self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+    }
+}
+
+/// Shows values that fit in a byte or a word in hex as well as decimal
+fn format_value(value: i64) -> String {
+    match value {
+        0..=0xFF => format!("${:02X} ({})", value, value),
+        0x100..=0xFFFF => format!("${:04X} ({})", value, value),
+        _ => value.to_string(),
+    }
+}
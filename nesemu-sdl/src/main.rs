@@ -0,0 +1,160 @@
+//! A minimal alternative frontend built on SDL2. It has none of the debugging tools of the egui frontend, just
+//! the game image and keyboard input, using the same key bindings.
+
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use bitfield::BitMut;
+use nesemu_core::{
+    controller::InputEvent,
+    screen::{Frame, SCREEN_HEIGHT, SCREEN_WIDTH},
+    RunResult, NES,
+};
+use sdl2::{
+    event::Event,
+    keyboard::{KeyboardState, Keycode, Mod, Scancode},
+    pixels::PixelFormatEnum,
+};
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        println!(
+            "Missing rom path! Usage: cargo run -p nesemu-sdl --features sdl -- <path/to/rom>"
+        );
+        std::process::exit(-1);
+    }
+
+    let mut nes = NES::new(args[1].clone())
+        .map_err(|error| format!("failed to initialize NES with error: {}", error))?;
+
+    let sdl = sdl2::init()?;
+    let window = sdl
+        .video()?
+        .window("NESEmu", 2 * SCREEN_WIDTH as u32, 2 * SCREEN_HEIGHT as u32)
+        .position_centered()
+        .resizable()
+        .build()
+        .map_err(|error| error.to_string())?;
+    let mut canvas = window
+        .into_canvas()
+        .build()
+        .map_err(|error| error.to_string())?;
+    canvas
+        .set_logical_size(SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+        .map_err(|error| error.to_string())?;
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator
+        .create_texture_streaming(
+            PixelFormatEnum::RGB24,
+            SCREEN_WIDTH as u32,
+            SCREEN_HEIGHT as u32,
+        )
+        .map_err(|error| error.to_string())?;
+    let mut event_pump = sdl.event_pump()?;
+
+    let mut frame = Frame::new();
+    let mut halt = false;
+    let mut frame_advance = false;
+    'running: loop {
+        let frame_start = Instant::now();
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    repeat: false,
+                    ..
+                } => halt = !halt,
+                Event::KeyDown {
+                    keycode: Some(Keycode::N),
+                    repeat: false,
+                    ..
+                } if halt => frame_advance = true,
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    keymod,
+                    repeat: false,
+                    ..
+                } => {
+                    let result = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) {
+                        nes.power_cycle().map_err(|error| error.to_string())
+                    } else {
+                        nes.soft_reset().map_err(|error| error.to_string())
+                    };
+                    if let Err(error) = result {
+                        halt = true;
+                        eprintln!("Reset failed with error: {}", error);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let input_event = read_controller(&event_pump.keyboard_state());
+        nes.bus_mut().controller.set_state_from_window(input_event);
+
+        // While halted, a frame advance request runs exactly one frame before pausing again
+        if !halt || frame_advance {
+            frame_advance = false;
+            match nes.run_frame(&mut frame) {
+                Ok(RunResult::FrameComplete) => {}
+                Ok(RunResult::Break(reason)) => {
+                    halt = true;
+                    println!("{}", reason);
+                }
+                Err(error) => {
+                    halt = true;
+                    eprintln!("Emulation failed with error: {}", error);
+                }
+            }
+        }
+
+        texture.with_lock(None, |buffer, pitch| {
+            for (y, row) in frame.pixels.chunks(SCREEN_WIDTH).enumerate() {
+                for (x, pixel) in row.iter().enumerate() {
+                    let offset = y * pitch + x * 3;
+                    buffer[offset..offset + 3].copy_from_slice(&[pixel.r, pixel.g, pixel.b]);
+                }
+            }
+        })?;
+        canvas.clear();
+        canvas.copy(&texture, None, None)?;
+        canvas.present();
+
+        let ft = Duration::from_secs_f64(NES::FRAME_TIME);
+        let duration = Instant::now() - frame_start;
+        if ft > duration {
+            spin_sleep::sleep(ft - duration);
+        }
+    }
+
+    Ok(())
+}
+
+// TODO: Dehardcode keys
+fn read_controller(keys: &KeyboardState) -> InputEvent {
+    let mut event = InputEvent { input_state: 0 };
+    let bindings = [
+        (InputEvent::RIGHT, Scancode::Right),
+        (InputEvent::LEFT, Scancode::Left),
+        (InputEvent::DOWN, Scancode::Down),
+        (InputEvent::UP, Scancode::Up),
+        (InputEvent::START, Scancode::Return),
+        (InputEvent::SELECT, Scancode::Backspace),
+        (InputEvent::B, Scancode::Z),
+        (InputEvent::A, Scancode::X),
+    ];
+    for (button, scancode) in bindings {
+        event
+            .input_state
+            .set_bit(button as usize, keys.is_scancode_pressed(scancode));
+    }
+    event
+}